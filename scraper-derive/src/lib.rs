@@ -0,0 +1,257 @@
+//! Derive macro for [`scraper::scrape::Scrape`].
+//!
+//! Use it through the `derive` feature of `scraper`, which re-exports it as
+//! `scraper::scrape::Scrape`.
+//!
+//! [`scraper::scrape::Scrape`]: https://docs.rs/scraper/latest/scraper/scrape/trait.Scrape.html
+
+#![warn(
+    missing_docs,
+    missing_debug_implementations,
+    missing_copy_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    variant_size_differences
+)]
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericArgument, LitStr, Path, PathArguments, Type,
+    parse_macro_input,
+};
+
+mod selector;
+
+/// Derives `scraper::scrape::Scrape` for a struct with named fields.
+///
+/// Each field takes a `#[scrape(...)]` attribute with:
+///
+/// - `select = "..."`: the CSS selector of the elements the field is extracted from, among the
+///   descendants of the element being scraped. It is checked when the struct is compiled.
+///   Without it, the field is extracted from the element itself.
+/// - One extraction mode: `text` (the default), `attr = "..."`, `html`, `inner_html` or
+///   `nested`, which extracts a struct that implements `Scrape` itself.
+/// - Optionally `with = path::to::function`, a function from `&str` to `Result<T, E>` with an
+///   error that implements `Display`, to use instead of `FromStr`.
+///
+/// A field of type `Option<T>` is `None` without a matching element or attribute, a field of
+/// type `Vec<T>` collects all matching elements that have the attribute, and a field of any
+/// other type requires a match.
+#[proc_macro_derive(Scrape, attributes(scrape))]
+pub fn derive_scrape(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How the value of a field is taken from an element.
+enum Mode {
+    Text,
+    Attr(LitStr),
+    Html,
+    InnerHtml,
+    Nested,
+}
+
+/// How many elements a field is extracted from.
+enum Cardinality {
+    One,
+    Optional,
+    All,
+}
+
+struct FieldOptions {
+    select: Option<LitStr>,
+    mode: Mode,
+    with: Option<Path>,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            input,
+            "Scrape can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &data.fields,
+            "Scrape can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut initializers = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        let options = field_options(field)?;
+        let value = field_value(name, ident, &field.ty, &options);
+        initializers.push(quote!(#ident: #value));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::scraper::scrape::Scrape for #name #ty_generics #where_clause {
+            fn from_element(
+                element: ::scraper::ElementRef<'_>,
+            ) -> ::std::result::Result<Self, ::scraper::error::ScrapeError> {
+                ::std::result::Result::Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+fn field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions {
+        select: None,
+        mode: Mode::Text,
+        with: None,
+    };
+    let mut found = false;
+    let mut mode_set = false;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("scrape"))
+    {
+        found = true;
+        attr.parse_nested_meta(|meta| {
+            let mut set_mode = |mode: Mode| {
+                if mode_set {
+                    return Err(meta.error("only one extraction mode can be given"));
+                }
+                mode_set = true;
+                options.mode = mode;
+                Ok(())
+            };
+
+            if meta.path.is_ident("select") {
+                let selector: LitStr = meta.value()?.parse()?;
+                if let Err(message) = selector::validate(&selector.value()) {
+                    return Err(Error::new_spanned(
+                        &selector,
+                        format!("invalid selector: {message}"),
+                    ));
+                }
+                options.select = Some(selector);
+                Ok(())
+            } else if meta.path.is_ident("text") {
+                set_mode(Mode::Text)
+            } else if meta.path.is_ident("attr") {
+                let name: LitStr = meta.value()?.parse()?;
+                set_mode(Mode::Attr(name))
+            } else if meta.path.is_ident("html") {
+                set_mode(Mode::Html)
+            } else if meta.path.is_ident("inner_html") {
+                set_mode(Mode::InnerHtml)
+            } else if meta.path.is_ident("nested") {
+                set_mode(Mode::Nested)
+            } else if meta.path.is_ident("with") {
+                options.with = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "expected `select`, `text`, `attr`, `html`, `inner_html`, `nested` or `with`",
+                ))
+            }
+        })?;
+    }
+
+    if !found {
+        return Err(Error::new_spanned(
+            field,
+            "missing `#[scrape(...)]` attribute",
+        ));
+    }
+    if let (Mode::Nested, Some(with)) = (&options.mode, &options.with) {
+        return Err(Error::new_spanned(
+            with,
+            "`with` cannot be used with `nested`",
+        ));
+    }
+    Ok(options)
+}
+
+/// Returns the cardinality of a field and the type of each value.
+fn cardinality(ty: &Type) -> (Cardinality, &Type) {
+    if let Type::Path(path) = ty
+        && path.qself.is_none()
+        && let Some(segment) = path.path.segments.last()
+        && let PathArguments::AngleBracketed(arguments) = &segment.arguments
+        && arguments.args.len() == 1
+        && let Some(GenericArgument::Type(inner)) = arguments.args.first()
+    {
+        if segment.ident == "Option" {
+            return (Cardinality::Optional, inner);
+        }
+        if segment.ident == "Vec" {
+            return (Cardinality::All, inner);
+        }
+    }
+    (Cardinality::One, ty)
+}
+
+fn field_value(
+    name: &syn::Ident,
+    field: &syn::Ident,
+    ty: &Type,
+    options: &FieldOptions,
+) -> TokenStream {
+    let private = quote!(::scraper::scrape::__private);
+    let (cardinality, value_ty) = cardinality(ty);
+
+    let elements = match &options.select {
+        Some(selector) => quote! {{
+            static SELECTOR: #private::OnceLock<::scraper::Selector> = #private::OnceLock::new();
+            element.select(#private::selector(&SELECTOR, #selector))
+        }},
+        None => quote!(::std::iter::once(element)),
+    };
+
+    let source = match &options.mode {
+        Mode::Text => quote!(&#private::text(element)),
+        Mode::Attr(attr) => quote!(#private::attr(element, #attr)?),
+        Mode::Html => quote!(&element.html()),
+        Mode::InnerHtml => quote!(&element.inner_html()),
+        Mode::Nested => quote!(),
+    };
+    let convert = match (&options.mode, &options.with) {
+        (Mode::Nested, _) => quote!(#private::nested::<#value_ty>(element)),
+        (_, Some(with)) => quote!(#private::convert::<#value_ty, _>(#with(#source))),
+        (_, None) => quote!(#private::parse::<#value_ty>(#source)),
+    };
+    let extract = quote! {
+        |element: ::scraper::ElementRef<'_>| -> ::std::result::Result<#value_ty, ::scraper::error::ScrapeErrorKind> {
+            #convert
+        }
+    };
+
+    let collect = match cardinality {
+        Cardinality::One => quote!(#private::one),
+        Cardinality::Optional => quote!(#private::optional),
+        Cardinality::All => quote!(#private::all),
+    };
+    let type_name = name.to_string();
+    let field_name = field.to_string();
+    let selector = match &options.select {
+        Some(selector) => quote!(::std::option::Option::Some(#selector)),
+        None => quote!(::std::option::Option::None),
+    };
+
+    quote! {
+        #collect(#elements, #extract).map_err(|kind| ::scraper::error::ScrapeError {
+            ty: #type_name,
+            field: #field_name,
+            selector: #selector,
+            kind,
+        })?
+    }
+}
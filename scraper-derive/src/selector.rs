@@ -0,0 +1,142 @@
+//! Compile-time validation of selectors.
+//!
+//! Parses selectors with the same `selectors` options as `scraper::Selector`, but with plain
+//! strings in place of the atoms of `html5ever`, which a macro does not need.
+
+use std::fmt;
+
+use cssparser::ToCss;
+use precomputed_hash::PrecomputedHash;
+use selectors::parser::{self, ParseRelative, SelectorList, SelectorParseErrorKind};
+
+/// Returns a description of the error if a selector group is invalid.
+pub(crate) fn validate(selectors: &str) -> Result<(), String> {
+    let mut input = cssparser::ParserInput::new(selectors);
+    let mut parser = cssparser::Parser::new(&mut input);
+    SelectorList::<Impl>::parse(&Parser, &mut parser, ParseRelative::No)
+        .map(drop)
+        .map_err(|error| format!("{:?}", error.kind))
+}
+
+struct Parser;
+
+impl<'i> parser::Parser<'i> for Parser {
+    type Impl = Impl;
+    type Error = SelectorParseErrorKind<'i>;
+
+    fn parse_is_and_where(&self) -> bool {
+        true
+    }
+
+    fn parse_has(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Impl;
+
+impl parser::SelectorImpl for Impl {
+    type AttrValue = Name;
+    type Identifier = Name;
+    type LocalName = Name;
+    type NamespacePrefix = Name;
+    type NamespaceUrl = Name;
+    type BorrowedNamespaceUrl = Name;
+    type BorrowedLocalName = Name;
+
+    type NonTSPseudoClass = NonTSPseudoClass;
+    type PseudoElement = PseudoElement;
+
+    type ExtraMatchingData<'a> = ();
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Name(String);
+
+impl From<&str> for Name {
+    fn from(value: &str) -> Self {
+        Name(value.to_owned())
+    }
+}
+
+impl ToCss for Name {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_str(&self.0)
+    }
+}
+
+impl PrecomputedHash for Name {
+    fn precomputed_hash(&self) -> u32 {
+        0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NonTSPseudoClass {}
+
+impl parser::NonTSPseudoClass for NonTSPseudoClass {
+    type Impl = Impl;
+
+    fn is_active_or_hover(&self) -> bool {
+        false
+    }
+
+    fn is_user_action_state(&self) -> bool {
+        false
+    }
+}
+
+impl ToCss for NonTSPseudoClass {
+    fn to_css<W: fmt::Write>(&self, _dest: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PseudoElement {}
+
+impl parser::PseudoElement for PseudoElement {
+    type Impl = Impl;
+}
+
+impl ToCss for PseudoElement {
+    fn to_css<W: fmt::Write>(&self, _dest: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+
+    #[test]
+    fn same_as_runtime_parsing() {
+        let selectors = [
+            "h1",
+            "div > p.a#b, li + li ~ li",
+            "a[href^='https' i]",
+            ":is(h1, h2):not(.x)",
+            "li:has(> a)",
+            "p:nth-child(2n + 1 of .x)",
+            "*|a",
+            ":scope > p",
+            "",
+            "h1[",
+            "a,",
+            "svg|rect",
+            "a:hover",
+            "p::before",
+            "p:nth-child(x)",
+            ":has(:has(a))",
+            ":where(",
+        ];
+        for selector in selectors {
+            assert_eq!(
+                validate(selector).is_ok(),
+                scraper::Selector::parse(selector).is_ok(),
+                "{selector:?}"
+            );
+        }
+    }
+}
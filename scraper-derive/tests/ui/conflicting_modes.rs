@@ -0,0 +1,9 @@
+use scraper::scrape::Scrape;
+
+#[derive(Scrape)]
+struct Product {
+    #[scrape(select = "img", attr = "src", html)]
+    image: String,
+}
+
+fn main() {}
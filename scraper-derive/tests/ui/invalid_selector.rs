@@ -0,0 +1,9 @@
+use scraper::scrape::Scrape;
+
+#[derive(Scrape)]
+struct Product {
+    #[scrape(select = "h1[")]
+    name: String,
+}
+
+fn main() {}
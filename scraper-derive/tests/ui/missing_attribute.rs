@@ -0,0 +1,10 @@
+use scraper::scrape::Scrape;
+
+#[derive(Scrape)]
+struct Product {
+    #[scrape(select = "h1")]
+    name: String,
+    price: u32,
+}
+
+fn main() {}
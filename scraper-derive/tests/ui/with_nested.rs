@@ -0,0 +1,19 @@
+use scraper::scrape::Scrape;
+
+#[derive(Scrape)]
+struct Review {
+    #[scrape(select = ".stars")]
+    stars: u8,
+}
+
+#[derive(Scrape)]
+struct Product {
+    #[scrape(select = ".review", nested, with = parse_review)]
+    review: Review,
+}
+
+fn parse_review(_: &str) -> Result<Review, String> {
+    Err(String::new())
+}
+
+fn main() {}
@@ -0,0 +1,336 @@
+//! Rendered text extraction, after the HTML `innerText` algorithm.
+
+use ego_tree::NodeRef;
+use ego_tree::iter::Edge;
+
+use super::{ElementRef, is_template_contents};
+use crate::Node;
+use crate::node::Element;
+
+/// Options for [`ElementRef::inner_text_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InnerTextOptions {
+    /// Only use the text nodes that are children of the element, ignoring descendant elements.
+    pub own_text: bool,
+
+    /// Include the contents of elements with a `hidden` attribute.
+    pub include_hidden: bool,
+
+    /// Keep whitespace as it is in the source instead of collapsing it as `white-space: normal`
+    /// does.
+    pub preserve_whitespace: bool,
+}
+
+/// Elements that are not rendered, so their contents are skipped.
+const NOT_RENDERED: &[&str] = &[
+    "area", "audio", "base", "canvas", "datalist", "embed", "head", "iframe", "input", "link",
+    "meta", "noembed", "noframes", "noscript", "object", "param", "rp", "script", "select",
+    "source", "style", "template", "textarea", "title", "track", "video",
+];
+
+/// Elements that are displayed as blocks, and so are on lines of their own.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "caption",
+    "center",
+    "dd",
+    "details",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "legend",
+    "li",
+    "listing",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "plaintext",
+    "pre",
+    "search",
+    "section",
+    "summary",
+    "table",
+    "ul",
+    "xmp",
+];
+
+/// Elements whose whitespace is preserved.
+const PREFORMATTED: &[&str] = &["listing", "plaintext", "pre", "xmp"];
+
+impl ElementRef<'_> {
+    /// Returns the text of this element as it would be rendered, like the DOM's `innerText`.
+    ///
+    /// Unlike [`ElementRef::text`], whitespace is collapsed, block elements and `<br>` start new
+    /// lines, paragraphs are separated by blank lines, table cells by tabs and rows by newlines,
+    /// and the contents of `<script>`, `<style>`, `<template>` and `hidden` elements are skipped.
+    /// As no styles are applied, the elements displayed as blocks are those that browsers display
+    /// as blocks by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let fragment = Html::parse_fragment(
+    ///     "<h1>Title</h1><p>Some\n   <b>bold</b> text.<br>Next line.</p><script>ignored()</script>",
+    /// );
+    /// assert_eq!(
+    ///     fragment.root_element().inner_text(),
+    ///     "Title\n\nSome bold text.\nNext line."
+    /// );
+    /// ```
+    pub fn inner_text(&self) -> String {
+        self.inner_text_with(InnerTextOptions::default())
+    }
+
+    /// Returns the text of this element as it would be rendered, using the given options.
+    pub fn inner_text_with(&self, options: InnerTextOptions) -> String {
+        let mut text = RenderedText::default();
+        let mut skipped = None;
+        let mut preformatted = 0_usize;
+
+        for edge in self.traverse() {
+            match edge {
+                Edge::Open(node) if skipped.is_none() && is_template_contents(node) => {
+                    skipped = Some(node.id());
+                }
+                Edge::Open(node) if skipped.is_none() => match node.value() {
+                    Node::Text(t) => {
+                        text.push_text(t, options.preserve_whitespace || preformatted > 0)
+                    }
+                    Node::Element(element) if node != **self => {
+                        if options.own_text || !is_rendered(element, options) {
+                            skipped = Some(node.id());
+                            continue;
+                        }
+                        match element.name() {
+                            "br" => text.push_literal('\n'),
+                            "p" => text.require_breaks(2),
+                            name if BLOCKS.contains(&name) => text.require_breaks(1),
+                            _ => {}
+                        }
+                        if PREFORMATTED.contains(&element.name()) {
+                            preformatted += 1;
+                        }
+                    }
+                    _ => {}
+                },
+                Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+                Edge::Close(node) if skipped.is_none() && node != **self => {
+                    let Some(element) = node.value().as_element() else {
+                        continue;
+                    };
+                    match element.name() {
+                        "p" => text.require_breaks(2),
+                        "td" | "th" if next_cell(node) => text.push_literal('\t'),
+                        "tr" if next_row(node) => text.push_literal('\n'),
+                        name if BLOCKS.contains(&name) => text.require_breaks(1),
+                        _ => {}
+                    }
+                    if PREFORMATTED.contains(&element.name()) {
+                        preformatted -= 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        text.output
+    }
+}
+
+fn is_rendered(element: &Element, options: InnerTextOptions) -> bool {
+    !NOT_RENDERED.contains(&element.name())
+        && (options.include_hidden || element.attr("hidden").is_none())
+}
+
+fn is_element(node: &NodeRef<'_, Node>, names: &[&str]) -> bool {
+    node.value()
+        .as_element()
+        .is_some_and(|element| names.contains(&element.name()))
+}
+
+/// Returns true if a table cell is followed by another cell in its row.
+fn next_cell(cell: NodeRef<'_, Node>) -> bool {
+    cell.next_siblings()
+        .any(|sibling| is_element(&sibling, &["td", "th"]))
+}
+
+/// Returns true if a table row is followed by another row in its table.
+fn next_row(row: NodeRef<'_, Node>) -> bool {
+    let sections = ["thead", "tbody", "tfoot"];
+    row.next_siblings()
+        .any(|sibling| is_element(&sibling, &["tr"]))
+        || row.parent().is_some_and(|section| {
+            is_element(&section, &sections)
+                && section.next_siblings().any(|sibling| {
+                    is_element(&sibling, &sections)
+                        && sibling.children().any(|row| is_element(&row, &["tr"]))
+                })
+        })
+}
+
+/// Collects rendered text, collapsing whitespace and line breaks.
+#[derive(Debug, Default)]
+struct RenderedText {
+    output: String,
+    /// Newlines required before any further text, which are dropped at the start and end.
+    breaks: usize,
+    /// Whether collapsed whitespace is waiting to be written as a single space.
+    space: bool,
+    /// Whether the output is at the start of a line, where collapsed whitespace is dropped.
+    line_start: bool,
+}
+
+impl RenderedText {
+    fn require_breaks(&mut self, count: usize) {
+        self.breaks = self.breaks.max(count);
+        self.space = false;
+    }
+
+    fn flush_breaks(&mut self) {
+        if self.breaks > 0 {
+            if !self.output.is_empty() {
+                self.output.extend(std::iter::repeat_n('\n', self.breaks));
+                self.line_start = true;
+            }
+            self.breaks = 0;
+            self.space = false;
+        }
+    }
+
+    fn push_literal(&mut self, c: char) {
+        self.flush_breaks();
+        self.space = false;
+        self.output.push(c);
+        self.line_start = true;
+    }
+
+    fn push_text(&mut self, text: &str, preserve_whitespace: bool) {
+        if preserve_whitespace {
+            if !text.is_empty() {
+                self.flush_breaks();
+                self.output.push_str(text);
+                self.space = false;
+                self.line_start = text.ends_with('\n');
+            }
+            return;
+        }
+
+        for c in text.chars() {
+            if c.is_ascii_whitespace() {
+                self.space = true;
+                continue;
+            }
+
+            self.flush_breaks();
+            if self.space && !self.line_start && !self.output.is_empty() {
+                self.output.push(' ');
+            }
+            self.space = false;
+            self.line_start = false;
+            self.output.push(c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InnerTextOptions;
+    use crate::Html;
+
+    fn inner_text(html: &str) -> String {
+        Html::parse_fragment(html).root_element().inner_text()
+    }
+
+    #[test]
+    fn blocks_paragraphs_and_breaks() {
+        assert_eq!(
+            inner_text(
+                "<div><p>Hello <b>world</b>!</p><p>Second\n   para</p>\
+                 <script>x</script><style>y</style>Tail<br>line</div>"
+            ),
+            "Hello world!\n\nSecond para\n\nTail\nline"
+        );
+        assert_eq!(
+            inner_text("<ul>\n  <li>one </li>\n  <li> two</li>\n</ul>  after"),
+            "one\ntwo\nafter"
+        );
+        assert_eq!(inner_text("<br>a<br><br>"), "\na\n\n");
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            inner_text(
+                "<table><thead><tr><th>a</th> <th>b</th></tr></thead>\n\
+                 <tbody><tr><td>1</td><td> 2 </td></tr><tr><td>3</td></tr></tbody></table>"
+            ),
+            "a\tb\n1\t2\n3"
+        );
+    }
+
+    #[test]
+    fn hidden_and_template_contents() {
+        let html = "<p>a<span hidden>b</span><template>c</template>d</p>";
+        assert_eq!(inner_text(html), "ad");
+
+        let options = InnerTextOptions {
+            include_hidden: true,
+            ..Default::default()
+        };
+        let fragment = Html::parse_fragment(html);
+        assert_eq!(fragment.root_element().inner_text_with(options), "abd");
+
+        let fragment = Html::parse_fragment("<template><p>inside</p></template>");
+        let template = fragment.root_element().first_child().unwrap();
+        let template = crate::ElementRef::wrap(template).unwrap();
+        assert_eq!(template.inner_text(), "");
+    }
+
+    #[test]
+    fn whitespace() {
+        assert_eq!(
+            inner_text("<div>x<pre>  a\n  b</pre>y</div>"),
+            "x\n  a\n  b\ny"
+        );
+
+        let fragment = Html::parse_fragment("<p> a\n  <b>b</b> c </p>");
+        let p = fragment.root_element().child_elements().next().unwrap();
+        assert_eq!(p.inner_text(), "a b c");
+
+        let options = InnerTextOptions {
+            preserve_whitespace: true,
+            ..Default::default()
+        };
+        assert_eq!(p.inner_text_with(options), " a\n  b c ");
+
+        let options = InnerTextOptions {
+            own_text: true,
+            ..Default::default()
+        };
+        assert_eq!(p.inner_text_with(options), "a c");
+    }
+}
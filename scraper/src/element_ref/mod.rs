@@ -6,10 +6,16 @@ use std::ops::Deref;
 
 use ego_tree::NodeRef;
 use ego_tree::iter::{Edge, Traverse};
+#[cfg(feature = "pretty")]
+use html5ever::serialize::Serialize;
 use html5ever::serialize::{SerializeOpts, TraversalScope, serialize};
 use selectors::matching::SelectorCaches;
 
+use crate::html::SerializeOptions;
 use crate::node::Element;
+use crate::node::serializable::serialize_with;
+#[cfg(feature = "pretty")]
+use crate::pretty::{PrettyOptions, PrettySerializer};
 use crate::{Node, Selector};
 
 /// Wrapper around a reference to an element node.
@@ -41,6 +47,9 @@ impl<'a> ElementRef<'a> {
     }
 
     /// Returns an iterator over descendent elements matching a selector.
+    ///
+    /// Elements inside the contents of `<template>` elements are skipped unless
+    /// [`Select::include_template_contents`] is called.
     pub fn select<'b>(&self, selector: &'b Selector) -> Select<'a, 'b> {
         let mut inner = self.traverse();
         inner.next(); // Skip Edge::Open(self).
@@ -50,12 +59,40 @@ impl<'a> ElementRef<'a> {
             inner,
             selector,
             caches: Default::default(),
+            template_contents: false,
+            template_depth: 0,
+        }
+    }
+
+    /// Returns the document fragment holding the contents of a `<template>` element, or `None` if
+    /// this is not a template element.
+    ///
+    /// Template contents are not children of the template as far as selectors are concerned, so
+    /// e.g. `template > p` never matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use scraper::{ElementRef, Html, Selector};
+    /// let fragment = Html::parse_fragment("<template><p>hidden</p></template>");
+    /// let template = fragment.select(&Selector::parse("template").unwrap()).next().unwrap();
+    ///
+    /// let contents = template.template_contents().unwrap();
+    /// let p = contents.children().find_map(ElementRef::wrap).unwrap();
+    /// assert_eq!(p.html(), "<p>hidden</p>");
+    /// ```
+    pub fn template_contents(&self) -> Option<NodeRef<'a, Node>> {
+        if self.value().name.expanded() != expanded_name!(html "template") {
+            return None;
         }
+        self.first_child()
+            .filter(|child| is_template_contents(*child))
     }
 
     fn serialize(&self, traversal_scope: TraversalScope) -> String {
         let opts = SerializeOpts {
-            scripting_enabled: false, // It's not clear what this does.
+            // Escape the contents of `<noscript>` elements like other text.
+            scripting_enabled: false,
             traversal_scope,
             create_missing_parent: false,
         };
@@ -74,11 +111,56 @@ impl<'a> ElementRef<'a> {
         self.serialize(TraversalScope::ChildrenOnly(None))
     }
 
+    /// Returns the HTML of this element, serialized with the given options.
+    pub fn html_with(&self, options: SerializeOptions) -> String {
+        serialize_with(**self, TraversalScope::IncludeNode, options)
+    }
+
+    /// Returns the inner HTML of this element, serialized with the given options.
+    pub fn inner_html_with(&self, options: SerializeOptions) -> String {
+        serialize_with(**self, TraversalScope::ChildrenOnly(None), options)
+    }
+
+    /// Returns the indented HTML of this element, see [`pretty`](crate::pretty).
+    #[cfg(feature = "pretty")]
+    pub fn pretty(&self) -> String {
+        self.pretty_with(PrettyOptions::default())
+    }
+
+    /// Returns the indented HTML of this element with the given options.
+    #[cfg(feature = "pretty")]
+    pub fn pretty_with(&self, options: PrettyOptions) -> String {
+        let mut serializer = PrettySerializer::new(Vec::new(), options);
+        Serialize::serialize(self, &mut serializer, TraversalScope::IncludeNode).unwrap();
+        String::from_utf8(serializer.finish().unwrap()).unwrap()
+    }
+
     /// Returns the value of an attribute.
     pub fn attr(&self, attr: &str) -> Option<&'a str> {
         self.value().attr(attr)
     }
 
+    /// Returns the location of this element in the parsed input, if it was recorded.
+    ///
+    /// Only elements have spans, see [`span`](crate::span) for which elements are recorded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use scraper::{Html, Selector};
+    /// let source = "<ul>\n  <li>one</li>\n</ul>";
+    /// let fragment = Html::parse_fragment(source);
+    /// let li = fragment.select(&Selector::parse("li").unwrap()).next().unwrap();
+    ///
+    /// let span = li.source_span().unwrap();
+    /// assert_eq!((span.start.line, span.start.column), (2, 3));
+    /// assert_eq!(&source[span.range()], "<li>one</li>");
+    /// ```
+    #[cfg(feature = "spans")]
+    pub fn source_span(&self) -> Option<crate::span::SourceSpan> {
+        self.value().source_span()
+    }
+
     /// Returns an iterator over descendent text nodes.
     pub fn text(&self) -> Text<'a> {
         Text {
@@ -130,12 +212,27 @@ impl<'a> Deref for ElementRef<'a> {
     }
 }
 
+/// Returns true if `node` is the contents of a `<template>` element.
+pub(crate) fn is_template_contents(node: NodeRef<'_, Node>) -> bool {
+    node.value().is_fragment() && node.parent().is_some()
+}
+
 /// Iterator over descendent elements matching a selector.
 pub struct Select<'a, 'b> {
     scope: ElementRef<'a>,
     inner: Traverse<'a, Node>,
     selector: &'b Selector,
     caches: SelectorCaches,
+    template_contents: bool,
+    template_depth: usize,
+}
+
+impl Select<'_, '_> {
+    /// Also yields matching elements inside the contents of `<template>` elements.
+    pub fn include_template_contents(mut self) -> Self {
+        self.template_contents = true;
+        self
+    }
 }
 
 impl Debug for Select<'_, '_> {
@@ -145,6 +242,7 @@ impl Debug for Select<'_, '_> {
             .field("inner", &self.inner)
             .field("selector", &self.selector)
             .field("caches", &"..")
+            .field("template_contents", &self.template_contents)
             .finish()
     }
 }
@@ -156,6 +254,8 @@ impl Clone for Select<'_, '_> {
             inner: self.inner.clone(),
             selector: self.selector,
             caches: Default::default(),
+            template_contents: self.template_contents,
+            template_depth: self.template_depth,
         }
     }
 }
@@ -165,7 +265,14 @@ impl<'a> Iterator for Select<'a, '_> {
 
     fn next(&mut self) -> Option<ElementRef<'a>> {
         for edge in &mut self.inner {
+            match edge {
+                Edge::Open(node) if is_template_contents(node) => self.template_depth += 1,
+                Edge::Close(node) if is_template_contents(node) => self.template_depth -= 1,
+                _ => {}
+            }
+
             if let Edge::Open(node) = edge
+                && (self.template_contents || self.template_depth == 0)
                 && let Some(element) = ElementRef::wrap(node)
                 && self.selector.matches_with_scope_and_cache(
                     &element,
@@ -206,8 +313,11 @@ impl<'a> Iterator for Text<'a> {
 impl FusedIterator for Text<'_> {}
 
 mod element;
+mod inner_text;
 mod serializable;
 
+pub use self::inner_text::InnerTextOptions;
+
 #[cfg(test)]
 mod tests {
     use crate::html::Html;
@@ -232,4 +342,85 @@ mod tests {
         let element2 = element1.select(&sel2).next().unwrap();
         assert_eq!(element2.inner_html(), "3");
     }
+
+    #[test]
+    fn template_contents_are_selected_on_request() {
+        let html = "<template><p>1</p><template><p>2</p></template></template><p>3</p>";
+        let fragment = Html::parse_fragment(html);
+        let p = Selector::parse("p").unwrap();
+
+        let texts = |select: &mut dyn Iterator<Item = super::ElementRef>| {
+            select.map(|p| p.inner_html()).collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&mut fragment.select(&p)), ["3"]);
+        assert_eq!(texts(&mut fragment.select(&p).rev()), ["3"]);
+        assert_eq!(
+            texts(&mut fragment.select(&p).include_template_contents()),
+            ["1", "2", "3"]
+        );
+        assert_eq!(
+            texts(&mut fragment.select(&p).include_template_contents().rev()),
+            ["3", "2", "1"]
+        );
+
+        let trailing = Html::parse_fragment("<p>0</p><template><p>1</p></template>");
+        assert_eq!(texts(&mut trailing.select(&p).rev()), ["0"]);
+        assert_eq!(
+            texts(&mut trailing.select(&p).include_template_contents().rev()),
+            ["1", "0"]
+        );
+
+        let root = fragment.root_element();
+        assert_eq!(texts(&mut root.select(&p)), ["3"]);
+        assert_eq!(
+            texts(&mut root.select(&p).include_template_contents()),
+            ["1", "2", "3"]
+        );
+
+        let template = root.child_elements().next().unwrap();
+        let contents = template.template_contents().unwrap();
+        assert!(contents.value().is_fragment());
+        assert_eq!(contents.children().count(), 2);
+        assert!(root.template_contents().is_none());
+
+        let nested = Selector::parse("template p").unwrap();
+        assert!(
+            fragment
+                .select(&nested)
+                .include_template_contents()
+                .next()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn template_serializes_its_contents() {
+        let mut fragment = Html::parse_fragment("<template><p>1</p></template>");
+        let template = fragment
+            .select(&Selector::parse("template").unwrap())
+            .next()
+            .unwrap()
+            .id();
+        fragment
+            .tree
+            .get_mut(template)
+            .unwrap()
+            .append(crate::Node::Text(crate::node::Text {
+                text: "not contents".into(),
+            }));
+
+        let template = crate::ElementRef::wrap(fragment.tree.get(template).unwrap()).unwrap();
+        assert_eq!(template.html(), "<template><p>1</p></template>");
+        assert_eq!(template.inner_html(), "<p>1</p>");
+        assert_eq!(
+            fragment.root_element().inner_html(),
+            "<template><p>1</p></template>"
+        );
+
+        let reparsed = Html::parse_fragment(&fragment.root_element().inner_html());
+        assert_eq!(
+            reparsed.root_element().html(),
+            fragment.root_element().html()
+        );
+    }
 }
@@ -3,9 +3,11 @@
 
 mod utils;
 
+#[cfg(feature = "errors")]
+use std::borrow::Cow;
 use std::{error::Error, fmt::Display};
 
-use cssparser::{BasicParseErrorKind, ParseErrorKind, Token};
+use cssparser::{BasicParseErrorKind, Token};
 use selectors::parser::SelectorParseErrorKind;
 
 /// Error type that is returned when calling `Selector::parse`
@@ -41,8 +43,8 @@ impl<'a> From<cssparser::ParseError<'a, SelectorParseErrorKind<'a>>> for Selecto
         // NOTE: This could be improved, but I dont
         // exactly know how
         match original.kind {
-            ParseErrorKind::Basic(err) => SelectorErrorKind::from(err),
-            ParseErrorKind::Custom(err) => SelectorErrorKind::from(err),
+            cssparser::ParseErrorKind::Basic(err) => SelectorErrorKind::from(err),
+            cssparser::ParseErrorKind::Custom(err) => SelectorErrorKind::from(err),
         }
     }
 }
@@ -116,3 +118,708 @@ impl Error for SelectorErrorKind<'_> {
         }
     }
 }
+
+/// The category of an HTML parse error.
+///
+/// Parse errors are classified from the messages reported by `html5ever`. Parsing with
+/// [`ParseOptions::exact_errors`](crate::html::ParseOptions::exact_errors) enabled makes the
+/// messages more detailed, which allows some errors reported as [`UnexpectedToken`] to be
+/// classified as [`UnexpectedStartTag`] or [`UnexpectedEndTag`] instead.
+///
+/// [`UnexpectedToken`]: ParseErrorKind::UnexpectedToken
+/// [`UnexpectedStartTag`]: ParseErrorKind::UnexpectedStartTag
+/// [`UnexpectedEndTag`]: ParseErrorKind::UnexpectedEndTag
+#[cfg(feature = "errors")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// A character that is not allowed where it appears
+    UnexpectedCharacter,
+
+    /// The input ended in the middle of a tag, comment or other construct
+    UnexpectedEof,
+
+    /// An attribute appears more than once on the same tag
+    DuplicateAttribute,
+
+    /// An end tag has attributes or is self-closing
+    MalformedEndTag,
+
+    /// A self-closing flag on a start tag that is not a void or foreign element
+    NonVoidSelfClosingTag,
+
+    /// A character reference is malformed, unknown or not terminated by a semicolon
+    BadCharacterReference,
+
+    /// The doctype is missing, malformed or misplaced
+    BadDoctype,
+
+    /// A start tag that is not allowed where it appears
+    UnexpectedStartTag,
+
+    /// An end tag that does not match an open element or is not allowed where it appears
+    UnexpectedEndTag,
+
+    /// A token that is not allowed where it appears
+    UnexpectedToken,
+
+    /// Text that is not allowed where it appears, such as inside a table
+    UnexpectedText,
+
+    /// An element was closed implicitly while it still had open children
+    UnclosedElement,
+
+    /// Elements are not properly nested, such as `<b><i></b></i>`
+    MisnestedTags,
+
+    /// A resource limit was exceeded and the document was truncated
+    LimitExceeded,
+
+    /// Any other error
+    Other,
+}
+
+#[cfg(feature = "errors")]
+impl ParseErrorKind {
+    /// Classifies an error message reported by `html5ever`.
+    pub(crate) fn classify(message: &str) -> Self {
+        let starts_with = |prefixes: &[&str]| prefixes.iter().any(|p| message.starts_with(p));
+
+        if starts_with(&["Bad character", "Saw "]) {
+            if message.contains("EOF") {
+                Self::UnexpectedEof
+            } else {
+                Self::UnexpectedCharacter
+            }
+        } else if starts_with(&["Unexpected EOF"]) || message.contains("EOF in") {
+            Self::UnexpectedEof
+        } else if starts_with(&["Duplicate attribute"]) {
+            Self::DuplicateAttribute
+        } else if starts_with(&["Attributes on an end tag", "Self-closing end tag"]) {
+            Self::MalformedEndTag
+        } else if starts_with(&["Unacknowledged self-closing tag"]) {
+            Self::NonVoidSelfClosingTag
+        } else if message.contains("character reference") {
+            Self::BadCharacterReference
+        } else if message.contains("DOCTYPE") {
+            Self::BadDoctype
+        } else if starts_with(&["Unexpected token"]) {
+            if message.contains("kind: StartTag") {
+                Self::UnexpectedStartTag
+            } else if message.contains("kind: EndTag") {
+                Self::UnexpectedEndTag
+            } else if message.contains("Characters(") {
+                Self::UnexpectedText
+            } else if starts_with(&["Unexpected token Eof"]) {
+                Self::UnexpectedEof
+            } else {
+                Self::UnexpectedToken
+            }
+        } else if starts_with(&["Unexpected characters", "Non-space table text"]) {
+            Self::UnexpectedText
+        } else if starts_with(&["Unexpected open element", "Unexpected open tag"]) {
+            Self::UnclosedElement
+        } else if starts_with(&["Formatting element", "Closing wrong heading tag"]) {
+            Self::MisnestedTags
+        } else if starts_with(&["nested ", "Nested <", "hr in option"]) {
+            Self::UnexpectedStartTag
+        } else if starts_with(&[
+            "No <p> tag to close",
+            "No matching tag to close",
+            "No heading tag to close",
+            "Found special tag while closing",
+            "expected to close",
+            "Null form element pointer",
+            "Form element not in scope",
+            "Bad open element on",
+        ]) || message.contains("with no <body> in scope")
+        {
+            Self::UnexpectedEndTag
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// An error encountered while parsing HTML.
+///
+/// Parsing never fails because of these errors, they are recorded in
+/// [`Html::parse_errors`](crate::Html::parse_errors) instead.
+#[cfg(feature = "errors")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The category of the error
+    pub kind: ParseErrorKind,
+
+    /// The error message reported by the parser
+    pub message: Cow<'static, str>,
+
+    /// The line of the input on which the error occurred, starting at 1, or 0 if the input was
+    /// parsed with an [`HtmlTreeSink`](crate::HtmlTreeSink) directly
+    pub line: u64,
+
+    /// The column on which the error occurred, starting at 1
+    ///
+    /// Columns are only known when the `spans` feature is enabled and the error could be
+    /// attributed to a tag, comment or doctype, or to the end of the input.
+    pub column: Option<u64>,
+}
+
+#[cfg(feature = "errors")]
+impl ParseError {
+    pub(crate) fn new(message: Cow<'static, str>, line: u64) -> Self {
+        Self {
+            kind: ParseErrorKind::classify(&message),
+            message,
+            line,
+            column: None,
+        }
+    }
+}
+
+#[cfg(feature = "errors")]
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{}: {}", self.line, column, self.message),
+            None => write!(f, "{}: {}", self.line, self.message),
+        }
+    }
+}
+
+#[cfg(feature = "errors")]
+impl Error for ParseError {}
+
+/// A resource limit that can be set with [`Limits`](crate::html::Limits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    /// The nesting depth of the tree
+    Depth,
+
+    /// The total number of nodes
+    Nodes,
+
+    /// The number of attributes on a single element
+    Attributes,
+
+    /// The length of a single attribute value in bytes
+    AttributeLength,
+
+    /// The total size of all text in bytes
+    TextBytes,
+}
+
+impl Display for LimitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Depth => "tree depth",
+            Self::Nodes => "number of nodes",
+            Self::Attributes => "number of attributes per element",
+            Self::AttributeLength => "attribute value length",
+            Self::TextBytes => "text size",
+        })
+    }
+}
+
+/// Error type that is returned when a resource limit is exceeded during
+/// [`Html::try_parse_document`](crate::Html::try_parse_document)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// The limit that was exceeded
+    pub kind: LimitKind,
+
+    /// The maximum that was configured for the limit
+    pub max: usize,
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Maximum {} of {} exceeded", self.kind, self.max)
+    }
+}
+
+impl Error for LimitExceeded {}
+
+/// Error type that is returned by [`StreamSelector::new`](crate::stream::StreamSelector::new)
+/// when a selector cannot be decided from an element's ancestors alone
+#[cfg(feature = "stream")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedSelector {
+    /// The offending selector, serialized back to CSS
+    pub selector: String,
+}
+
+#[cfg(feature = "stream")]
+impl Display for UnsupportedSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Selector `{}` depends on siblings or descendants and cannot be matched while streaming",
+            self.selector
+        )
+    }
+}
+
+#[cfg(feature = "stream")]
+impl Error for UnsupportedSelector {}
+
+/// Error type that is returned when adding a class that is empty or contains whitespace, from
+/// [`Element::add_class`](crate::node::Element::add_class) and
+/// [`Element::toggle_class`](crate::node::Element::toggle_class)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidClass {
+    /// The rejected class
+    pub class: String,
+}
+
+impl Display for InvalidClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Class `{}` is empty or contains whitespace", self.class)
+    }
+}
+
+impl Error for InvalidClass {}
+
+/// Error type that is returned when HTML cannot be parsed in the context of a node, from
+/// [`NodeMut::set_inner_html`](crate::node_mut::NodeMut::set_inner_html) and friends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HtmlContextError {
+    /// The node is not an element
+    NotAnElement,
+
+    /// The element has no parent element or fragment root to insert nodes next to it in
+    NoParentElement,
+}
+
+impl Display for HtmlContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAnElement => write!(f, "Only elements can have their HTML set"),
+            Self::NoParentElement => {
+                write!(
+                    f,
+                    "Cannot insert HTML next to an element without a parent element"
+                )
+            }
+        }
+    }
+}
+
+impl Error for HtmlContextError {}
+
+/// Error type that is returned when setting the value of a control or submitting a form from
+/// [`forms`](crate::forms)
+#[cfg(feature = "forms")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormError {
+    /// The form has no control with the name that takes a value
+    NoSuchControl(String),
+
+    /// The control cannot take the value, such as a radio group without a radio button of that
+    /// value
+    InvalidValue {
+        /// The name of the control
+        name: String,
+
+        /// The rejected value
+        value: String,
+    },
+
+    /// The submitter is not a submit button of the form
+    InvalidSubmitter,
+
+    /// The action URL is relative and there is no absolute URL to resolve it against, or it is
+    /// invalid
+    InvalidAction(String),
+}
+
+#[cfg(feature = "forms")]
+impl Display for FormError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSuchControl(name) => write!(f, "No control named {name:?}"),
+            Self::InvalidValue { name, value } => {
+                write!(f, "Control {name:?} cannot take the value {value:?}")
+            }
+            Self::InvalidSubmitter => {
+                f.write_str("The submitter is not a submit button of the form")
+            }
+            Self::InvalidAction(action) => write!(f, "Cannot resolve the action URL {action:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "forms")]
+impl Error for FormError {}
+
+/// Error type that is returned when a [`Scrape`](crate::scrape::Scrape) implementation cannot
+/// extract a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrapeError {
+    /// The name of the type being extracted
+    pub ty: &'static str,
+
+    /// The name of the field that could not be extracted
+    pub field: &'static str,
+
+    /// The selector of the field, if it has one
+    pub selector: Option<&'static str>,
+
+    /// Why the field could not be extracted
+    pub kind: ScrapeErrorKind,
+}
+
+/// Why a field could not be extracted, see [`ScrapeError`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrapeErrorKind {
+    /// No element matches the selector of a required field
+    NotFound,
+
+    /// The element lacks the attribute the field is extracted from
+    MissingAttribute(&'static str),
+
+    /// The extracted text could not be converted to the type of the field
+    Parse(String),
+
+    /// A nested type could not be extracted
+    Nested(Box<ScrapeError>),
+}
+
+impl Display for ScrapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot extract `{}::{}`", self.ty, self.field)?;
+        if let Some(selector) = self.selector {
+            write!(f, " from `{selector}`")?;
+        }
+        match &self.kind {
+            ScrapeErrorKind::NotFound => f.write_str(": no element matches"),
+            ScrapeErrorKind::MissingAttribute(name) => write!(f, ": missing attribute {name:?}"),
+            ScrapeErrorKind::Parse(message) => write!(f, ": {message}"),
+            ScrapeErrorKind::Nested(_) => f.write_str(": nested value is invalid"),
+        }
+    }
+}
+
+impl Error for ScrapeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ScrapeErrorKind::Nested(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Error type that is returned when a [`Schema`](crate::schema::Schema) cannot extract a field
+#[cfg(feature = "schema")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// The path of the field, such as `reviews[1].stars`, empty for the whole object
+    pub path: String,
+
+    /// The selector of the field, if it has one
+    pub selector: Option<String>,
+
+    /// Why the field could not be extracted
+    pub kind: SchemaErrorKind,
+}
+
+/// Why a field could not be extracted, see [`SchemaError`]
+#[cfg(feature = "schema")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaErrorKind {
+    /// No element matches the selector of a required field
+    NotFound,
+
+    /// The element lacks the attribute the field is extracted from
+    MissingAttribute(String),
+
+    /// The text does not match the regular expression of a process step
+    NoMatch(String),
+
+    /// A process step that takes text was applied to a number or an object
+    NotText,
+
+    /// The text cannot be parsed as a number
+    InvalidNumber(String),
+
+    /// The extracted object cannot be deserialized to the requested type
+    Deserialize(String),
+}
+
+#[cfg(feature = "schema")]
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            f.write_str("Cannot extract object")?;
+        } else {
+            write!(f, "Cannot extract `{}`", self.path)?;
+        }
+        if let Some(selector) = &self.selector {
+            write!(f, " from `{selector}`")?;
+        }
+        match &self.kind {
+            SchemaErrorKind::NotFound => f.write_str(": no element matches"),
+            SchemaErrorKind::MissingAttribute(name) => write!(f, ": missing attribute {name:?}"),
+            SchemaErrorKind::NoMatch(regex) => write!(f, ": text does not match `{regex}`"),
+            SchemaErrorKind::NotText => f.write_str(": cannot process a value that is not text"),
+            SchemaErrorKind::InvalidNumber(text) => write!(f, ": {text:?} is not a number"),
+            SchemaErrorKind::Deserialize(message) => write!(f, ": {message}"),
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl Error for SchemaError {}
+
+#[cfg(feature = "schema")]
+impl serde::de::Error for SchemaError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            path: String::new(),
+            selector: None,
+            kind: SchemaErrorKind::Deserialize(msg.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Pins the messages of `html5ever` that [`ParseErrorKind::classify`] relies on, so that
+    /// changes to them are noticed when `html5ever` is upgraded.
+    #[cfg(feature = "errors")]
+    #[test]
+    fn classified_messages() {
+        use super::ParseErrorKind::*;
+        use crate::Html;
+        use crate::html::ParseOptions;
+
+        let document = |input: &str| Html::parse_document(input);
+        let exact = |input: &str| {
+            Html::parse_document_with(input, ParseOptions::default().exact_errors(true))
+        };
+        let fragment = |input: &str| Html::parse_fragment(input);
+
+        let cases: &[(Html, &str, super::ParseErrorKind)] = &[
+            (
+                document("<p =a>"),
+                "Saw = in state BeforeAttributeName",
+                UnexpectedCharacter,
+            ),
+            (exact("<p =a>"), "Bad character", UnexpectedCharacter),
+            (
+                exact("<!DOCTYPE html>\u{1}"),
+                "Bad character \u{1}",
+                UnexpectedCharacter,
+            ),
+            (document("<p"), "Saw EOF in state TagName", UnexpectedEof),
+            (exact("<p"), "Unexpected EOF", UnexpectedEof),
+            (
+                document("&#1"),
+                "EOF in numeric character reference",
+                UnexpectedEof,
+            ),
+            (
+                document("<p a=1 a=2>"),
+                "Duplicate attribute",
+                DuplicateAttribute,
+            ),
+            (
+                document("</p a=1>"),
+                "Attributes on an end tag",
+                MalformedEndTag,
+            ),
+            (document("</p/>"), "Self-closing end tag", MalformedEndTag),
+            (
+                document("<div/>"),
+                "Unacknowledged self-closing tag",
+                NonVoidSelfClosingTag,
+            ),
+            (
+                document("&bogus;"),
+                "Invalid character reference",
+                BadCharacterReference,
+            ),
+            (
+                exact("&bogus;"),
+                "Invalid character reference &bogus;",
+                BadCharacterReference,
+            ),
+            (
+                document("&#;"),
+                "Numeric character reference without digits",
+                BadCharacterReference,
+            ),
+            (
+                document("&#"),
+                "EOF after '#' in character reference",
+                BadCharacterReference,
+            ),
+            (
+                document("&#1"),
+                "Invalid numeric character reference",
+                BadCharacterReference,
+            ),
+            (document("<!DOCTYPE foo>"), "Bad DOCTYPE", BadDoctype),
+            (
+                document("<!DOCTYPE html><!DOCTYPE html>"),
+                "DOCTYPE in body",
+                BadDoctype,
+            ),
+            (
+                exact("<!DOCTYPE html><!DOCTYPE html>"),
+                "DOCTYPE in insertion mode BeforeHtml",
+                BadDoctype,
+            ),
+            (document("<p>"), "Unexpected token", UnexpectedToken),
+            (
+                exact("<p>"),
+                "Unexpected token Tag(Tag { kind: StartTag, name: Atom(\\'p\\' type=inline), self_closing: false, attrs: [], had_duplicate_attributes: false }) in insertion mode Initial",
+                UnexpectedStartTag,
+            ),
+            (
+                exact("<!DOCTYPE html></p>"),
+                "Unexpected token Tag { kind: EndTag, name: Atom(\\'p\\' type=inline), self_closing: false, attrs: [], had_duplicate_attributes: false } in insertion mode BeforeHtml",
+                UnexpectedEndTag,
+            ),
+            (
+                exact("x"),
+                "Unexpected token Characters(NotWhitespace, Tendril<UTF8>(inline: \\\"x\\\")) in insertion mode Initial",
+                UnexpectedText,
+            ),
+            (
+                exact("<!DOCTYPE html><frameset>"),
+                "Unexpected token Eof in insertion mode InFrameset",
+                UnexpectedEof,
+            ),
+            (
+                document("<!DOCTYPE html><table><b>x"),
+                "Unexpected characters in table",
+                UnexpectedText,
+            ),
+            (
+                document("<!DOCTYPE html><table>x</table>"),
+                "Non-space table text",
+                UnexpectedText,
+            ),
+            (
+                document("<!DOCTYPE html><div><span></div>"),
+                "Unexpected open element",
+                UnclosedElement,
+            ),
+            (
+                document("<!DOCTYPE html><div>"),
+                "Unexpected open tag at end of body",
+                UnclosedElement,
+            ),
+            (
+                document("<!DOCTYPE html><b><i></b></i>"),
+                "Formatting element not current node",
+                MisnestedTags,
+            ),
+            (
+                document("<!DOCTYPE html><b><i></b></i>"),
+                "Formatting element not open",
+                MisnestedTags,
+            ),
+            (
+                document("<!DOCTYPE html><b><table></b>"),
+                "Formatting element not in scope",
+                MisnestedTags,
+            ),
+            (
+                document("<!DOCTYPE html><h1></h2>"),
+                "Closing wrong heading tag",
+                MisnestedTags,
+            ),
+            (
+                document("<!DOCTYPE html><h1><h2>"),
+                "nested heading tags",
+                UnexpectedStartTag,
+            ),
+            (
+                document("<!DOCTYPE html><form><form>"),
+                "nested forms",
+                UnexpectedStartTag,
+            ),
+            (
+                document("<!DOCTYPE html><button><button>"),
+                "nested buttons",
+                UnexpectedStartTag,
+            ),
+            (
+                document("<!DOCTYPE html><nobr><nobr>"),
+                "Nested <nobr>",
+                UnexpectedStartTag,
+            ),
+            (
+                document("<!DOCTYPE html><select><option><span><hr>"),
+                "hr in option",
+                UnexpectedStartTag,
+            ),
+            (
+                document("<!DOCTYPE html><select><option><span><option>"),
+                "nested options",
+                UnexpectedStartTag,
+            ),
+            (
+                document("<!DOCTYPE html><body></p>"),
+                "No <p> tag to close",
+                UnexpectedEndTag,
+            ),
+            (
+                document("<!DOCTYPE html><body></li>"),
+                "No matching tag to close",
+                UnexpectedEndTag,
+            ),
+            (
+                document("<!DOCTYPE html><body></h1>"),
+                "No heading tag to close",
+                UnexpectedEndTag,
+            ),
+            (
+                document("<!DOCTYPE html><div></span>"),
+                "Found special tag while closing generic tag",
+                UnexpectedEndTag,
+            ),
+            (
+                document("<!DOCTYPE html><table><tr><td><span><td>"),
+                "expected to close <td> or <th> with cell",
+                UnexpectedEndTag,
+            ),
+            (
+                document("<!DOCTYPE html><body></form>"),
+                "Null form element pointer on </form>",
+                UnexpectedEndTag,
+            ),
+            (
+                document("<!DOCTYPE html><form><marquee></form>"),
+                "Form element not in scope on </form>",
+                UnexpectedEndTag,
+            ),
+            (
+                document("<!DOCTYPE html><form><div></form>"),
+                "Bad open element on </form>",
+                UnexpectedEndTag,
+            ),
+            (
+                fragment("</body>"),
+                "</body> with no <body> in scope",
+                UnexpectedEndTag,
+            ),
+            (
+                fragment("</html>"),
+                "</html> with no <body> in scope",
+                UnexpectedEndTag,
+            ),
+        ];
+
+        for (html, message, kind) in cases {
+            let error = html
+                .parse_errors()
+                .iter()
+                .find(|error| error.message == *message)
+                .unwrap_or_else(|| panic!("{message:?} not in {:?}", html.errors));
+            assert_eq!(error.kind, *kind, "{message:?}");
+        }
+    }
+}
@@ -0,0 +1,1018 @@
+//! HTML forms and their submission.
+//!
+//! Requires the `forms` feature.
+//!
+//! Models the controls of each `<form>` with their default values, lets their values be changed,
+//! and builds the request a browser sends when the form is submitted, following the HTML [form
+//! submission algorithm][spec]. Controls are associated with their form by nesting or by the
+//! `form` attribute. Forms are always encoded as UTF-8, and file inputs are submitted without a
+//! file.
+//!
+//! [spec]: https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#form-submission-algorithm
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//! use scraper::forms::{self, Method};
+//! use url::Url;
+//!
+//! let html = Html::parse_document(
+//!     r#"<form action="/login" method="post">
+//!         <input name="user">
+//!         <input name="password" type="password">
+//!         <input name="remember" type="checkbox" checked>
+//!         <button name="go" value="1">Log in</button>
+//!     </form>"#,
+//! );
+//! let url = Url::parse("https://example.com/").unwrap();
+//! let mut form = forms::forms(&html, Some(&url)).remove(0);
+//!
+//! form.set("user", "ann").unwrap();
+//! form.set("password", "p&ss").unwrap();
+//! let submission = form.submit(None).unwrap();
+//!
+//! assert_eq!(submission.method, Method::Post);
+//! assert_eq!(submission.url.as_str(), "https://example.com/login");
+//! assert_eq!(submission.body, b"user=ann&password=p%26ss&remember=on");
+//! ```
+
+use std::collections::HashMap;
+
+use ego_tree::iter::Edge;
+use url::Url;
+use url::form_urlencoded;
+
+use crate::element_ref::is_template_contents;
+use crate::error::FormError;
+use crate::{ElementRef, Html};
+
+/// A `<form>` and its controls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Form<'a> {
+    /// The `<form>` element.
+    pub element: ElementRef<'a>,
+
+    /// The controls of the form in tree order.
+    pub controls: Vec<Control<'a>>,
+
+    /// The URL that the action is resolved against.
+    base: Option<Url>,
+}
+
+/// A control of a form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Control<'a> {
+    /// The `<input>`, `<button>`, `<select>` or `<textarea>` element.
+    pub element: ElementRef<'a>,
+
+    /// The kind of control.
+    pub kind: ControlKind,
+
+    /// The `name`, or an empty string.
+    pub name: String,
+
+    /// The value: the text of a text field or text area, the value submitted for a checked
+    /// checkbox or radio button, or the `value` of a button. Unused for selects, which submit
+    /// their selected options.
+    pub value: String,
+
+    /// Whether a checkbox or radio button is checked.
+    pub checked: bool,
+
+    /// Whether the control is disabled, by its `disabled` attribute or by a disabled
+    /// `<fieldset>`. Disabled controls are not submitted.
+    pub disabled: bool,
+
+    /// The options of a select.
+    pub options: Vec<SelectOption>,
+}
+
+/// The kind of a form control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControlKind {
+    /// A single-line text input, such as `text`, `password`, `email`, `number` or `date`.
+    Text,
+
+    /// `<input type="hidden">`.
+    Hidden,
+
+    /// `<input type="checkbox">`.
+    Checkbox,
+
+    /// `<input type="radio">`.
+    Radio,
+
+    /// `<input type="file">`.
+    File,
+
+    /// A submit button: `<input type="submit">` or `<button>` of type `submit`.
+    Submit,
+
+    /// `<input type="image">`, a submit button that also submits the coordinates clicked.
+    Image,
+
+    /// A reset button, which is never submitted.
+    Reset,
+
+    /// A button without a default behavior, which is never submitted.
+    Button,
+
+    /// `<select>`.
+    Select {
+        /// Whether more than one option can be selected.
+        multiple: bool,
+    },
+
+    /// `<textarea>`.
+    Textarea,
+}
+
+impl ControlKind {
+    fn is_submit_button(self) -> bool {
+        matches!(self, ControlKind::Submit | ControlKind::Image)
+    }
+
+    fn is_button(self) -> bool {
+        matches!(
+            self,
+            ControlKind::Submit | ControlKind::Image | ControlKind::Reset | ControlKind::Button
+        )
+    }
+}
+
+/// An `<option>` of a select.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectOption {
+    /// The `value`, or the text of the option.
+    pub value: String,
+
+    /// The text of the option, with whitespace collapsed.
+    pub label: String,
+
+    /// Whether the option is selected.
+    pub selected: bool,
+
+    /// Whether the option is disabled, by its `disabled` attribute or by a disabled
+    /// `<optgroup>`. Disabled options are not submitted.
+    pub disabled: bool,
+}
+
+/// The HTTP method of a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    /// `GET`, with the form data in the query of the URL.
+    Get,
+
+    /// `POST`, with the form data in the body.
+    Post,
+
+    /// `dialog`, which closes the enclosing dialog instead of sending a request.
+    Dialog,
+}
+
+/// The encoding of the form data of a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Enctype {
+    /// `application/x-www-form-urlencoded`.
+    UrlEncoded,
+
+    /// `multipart/form-data`.
+    Multipart,
+
+    /// `text/plain`.
+    TextPlain,
+}
+
+/// The request a browser sends when a form is submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    /// The method.
+    pub method: Method,
+
+    /// The URL, including the form data as its query for [`Method::Get`].
+    pub url: Url,
+
+    /// The encoding of the body.
+    pub enctype: Enctype,
+
+    /// The `Content-Type` of the body, including the boundary for `multipart/form-data`, or `None`
+    /// without a body.
+    pub content_type: Option<String>,
+
+    /// The body, empty unless the method is [`Method::Post`].
+    pub body: Vec<u8>,
+}
+
+/// Returns the forms of a document in tree order, with relative action URLs resolved against
+/// `document_url` and `<base>`.
+///
+/// Forms and controls inside `<template>` contents are skipped, as are controls without a form.
+pub fn forms<'a>(html: &'a Html, document_url: Option<&Url>) -> Vec<Form<'a>> {
+    let base = html.base_url(document_url);
+    let elements = elements(html);
+
+    let mut ids = HashMap::new();
+    for element in &elements {
+        if let Some(id) = element.value().id() {
+            ids.entry(id).or_insert(*element);
+        }
+    }
+
+    let mut forms = elements
+        .iter()
+        .filter(|element| element.value().name() == "form")
+        .map(|element| Form {
+            element: *element,
+            controls: Vec::new(),
+            base: base.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    for element in &elements {
+        let Some(kind) = kind(*element) else {
+            continue;
+        };
+        let owner = match element.value().attr("form") {
+            Some(id) => ids
+                .get(id)
+                .filter(|owner| owner.value().name() == "form")
+                .copied(),
+            None => element
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|ancestor| ancestor.value().name() == "form"),
+        };
+        if let Some(form) = owner.and_then(|owner| forms.iter_mut().find(|f| f.element == owner)) {
+            form.controls.push(control(*element, kind));
+        }
+    }
+
+    for form in &mut forms {
+        form.uncheck_duplicate_radios();
+    }
+    forms
+}
+
+/// Returns the elements of a document in tree order, skipping template contents.
+fn elements(html: &Html) -> Vec<ElementRef<'_>> {
+    let mut elements = Vec::new();
+    let mut skipped = None;
+    for edge in html.tree.root().traverse() {
+        match edge {
+            Edge::Open(node) if skipped.is_none() => {
+                if is_template_contents(node) {
+                    skipped = Some(node.id());
+                } else if let Some(element) = ElementRef::wrap(node) {
+                    elements.push(element);
+                }
+            }
+            Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+            _ => {}
+        }
+    }
+    elements
+}
+
+fn kind(element: ElementRef<'_>) -> Option<ControlKind> {
+    let value = element.value();
+    let ty = value.attr("type").unwrap_or_default().to_ascii_lowercase();
+    Some(match value.name() {
+        "input" => match ty.as_str() {
+            "hidden" => ControlKind::Hidden,
+            "checkbox" => ControlKind::Checkbox,
+            "radio" => ControlKind::Radio,
+            "file" => ControlKind::File,
+            "submit" => ControlKind::Submit,
+            "image" => ControlKind::Image,
+            "reset" => ControlKind::Reset,
+            "button" => ControlKind::Button,
+            _ => ControlKind::Text,
+        },
+        "button" => match ty.as_str() {
+            "reset" => ControlKind::Reset,
+            "button" => ControlKind::Button,
+            _ => ControlKind::Submit,
+        },
+        "select" => ControlKind::Select {
+            multiple: value.attr("multiple").is_some(),
+        },
+        "textarea" => ControlKind::Textarea,
+        _ => return None,
+    })
+}
+
+fn control(element: ElementRef<'_>, kind: ControlKind) -> Control<'_> {
+    let attr = |name: &str| element.value().attr(name);
+    let value = match kind {
+        ControlKind::Text => {
+            let value = attr("value").unwrap_or_default().replace(['\r', '\n'], "");
+            let ty = attr("type").unwrap_or_default();
+            if ty.eq_ignore_ascii_case("email") || ty.eq_ignore_ascii_case("url") {
+                value.trim().to_owned()
+            } else {
+                value
+            }
+        }
+        ControlKind::Checkbox | ControlKind::Radio => attr("value").unwrap_or("on").to_owned(),
+        ControlKind::Textarea => element.text().collect(),
+        ControlKind::Select { .. } | ControlKind::File => String::new(),
+        _ => attr("value").unwrap_or_default().to_owned(),
+    };
+
+    let mut control = Control {
+        element,
+        kind,
+        name: attr("name").unwrap_or_default().to_owned(),
+        value,
+        checked: matches!(kind, ControlKind::Checkbox | ControlKind::Radio)
+            && attr("checked").is_some(),
+        disabled: is_disabled(element),
+        options: Vec::new(),
+    };
+    if let ControlKind::Select { multiple } = kind {
+        control.options = options(element);
+        let size = attr("size").and_then(|size| size.trim().parse::<u32>().ok());
+        if !multiple {
+            select_one(&mut control.options, size.unwrap_or(1) <= 1);
+        }
+    }
+    control
+}
+
+/// Returns true if an element is disabled by its `disabled` attribute or by a disabled
+/// `<fieldset>` ancestor, unless it is in that fieldset's first `<legend>`.
+fn is_disabled(element: ElementRef<'_>) -> bool {
+    if element.value().attr("disabled").is_some() {
+        return true;
+    }
+
+    let mut child = *element;
+    for ancestor in element.ancestors() {
+        if let Some(fieldset) = ElementRef::wrap(ancestor)
+            && fieldset.value().name() == "fieldset"
+            && fieldset.value().attr("disabled").is_some()
+        {
+            let first_legend = fieldset
+                .child_elements()
+                .find(|child| child.value().name() == "legend");
+            if first_legend.is_none_or(|legend| *legend != child) {
+                return true;
+            }
+        }
+        child = ancestor;
+    }
+    false
+}
+
+fn options(select: ElementRef<'_>) -> Vec<SelectOption> {
+    let mut options = Vec::new();
+    for child in select.child_elements() {
+        let (group_disabled, children) = match child.value().name() {
+            "optgroup" => (
+                child.value().attr("disabled").is_some(),
+                child.child_elements().collect::<Vec<_>>(),
+            ),
+            _ => (false, vec![child]),
+        };
+        for option in children {
+            if option.value().name() != "option" {
+                continue;
+            }
+            let text = option.text().collect::<String>();
+            let label = text.split_ascii_whitespace().collect::<Vec<_>>().join(" ");
+            options.push(SelectOption {
+                value: option
+                    .value()
+                    .attr("value")
+                    .map_or_else(|| label.clone(), str::to_owned),
+                label,
+                selected: option.value().attr("selected").is_some(),
+                disabled: group_disabled || option.value().attr("disabled").is_some(),
+            });
+        }
+    }
+    options
+}
+
+/// Leaves only the last selected option of a single select selected, and selects the first
+/// enabled option of a drop-down box without a selected option.
+fn select_one(options: &mut [SelectOption], drop_down: bool) {
+    let last = options.iter().rposition(|option| option.selected);
+    for (index, option) in options.iter_mut().enumerate() {
+        option.selected = Some(index) == last;
+    }
+    if last.is_none()
+        && drop_down
+        && let Some(first) = options.iter_mut().find(|option| !option.disabled)
+    {
+        first.selected = true;
+    }
+}
+
+/// An entry of the form data.
+#[derive(Debug)]
+enum Entry {
+    Text(String, String),
+    /// A file input, submitted without a file.
+    File(String),
+}
+
+impl Entry {
+    /// Appends a text entry, with line breaks normalized.
+    fn text(entries: &mut Vec<Entry>, name: &str, value: &str) {
+        entries.push(Entry::Text(
+            normalize_newlines(name),
+            normalize_newlines(value),
+        ));
+    }
+}
+
+impl<'a> Form<'a> {
+    /// Returns the first control with a name.
+    pub fn control(&self, name: &str) -> Option<&Control<'a>> {
+        self.controls.iter().find(|control| control.name == name)
+    }
+
+    /// Sets the value of a control by name.
+    ///
+    /// Sets the text of the first text field or text area with the name, checks the radio button
+    /// of a group with the value, checks the checkbox with the value, or selects only the option
+    /// of a select with the value. Buttons are not considered.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), FormError> {
+        let invalid = || FormError::InvalidValue {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        };
+        let kind = self
+            .named(name)
+            .next()
+            .map(|control| control.kind)
+            .ok_or_else(|| FormError::NoSuchControl(name.to_owned()))?;
+
+        match kind {
+            ControlKind::Text | ControlKind::Hidden | ControlKind::Textarea => {
+                let control = self.named(name).next().unwrap();
+                control.value = if kind == ControlKind::Textarea {
+                    value.to_owned()
+                } else {
+                    value.replace(['\r', '\n'], "")
+                };
+                Ok(())
+            }
+            ControlKind::Radio => {
+                if !self
+                    .named(name)
+                    .any(|control| control.kind == kind && control.value == value)
+                {
+                    return Err(invalid());
+                }
+                for control in self.named(name).filter(|control| control.kind == kind) {
+                    control.checked = control.value == value;
+                }
+                Ok(())
+            }
+            ControlKind::Checkbox => self.set_checked(name, value, true),
+            ControlKind::Select { .. } => self.set_selected(name, &[value]),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Checks or unchecks the checkbox or radio button with a name and value.
+    ///
+    /// Checking a radio button unchecks the others in its group.
+    pub fn set_checked(&mut self, name: &str, value: &str, checked: bool) -> Result<(), FormError> {
+        let index = self
+            .controls
+            .iter()
+            .position(|control| {
+                control.name == name
+                    && matches!(control.kind, ControlKind::Checkbox | ControlKind::Radio)
+                    && control.value == value
+            })
+            .ok_or_else(|| FormError::InvalidValue {
+                name: name.to_owned(),
+                value: value.to_owned(),
+            })?;
+
+        if checked && self.controls[index].kind == ControlKind::Radio {
+            for control in self.named(name) {
+                if control.kind == ControlKind::Radio {
+                    control.checked = false;
+                }
+            }
+        }
+        self.controls[index].checked = checked;
+        Ok(())
+    }
+
+    /// Selects exactly the options with the values in the first select with a name.
+    ///
+    /// A select without `multiple` takes a single value.
+    pub fn set_selected(&mut self, name: &str, values: &[&str]) -> Result<(), FormError> {
+        let select = self
+            .named(name)
+            .find(|control| matches!(control.kind, ControlKind::Select { .. }))
+            .ok_or_else(|| FormError::NoSuchControl(name.to_owned()))?;
+
+        let single = select.kind == (ControlKind::Select { multiple: false });
+        let invalid = values
+            .iter()
+            .find(|value| !select.options.iter().any(|option| option.value == **value));
+        if let Some(value) = invalid.or(values.get(1).filter(|_| single)) {
+            return Err(FormError::InvalidValue {
+                name: name.to_owned(),
+                value: (*value).to_owned(),
+            });
+        }
+
+        let mut found = false;
+        for option in &mut select.options {
+            // A single select selects the first option with the value.
+            option.selected = values.contains(&option.value.as_str()) && !(single && found);
+            found |= option.selected;
+        }
+        Ok(())
+    }
+
+    /// Returns the controls with a name that take a value, which excludes buttons.
+    fn named<'s>(&'s mut self, name: &'s str) -> impl Iterator<Item = &'s mut Control<'a>> {
+        self.controls
+            .iter_mut()
+            .filter(move |control| control.name == name && !control.kind.is_button())
+    }
+
+    /// Unchecks all but the last checked radio button of each group, as the parser does when it
+    /// inserts them one after another.
+    fn uncheck_duplicate_radios(&mut self) {
+        let mut last = HashMap::new();
+        for (index, control) in self.controls.iter().enumerate() {
+            if control.kind == ControlKind::Radio && control.checked && !control.name.is_empty() {
+                last.insert(control.name.clone(), index);
+            }
+        }
+        for (index, control) in self.controls.iter_mut().enumerate() {
+            if control.kind == ControlKind::Radio && control.checked && !control.name.is_empty() {
+                control.checked = last[&control.name] == index;
+            }
+        }
+    }
+
+    /// Returns the name-value pairs that a submission by `submitter` encodes, with line breaks
+    /// normalized to CRLF. File inputs have an empty value.
+    ///
+    /// Submitting with an image button sends its click coordinates as `0`.
+    pub fn entries(&self, submitter: Option<&Control<'_>>) -> Vec<(String, String)> {
+        self.entry_list(submitter)
+            .into_iter()
+            .map(|entry| match entry {
+                Entry::Text(name, value) => (name, value),
+                Entry::File(name) => (name, String::new()),
+            })
+            .collect()
+    }
+
+    fn entry_list(&self, submitter: Option<&Control<'_>>) -> Vec<Entry> {
+        let mut entries = Vec::new();
+
+        for control in &self.controls {
+            let is_submitter =
+                submitter.is_some_and(|submitter| submitter.element == control.element);
+            let in_datalist = control
+                .element
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| ancestor.value().name() == "datalist");
+            if control.disabled
+                || in_datalist
+                || (control.kind.is_button() && !is_submitter)
+                || (matches!(control.kind, ControlKind::Checkbox | ControlKind::Radio)
+                    && !control.checked)
+            {
+                continue;
+            }
+
+            if control.kind == ControlKind::Image {
+                let prefix = if control.name.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}.", control.name)
+                };
+                Entry::text(&mut entries, &format!("{prefix}x"), "0");
+                Entry::text(&mut entries, &format!("{prefix}y"), "0");
+                continue;
+            }
+            if control.name.is_empty() {
+                continue;
+            }
+
+            match control.kind {
+                ControlKind::Select { .. } => {
+                    for option in &control.options {
+                        if option.selected && !option.disabled {
+                            Entry::text(&mut entries, &control.name, &option.value);
+                        }
+                    }
+                }
+                ControlKind::File => entries.push(Entry::File(normalize_newlines(&control.name))),
+                ControlKind::Hidden if control.name.eq_ignore_ascii_case("_charset_") => {
+                    Entry::text(&mut entries, &control.name, "UTF-8");
+                }
+                _ => Entry::text(&mut entries, &control.name, &control.value),
+            }
+
+            if matches!(control.kind, ControlKind::Text | ControlKind::Textarea)
+                && let Some(dirname) = control.element.value().attr("dirname")
+                && !dirname.is_empty()
+            {
+                Entry::text(&mut entries, dirname, "ltr");
+            }
+        }
+        entries
+    }
+
+    /// Builds the request that submitting the form with `submitter`, one of its submit buttons,
+    /// or without a submitter sends.
+    ///
+    /// The submitter's `formmethod`, `formaction` and `formenctype` override the form's `method`,
+    /// `action` and `enctype`. An empty or missing action submits to the document URL.
+    pub fn submit(&self, submitter: Option<&Control<'_>>) -> Result<Submission, FormError> {
+        if let Some(submitter) = submitter
+            && (!submitter.kind.is_submit_button()
+                || !self
+                    .controls
+                    .iter()
+                    .any(|control| control.element == submitter.element))
+        {
+            return Err(FormError::InvalidSubmitter);
+        }
+
+        let attr = |form_attr: &str, submitter_attr: &str| {
+            submitter
+                .and_then(|submitter| submitter.element.value().attr(submitter_attr))
+                .or_else(|| self.element.value().attr(form_attr))
+                .map(str::trim)
+        };
+
+        let method = match attr("method", "formmethod")
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("post") => Method::Post,
+            Some("dialog") => Method::Dialog,
+            _ => Method::Get,
+        };
+        let enctype = match attr("enctype", "formenctype")
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("multipart/form-data") => Enctype::Multipart,
+            Some("text/plain") => Enctype::TextPlain,
+            _ => Enctype::UrlEncoded,
+        };
+
+        let action = attr("action", "formaction").unwrap_or_default();
+        let mut url = match &self.base {
+            Some(base) => base.join(action).ok(),
+            None => Url::parse(action).ok(),
+        }
+        .ok_or_else(|| FormError::InvalidAction(action.to_owned()))?;
+
+        let entries = self.entry_list(submitter);
+        let mut submission = Submission {
+            method,
+            url: url.clone(),
+            enctype,
+            content_type: None,
+            body: Vec::new(),
+        };
+        match method {
+            Method::Get => {
+                url.set_query(Some(&urlencoded(&entries)));
+                submission.url = url;
+            }
+            Method::Post => {
+                let (content_type, body) = match enctype {
+                    Enctype::UrlEncoded => (
+                        "application/x-www-form-urlencoded".to_owned(),
+                        urlencoded(&entries).into_bytes(),
+                    ),
+                    Enctype::Multipart => {
+                        let (boundary, body) = multipart(&entries);
+                        (format!("multipart/form-data; boundary={boundary}"), body)
+                    }
+                    Enctype::TextPlain => {
+                        ("text/plain;charset=UTF-8".to_owned(), text_plain(&entries))
+                    }
+                };
+                submission.content_type = Some(content_type);
+                submission.body = body;
+            }
+            Method::Dialog => {}
+        }
+        Ok(submission)
+    }
+}
+
+/// Replaces every CR not followed by LF, and every LF not preceded by CR, with CRLF.
+fn normalize_newlines(value: &str) -> String {
+    value
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\n', "\r\n")
+}
+
+fn urlencoded(entries: &[Entry]) -> String {
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    for entry in entries {
+        match entry {
+            Entry::Text(name, value) => serializer.append_pair(name, value),
+            Entry::File(name) => serializer.append_pair(name, ""),
+        };
+    }
+    serializer.finish()
+}
+
+fn text_plain(entries: &[Entry]) -> Vec<u8> {
+    let mut body = String::new();
+    for entry in entries {
+        let (name, value) = match entry {
+            Entry::Text(name, value) => (name.as_str(), value.as_str()),
+            Entry::File(name) => (name.as_str(), ""),
+        };
+        body.push_str(name);
+        body.push('=');
+        body.push_str(value);
+        body.push_str("\r\n");
+    }
+    body.into_bytes()
+}
+
+/// Returns a boundary that does not occur in the entries, and the `multipart/form-data` body.
+fn multipart(entries: &[Entry]) -> (String, Vec<u8>) {
+    let occurs = |boundary: &str| {
+        entries.iter().any(|entry| match entry {
+            Entry::Text(name, value) => name.contains(boundary) || value.contains(boundary),
+            Entry::File(name) => name.contains(boundary),
+        })
+    };
+    let mut boundary = "----scraper-form-boundary".to_owned();
+    let mut counter = 0_u32;
+    while occurs(&boundary) {
+        counter += 1;
+        boundary = format!("----scraper-form-boundary-{counter}");
+    }
+
+    let escape = |name: &str| {
+        name.replace('\n', "%0A")
+            .replace('\r', "%0D")
+            .replace('"', "%22")
+    };
+    let mut body = String::new();
+    for entry in entries {
+        body.push_str("--");
+        body.push_str(&boundary);
+        body.push_str("\r\n");
+        match entry {
+            Entry::Text(name, value) => {
+                body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"\r\n\r\n{value}\r\n",
+                    escape(name)
+                ));
+            }
+            Entry::File(name) => {
+                body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"; filename=\"\"\r\n\
+                     Content-Type: application/octet-stream\r\n\r\n\r\n",
+                    escape(name)
+                ));
+            }
+        }
+    }
+    body.push_str("--");
+    body.push_str(&boundary);
+    body.push_str("--\r\n");
+    (boundary, body.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{ControlKind, Enctype, Method, forms};
+    use crate::Html;
+    use crate::error::FormError;
+
+    fn pairs(entries: &[(String, String)]) -> Vec<(&str, &str)> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn association_and_defaults() {
+        let html = Html::parse_document(
+            r#"<form id="f">
+                <input name="q" value="a&#10;b">
+                <input type="checkbox" name="c" value="1">
+                <input type="checkbox" name="c" checked>
+                <input type="radio" name="r" value="x" checked>
+                <input type="radio" name="r" value="y" checked>
+                <select name="s"><option>One</option><option value="2" selected>Two</option></select>
+                <select name="d"><option disabled>A</option><option> B  </option></select>
+                <select name="m" multiple>
+                    <option selected>A</option>
+                    <optgroup disabled><option selected>B</option></optgroup>
+                </select>
+                <textarea name="t">Line 1
+Line 2</textarea>
+                <fieldset disabled>
+                    <legend><input name="in-legend" value="1"></legend>
+                    <input name="disabled" value="1">
+                </fieldset>
+                <input name="off" disabled>
+                <datalist><input name="listed"></datalist>
+                <input name="_charset_" type="hidden">
+                <input type="text" name="dir" dirname="dir.dir">
+            </form>
+            <input name="outside" value="1" form="f">
+            <input name="orphan">
+            <form><template><input name="template"></template></form>"#,
+        );
+        let forms = forms(&html, None);
+        assert_eq!(forms.len(), 2);
+        assert!(forms[1].controls.is_empty());
+
+        let form = &forms[0];
+        assert_eq!(form.control("q").unwrap().value, "ab");
+        assert_eq!(form.control("c").unwrap().kind, ControlKind::Checkbox);
+        assert_eq!(
+            form.control("m").unwrap().kind,
+            ControlKind::Select { multiple: true }
+        );
+        assert!(form.control("disabled").unwrap().disabled);
+        assert!(!form.control("in-legend").unwrap().disabled);
+        assert_eq!(form.control("outside").unwrap().value, "1");
+        assert!(form.control("orphan").is_none());
+
+        assert_eq!(
+            pairs(&form.entries(None)),
+            [
+                ("q", "ab"),
+                ("c", "on"),
+                ("r", "y"),
+                ("s", "2"),
+                ("d", "B"),
+                ("m", "A"),
+                ("t", "Line 1\r\nLine 2"),
+                ("in-legend", "1"),
+                ("_charset_", "UTF-8"),
+                ("dir", ""),
+                ("dir.dir", "ltr"),
+                ("outside", "1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn set_values() {
+        let html = Html::parse_fragment(
+            r#"<form>
+                <input name="q"><textarea name="t"></textarea>
+                <input type="radio" name="r" value="x" checked><input type="radio" name="r" value="y">
+                <input type="checkbox" name="c" value="1"><input type="checkbox" name="c" value="2">
+                <select name="s"><option>A</option><option>B</option></select>
+                <select name="m" multiple><option>A</option><option>B</option><option>C</option></select>
+                <input type="submit" name="go">
+            </form>"#,
+        );
+        let mut form = forms(&html, None).remove(0);
+
+        form.set("q", "x\ny").unwrap();
+        form.set("t", "x\ny").unwrap();
+        form.set("r", "y").unwrap();
+        form.set("c", "2").unwrap();
+        form.set("s", "B").unwrap();
+        form.set_selected("m", &["A", "C"]).unwrap();
+        assert_eq!(
+            pairs(&form.entries(None)),
+            [
+                ("q", "xy"),
+                ("t", "x\r\ny"),
+                ("r", "y"),
+                ("c", "2"),
+                ("s", "B"),
+                ("m", "A"),
+                ("m", "C")
+            ]
+        );
+
+        form.set_checked("c", "2", false).unwrap();
+        assert!(!form.entries(None).iter().any(|(name, _)| name == "c"));
+
+        let invalid = |value: &str| FormError::InvalidValue {
+            name: if value == "z" { "r" } else { "s" }.to_owned(),
+            value: value.to_owned(),
+        };
+        assert_eq!(form.set("r", "z"), Err(invalid("z")));
+        assert_eq!(form.set("s", "Z"), Err(invalid("Z")));
+        assert_eq!(form.set_selected("s", &["A", "B"]), Err(invalid("B")));
+        assert_eq!(
+            form.set("go", "1"),
+            Err(FormError::NoSuchControl("go".to_owned()))
+        );
+    }
+
+    #[test]
+    fn submit() {
+        let html = Html::parse_document(
+            r#"<base href="https://example.com/app/">
+            <form action="search?old=1#frag">
+                <input name="q" value="a b&amp;c">
+                <button name="b1" value="one">One</button>
+                <button name="b2" value="two" formmethod="post" formaction="/post"
+                    formenctype="multipart/form-data">Two</button>
+                <input type="image" name="map">
+                <input type="reset" name="reset">
+            </form>
+            <form method="POST" enctype="text/plain"><input name="a" value="1"></form>
+            <form method="dialog"><input name="a"></form>"#,
+        );
+        let forms = forms(&html, None);
+        let form = &forms[0];
+
+        let get = form.submit(None).unwrap();
+        assert_eq!(get.method, Method::Get);
+        assert_eq!(
+            get.url.as_str(),
+            "https://example.com/app/search?q=a+b%26c#frag"
+        );
+        assert!(get.body.is_empty());
+        assert_eq!(get.content_type, None);
+
+        let submitter = &form.controls[1];
+        let get = form.submit(Some(submitter)).unwrap();
+        assert_eq!(get.url.query(), Some("q=a+b%26c&b1=one"));
+
+        let image = &form.controls[3];
+        let get = form.submit(Some(image)).unwrap();
+        assert_eq!(get.url.query(), Some("q=a+b%26c&map.x=0&map.y=0"));
+
+        let submitter = &form.controls[2];
+        let post = form.submit(Some(submitter)).unwrap();
+        assert_eq!(post.method, Method::Post);
+        assert_eq!(post.enctype, Enctype::Multipart);
+        assert_eq!(post.url.as_str(), "https://example.com/post");
+        assert_eq!(
+            post.content_type.as_deref(),
+            Some("multipart/form-data; boundary=----scraper-form-boundary")
+        );
+        assert_eq!(
+            String::from_utf8(post.body).unwrap(),
+            "------scraper-form-boundary\r\n\
+             Content-Disposition: form-data; name=\"q\"\r\n\r\na b&c\r\n\
+             ------scraper-form-boundary\r\n\
+             Content-Disposition: form-data; name=\"b2\"\r\n\r\ntwo\r\n\
+             ------scraper-form-boundary--\r\n"
+        );
+
+        assert_eq!(
+            form.submit(Some(&form.controls[4])),
+            Err(FormError::InvalidSubmitter)
+        );
+        assert_eq!(
+            form.submit(Some(&forms[1].controls[0])),
+            Err(FormError::InvalidSubmitter)
+        );
+
+        let plain = forms[1].submit(None).unwrap();
+        assert_eq!(plain.url.as_str(), "https://example.com/app/");
+        assert_eq!(
+            plain.content_type.as_deref(),
+            Some("text/plain;charset=UTF-8")
+        );
+        assert_eq!(plain.body, b"a=1\r\n");
+
+        let dialog = forms[2].submit(None).unwrap();
+        assert_eq!(dialog.method, Method::Dialog);
+        assert!(dialog.body.is_empty());
+    }
+
+    #[test]
+    fn unresolved_action() {
+        let html = Html::parse_fragment(r#"<form action="/relative"></form>"#);
+        let form = &forms(&html, None)[0];
+        assert_eq!(
+            form.submit(None),
+            Err(FormError::InvalidAction("/relative".to_owned()))
+        );
+
+        let url = Url::parse("https://example.com/").unwrap();
+        let form = &forms(&html, Some(&url))[0];
+        assert_eq!(
+            form.submit(None).unwrap().url.as_str(),
+            "https://example.com/relative?"
+        );
+    }
+}
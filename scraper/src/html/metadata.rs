@@ -0,0 +1,787 @@
+//! Document metadata from `<title>`, `<meta>`, `<link>` and `<base>` elements.
+
+use std::collections::BTreeMap;
+
+use ego_tree::iter::Edge;
+
+use super::Html;
+use crate::ElementRef;
+use crate::element_ref::is_template_contents;
+
+/// The metadata of a document, returned by [`Html::metadata`].
+///
+/// Values are taken as written, trimmed but with URLs not resolved; [`Html::base_url`] gives the
+/// URL to resolve them against. Where a document declares a single-valued field more than once,
+/// the first declaration in document order wins. List fields collect every declaration in
+/// document order, without duplicates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// The text of the first `<title>`, with whitespace collapsed.
+    pub title: Option<String>,
+
+    /// The content of the first `<meta name="description">`.
+    pub description: Option<String>,
+
+    /// The comma-separated keywords of every `<meta name="keywords">`.
+    pub keywords: Vec<String>,
+
+    /// The `href` of the first `<link rel="canonical">`.
+    pub canonical: Option<String>,
+
+    /// The `href` of the first `<base>` with one.
+    pub base: Option<String>,
+
+    /// The OpenGraph properties.
+    pub open_graph: OpenGraph,
+
+    /// The Twitter card properties.
+    pub twitter: Twitter,
+
+    /// The directives of every `<meta name="robots">`.
+    pub robots: Robots,
+
+    /// The `<link rel="alternate">` elements with `hreflang`.
+    pub alternates: Vec<Alternate>,
+
+    /// The `<link rel="alternate">` elements of RSS, Atom and JSON feeds.
+    pub feeds: Vec<Feed>,
+
+    /// The `<link>` elements of icons, such as `rel="icon"` and `rel="apple-touch-icon"`.
+    pub icons: Vec<Icon>,
+
+    /// The first `<meta http-equiv="refresh">` with a valid content.
+    pub refresh: Option<Refresh>,
+
+    /// The character encoding declared by the first `<meta charset>`, or `<meta
+    /// http-equiv="content-type">` with a charset, lowercased.
+    pub charset: Option<String>,
+}
+
+/// OpenGraph properties, from `<meta property="og:...">`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpenGraph {
+    /// `og:title`.
+    pub title: Option<String>,
+
+    /// `og:type`.
+    pub kind: Option<String>,
+
+    /// `og:url`.
+    pub url: Option<String>,
+
+    /// `og:description`.
+    pub description: Option<String>,
+
+    /// `og:site_name`.
+    pub site_name: Option<String>,
+
+    /// `og:locale`.
+    pub locale: Option<String>,
+
+    /// `og:image` and its structured properties, such as `og:image:width`.
+    pub images: Vec<Media>,
+
+    /// `og:video` and its structured properties.
+    pub videos: Vec<Media>,
+
+    /// `og:audio` and its structured properties.
+    pub audio: Vec<Media>,
+
+    /// Every `og:` property by name without the prefix, such as `image:width` or
+    /// `locale:alternate`, with its values in document order.
+    pub properties: BTreeMap<String, Vec<String>>,
+}
+
+/// An OpenGraph image, video or audio file.
+///
+/// An `og:image` or `og:image:url` starts a new image; the structured properties that follow,
+/// such as `og:image:width`, describe it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Media {
+    /// The URL.
+    pub url: Option<String>,
+
+    /// `:secure_url`.
+    pub secure_url: Option<String>,
+
+    /// `:type`, the MIME type.
+    pub media_type: Option<String>,
+
+    /// `:width`, in pixels.
+    pub width: Option<u32>,
+
+    /// `:height`, in pixels.
+    pub height: Option<u32>,
+
+    /// `:alt`.
+    pub alt: Option<String>,
+}
+
+/// Twitter card properties, from `<meta name="twitter:...">`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Twitter {
+    /// `twitter:card`, such as `summary_large_image`.
+    pub card: Option<String>,
+
+    /// `twitter:site`, the account of the site.
+    pub site: Option<String>,
+
+    /// `twitter:creator`, the account of the author.
+    pub creator: Option<String>,
+
+    /// `twitter:title`.
+    pub title: Option<String>,
+
+    /// `twitter:description`.
+    pub description: Option<String>,
+
+    /// `twitter:image`, or the older `twitter:image:src`.
+    pub image: Option<String>,
+
+    /// `twitter:image:alt`.
+    pub image_alt: Option<String>,
+
+    /// Every `twitter:` property by name without the prefix, with its first value.
+    pub properties: BTreeMap<String, String>,
+}
+
+/// Robots directives.
+///
+/// The directives of all `<meta name="robots">` elements are combined, so a page is not indexed if
+/// any of them says `noindex` or `none`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Robots {
+    /// The page should not be indexed, from `noindex` or `none`.
+    pub noindex: bool,
+
+    /// The links of the page should not be followed, from `nofollow` or `none`.
+    pub nofollow: bool,
+
+    /// Every directive, lowercased, such as `noarchive` or `max-snippet:50`.
+    pub directives: Vec<String>,
+}
+
+/// A translation of the document, from `<link rel="alternate" hreflang>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternate {
+    /// The URL.
+    pub href: String,
+
+    /// The language, such as `en-GB` or `x-default`.
+    pub hreflang: String,
+}
+
+/// A feed, from `<link rel="alternate">` with a feed `type`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    /// The URL.
+    pub href: String,
+
+    /// The format.
+    pub kind: FeedKind,
+
+    /// The `title`.
+    pub title: Option<String>,
+}
+
+/// The format of a [`Feed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeedKind {
+    /// `application/rss+xml`.
+    Rss,
+
+    /// `application/atom+xml`.
+    Atom,
+
+    /// `application/feed+json` or `application/json`.
+    Json,
+}
+
+/// An icon, from `<link rel="icon">` and similar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    /// The URL.
+    pub href: String,
+
+    /// The icon `rel` value, lowercased, such as `icon`, `apple-touch-icon` or `mask-icon`.
+    pub rel: String,
+
+    /// The sizes the icon is suitable for, from `sizes`.
+    pub sizes: Vec<IconSize>,
+
+    /// The `type`.
+    pub media_type: Option<String>,
+}
+
+/// A size from the `sizes` of an [`Icon`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconSize {
+    /// `any`, for scalable icons.
+    Any,
+
+    /// A size in pixels, such as `32x32`.
+    Pixels {
+        /// The width.
+        width: u32,
+
+        /// The height.
+        height: u32,
+    },
+}
+
+/// A refresh or redirect, from `<meta http-equiv="refresh">`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refresh {
+    /// The number of seconds to wait.
+    pub delay: u32,
+
+    /// The URL to go to, or `None` to reload the page.
+    pub url: Option<String>,
+}
+
+impl Html {
+    /// Returns the metadata of the document, such as its title, description, canonical URL and
+    /// OpenGraph properties.
+    ///
+    /// Elements inside `<template>` contents are ignored. See [`Metadata`] for how repeated
+    /// declarations are resolved.
+    ///
+    /// Requires the `metadata` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(
+    ///     r#"<title> Hello
+    ///     world </title>
+    ///     <meta property="og:image" content="/a.png">
+    ///     <meta property="og:image:width" content="640">
+    ///     <meta property="og:image" content="/b.png">
+    ///     <meta name="robots" content="noindex">"#,
+    /// );
+    /// let metadata = html.metadata();
+    ///
+    /// assert_eq!(metadata.title.as_deref(), Some("Hello world"));
+    /// assert_eq!(metadata.open_graph.images.len(), 2);
+    /// assert_eq!(metadata.open_graph.images[0].width, Some(640));
+    /// assert!(metadata.robots.noindex);
+    /// ```
+    pub fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata::default();
+        let mut skipped = None;
+        for edge in self.tree.root().traverse() {
+            match edge {
+                Edge::Open(node) if skipped.is_none() => {
+                    if is_template_contents(node) {
+                        skipped = Some(node.id());
+                    } else if let Some(element) = ElementRef::wrap(node)
+                        && element.value().name.ns == ns!(html)
+                    {
+                        metadata.add(element);
+                    }
+                }
+                Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+                _ => {}
+            }
+        }
+        metadata
+    }
+}
+
+impl Metadata {
+    fn add(&mut self, element: ElementRef<'_>) {
+        let value = element.value();
+        let attr = |name: &str| value.attr(name).map(str::trim);
+        match value.name() {
+            "title" if self.title.is_none() => {
+                let text = element.text().collect::<String>();
+                self.title = Some(text.split_ascii_whitespace().collect::<Vec<_>>().join(" "));
+            }
+            "base" if self.base.is_none() => self.base = attr("href").map(str::to_owned),
+            "meta" => self.add_meta(element),
+            "link" => {
+                if let Some(href) = attr("href").filter(|href| !href.is_empty()) {
+                    self.add_link(element, href);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_meta(&mut self, element: ElementRef<'_>) {
+        let value = element.value();
+        if self.charset.is_none()
+            && let Some(charset) = value.attr("charset")
+        {
+            let charset = charset.trim();
+            self.charset = (!charset.is_empty()).then(|| charset.to_ascii_lowercase());
+        }
+
+        let Some(content) = value.attr("content").map(str::trim) else {
+            return;
+        };
+
+        if let Some(http_equiv) = value.attr("http-equiv") {
+            let http_equiv = http_equiv.trim();
+            if http_equiv.eq_ignore_ascii_case("refresh") && self.refresh.is_none() {
+                self.refresh = parse_refresh(content);
+            } else if http_equiv.eq_ignore_ascii_case("content-type") && self.charset.is_none() {
+                self.charset = content_type_charset(content);
+            }
+        }
+
+        // OpenGraph uses `property` and Twitter uses `name`, but pages mix them up.
+        for key in ["property", "name"] {
+            let Some(key) = value.attr(key) else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            if let Some(name) = key.strip_prefix("og:") {
+                self.open_graph.add(name, content);
+            } else if let Some(name) = key.strip_prefix("twitter:") {
+                self.twitter.add(name, content);
+            } else {
+                match key.as_str() {
+                    "description" if self.description.is_none() => {
+                        self.description = Some(content.to_owned());
+                    }
+                    "keywords" => {
+                        for keyword in content.split(',').map(str::trim) {
+                            if !keyword.is_empty() && !self.keywords.iter().any(|k| k == keyword) {
+                                self.keywords.push(keyword.to_owned());
+                            }
+                        }
+                    }
+                    "robots" => self.robots.add(content),
+                    _ => {}
+                }
+            }
+            // A declaration with both attributes counts once.
+            break;
+        }
+    }
+
+    fn add_link(&mut self, element: ElementRef<'_>, href: &str) {
+        let value = element.value();
+        let attr = |name: &str| {
+            value
+                .attr(name)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        let rels = value
+            .attr("rel")
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect::<Vec<_>>();
+        let has_rel = |rel: &str| rels.iter().any(|r| r == rel);
+
+        if has_rel("canonical") && self.canonical.is_none() {
+            self.canonical = Some(href.to_owned());
+        }
+
+        if has_rel("alternate") {
+            if let Some(hreflang) = attr("hreflang") {
+                let alternate = Alternate {
+                    href: href.to_owned(),
+                    hreflang: hreflang.to_owned(),
+                };
+                if !self.alternates.contains(&alternate) {
+                    self.alternates.push(alternate);
+                }
+            }
+
+            let kind = attr("type").and_then(|media_type| {
+                match media_type.to_ascii_lowercase().as_str() {
+                    "application/rss+xml" => Some(FeedKind::Rss),
+                    "application/atom+xml" => Some(FeedKind::Atom),
+                    "application/feed+json" | "application/json" => Some(FeedKind::Json),
+                    _ => None,
+                }
+            });
+            if let Some(kind) = kind
+                && !self.feeds.iter().any(|feed| feed.href == href)
+            {
+                self.feeds.push(Feed {
+                    href: href.to_owned(),
+                    kind,
+                    title: attr("title").map(str::to_owned),
+                });
+            }
+        }
+
+        // `shortcut icon` is the legacy spelling of `icon`.
+        let rel = if has_rel("icon") {
+            Some("icon")
+        } else {
+            [
+                "apple-touch-icon",
+                "apple-touch-icon-precomposed",
+                "mask-icon",
+            ]
+            .into_iter()
+            .find(|rel| has_rel(rel))
+        };
+        if let Some(rel) = rel
+            && !self
+                .icons
+                .iter()
+                .any(|icon| icon.href == href && icon.rel == rel)
+        {
+            self.icons.push(Icon {
+                href: href.to_owned(),
+                rel: rel.to_owned(),
+                sizes: attr("sizes").map(parse_sizes).unwrap_or_default(),
+                media_type: attr("type").map(str::to_owned),
+            });
+        }
+    }
+}
+
+impl OpenGraph {
+    fn add(&mut self, name: &str, content: &str) {
+        let values = self.properties.entry(name.to_owned()).or_default();
+        values.push(content.to_owned());
+
+        let field = match name {
+            "title" => &mut self.title,
+            "type" => &mut self.kind,
+            "url" => &mut self.url,
+            "description" => &mut self.description,
+            "site_name" => &mut self.site_name,
+            "locale" => &mut self.locale,
+            _ => {
+                let (kind, property) = name.split_once(':').unwrap_or((name, "url"));
+                let media = match kind {
+                    "image" => &mut self.images,
+                    "video" => &mut self.videos,
+                    "audio" => &mut self.audio,
+                    _ => return,
+                };
+                add_media(media, property, content);
+                return;
+            }
+        };
+        set(field, content);
+    }
+}
+
+fn add_media(media: &mut Vec<Media>, property: &str, content: &str) {
+    if property == "url" {
+        media.push(Media {
+            url: Some(content.to_owned()),
+            ..Default::default()
+        });
+        return;
+    }
+
+    // Structured properties before any URL describe a media without one.
+    if media.is_empty() {
+        media.push(Media::default());
+    }
+    let last = media.last_mut().unwrap();
+    match property {
+        "secure_url" => set(&mut last.secure_url, content),
+        "type" => set(&mut last.media_type, content),
+        "alt" => set(&mut last.alt, content),
+        "width" => last.width = last.width.or_else(|| content.parse().ok()),
+        "height" => last.height = last.height.or_else(|| content.parse().ok()),
+        _ => {}
+    }
+}
+
+/// Sets a field unless an earlier declaration already set it.
+fn set(field: &mut Option<String>, content: &str) {
+    if field.is_none() {
+        *field = Some(content.to_owned());
+    }
+}
+
+impl Twitter {
+    fn add(&mut self, name: &str, content: &str) {
+        if self.properties.contains_key(name) {
+            return;
+        }
+        self.properties.insert(name.to_owned(), content.to_owned());
+
+        let field = match name {
+            "card" => &mut self.card,
+            "site" => &mut self.site,
+            "creator" => &mut self.creator,
+            "title" => &mut self.title,
+            "description" => &mut self.description,
+            "image" | "image:src" => &mut self.image,
+            "image:alt" => &mut self.image_alt,
+            _ => return,
+        };
+        set(field, content);
+    }
+}
+
+impl Robots {
+    fn add(&mut self, content: &str) {
+        for directive in content.split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            match directive.as_str() {
+                "" => continue,
+                "noindex" => self.noindex = true,
+                "nofollow" => self.nofollow = true,
+                "none" => {
+                    self.noindex = true;
+                    self.nofollow = true;
+                }
+                _ => {}
+            }
+            if !self.directives.contains(&directive) {
+                self.directives.push(directive);
+            }
+        }
+    }
+}
+
+/// Parses the content of a refresh declaration, such as `5; url=/next`.
+fn parse_refresh(content: &str) -> Option<Refresh> {
+    let digits = content
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(content.len());
+    let (delay, rest) = content.split_at(digits);
+    let delay = delay.split('.').next()?.parse().ok()?;
+
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ';' || c == ',');
+    if rest.is_empty() {
+        return Some(Refresh { delay, url: None });
+    }
+
+    let url = match rest.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("url") => {
+            let rest = rest[3..].trim_start();
+            rest.strip_prefix('=').map_or(rest, str::trim_start)
+        }
+        _ => rest,
+    };
+    let url = match url.chars().next() {
+        Some(quote @ ('"' | '\'')) => url[1..].split(quote).next().unwrap_or_default(),
+        _ => url,
+    };
+    Some(Refresh {
+        delay,
+        url: Some(url.trim().to_owned()),
+    })
+}
+
+/// Returns the charset parameter of a content type, such as `text/html; charset=utf-8`.
+fn content_type_charset(content: &str) -> Option<String> {
+    content.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(['"', '\'']).to_ascii_lowercase())
+            .filter(|charset| !charset.is_empty())
+    })
+}
+
+fn parse_sizes(sizes: &str) -> Vec<IconSize> {
+    sizes
+        .split_ascii_whitespace()
+        .filter_map(|size| {
+            if size.eq_ignore_ascii_case("any") {
+                return Some(IconSize::Any);
+            }
+            let (width, height) = size.split_once(['x', 'X'])?;
+            Some(IconSize::Pixels {
+                width: width.parse().ok()?,
+                height: height.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alternate, FeedKind, IconSize, Media, Refresh, parse_refresh};
+    use crate::Html;
+
+    #[test]
+    fn head() {
+        let html = Html::parse_document(
+            r#"<meta charset="UTF-8">
+            <meta http-equiv="Content-Type" content="text/html; charset=latin1">
+            <title>First</title><title>Second</title>
+            <base href="/root/"><base href="/other/">
+            <meta name="description" content=" About us ">
+            <meta name="description" content="Ignored">
+            <meta name="keywords" content="rust, html,, scraping">
+            <meta name="keywords" content="html, css">
+            <link rel="canonical" href="https://example.com/page">
+            <link rel="canonical" href="https://example.com/other">
+            <meta name="robots" content="noarchive, NOFOLLOW">
+            <meta name="robots" content="max-snippet:50">
+            <meta http-equiv="refresh" content="5; URL='/next'">
+            <svg><title>Not this</title></svg>
+            <template><meta name="description" content="Template"></template>"#,
+        );
+        let metadata = html.metadata();
+
+        assert_eq!(metadata.charset.as_deref(), Some("utf-8"));
+        assert_eq!(metadata.title.as_deref(), Some("First"));
+        assert_eq!(metadata.base.as_deref(), Some("/root/"));
+        assert_eq!(metadata.description.as_deref(), Some("About us"));
+        assert_eq!(metadata.keywords, ["rust", "html", "scraping", "css"]);
+        assert_eq!(
+            metadata.canonical.as_deref(),
+            Some("https://example.com/page")
+        );
+        assert!(!metadata.robots.noindex);
+        assert!(metadata.robots.nofollow);
+        assert_eq!(
+            metadata.robots.directives,
+            ["noarchive", "nofollow", "max-snippet:50"]
+        );
+        assert_eq!(
+            metadata.refresh,
+            Some(Refresh {
+                delay: 5,
+                url: Some("/next".to_owned())
+            })
+        );
+    }
+
+    #[test]
+    fn charset_from_content_type() {
+        let html = Html::parse_document(
+            r#"<meta http-equiv="content-type" content="text/html; charset=&quot;Shift_JIS&quot;">"#,
+        );
+        assert_eq!(html.metadata().charset.as_deref(), Some("shift_jis"));
+    }
+
+    #[test]
+    fn refresh() {
+        let refresh = |delay, url: Option<&str>| {
+            Some(Refresh {
+                delay,
+                url: url.map(str::to_owned),
+            })
+        };
+        assert_eq!(parse_refresh("0"), refresh(0, None));
+        assert_eq!(parse_refresh("3.5;"), refresh(3, None));
+        assert_eq!(parse_refresh("0;url=/a b"), refresh(0, Some("/a b")));
+        assert_eq!(parse_refresh("1, /b"), refresh(1, Some("/b")));
+        assert_eq!(parse_refresh("2; url = \"/c\" x"), refresh(2, Some("/c")));
+        assert_eq!(parse_refresh("soon"), None);
+    }
+
+    #[test]
+    fn social() {
+        let html = Html::parse_document(
+            r#"<meta property="og:title" content="Title">
+            <meta property="og:type" content="article">
+            <meta property="og:image" content="/a.png">
+            <meta property="og:image:width" content="640">
+            <meta property="og:image:height" content="wide">
+            <meta property="og:image:url" content="/b.png">
+            <meta property="og:image:alt" content="B">
+            <meta property="og:locale:alternate" content="fr_FR">
+            <meta property="og:locale:alternate" content="de_DE">
+            <meta property="og:title" content="Ignored">
+            <meta name="twitter:card" content="summary">
+            <meta property="twitter:image:src" content="/t.png">
+            <meta name="twitter:image" content="/ignored.png">
+            <meta name="twitter:label1" content="Reading time">"#,
+        );
+        let metadata = html.metadata();
+
+        let og = &metadata.open_graph;
+        assert_eq!(og.title.as_deref(), Some("Title"));
+        assert_eq!(og.kind.as_deref(), Some("article"));
+        assert_eq!(
+            og.images,
+            [
+                Media {
+                    url: Some("/a.png".to_owned()),
+                    width: Some(640),
+                    ..Default::default()
+                },
+                Media {
+                    url: Some("/b.png".to_owned()),
+                    alt: Some("B".to_owned()),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(og.properties["locale:alternate"], ["fr_FR", "de_DE"]);
+        assert_eq!(og.properties["title"], ["Title", "Ignored"]);
+
+        let twitter = &metadata.twitter;
+        assert_eq!(twitter.card.as_deref(), Some("summary"));
+        assert_eq!(twitter.image.as_deref(), Some("/t.png"));
+        assert_eq!(twitter.properties["label1"], "Reading time");
+    }
+
+    #[test]
+    fn links() {
+        let html = Html::parse_document(
+            r#"<link rel="alternate" hreflang="en" href="/en/">
+            <link rel="alternate" hreflang="x-default" href="/">
+            <link rel="alternate" hreflang="en" href="/en/">
+            <link rel="alternate" type="application/rss+xml" title="News" href="/rss">
+            <link rel="alternate" type="application/atom+xml" href="/atom">
+            <link rel="alternate" type="text/html" href="/print">
+            <link rel="shortcut icon" href="/favicon.ico">
+            <link rel="icon" sizes="16x16 32X32 bad" type="image/png" href="/icon.png">
+            <link rel="icon" sizes="any" href="/icon.svg">
+            <link rel="apple-touch-icon" href="/touch.png">
+            <link rel="icon">"#,
+        );
+        let metadata = html.metadata();
+
+        assert_eq!(
+            metadata.alternates,
+            [
+                Alternate {
+                    href: "/en/".to_owned(),
+                    hreflang: "en".to_owned()
+                },
+                Alternate {
+                    href: "/".to_owned(),
+                    hreflang: "x-default".to_owned()
+                },
+            ]
+        );
+
+        let feeds = &metadata.feeds;
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(
+            (
+                feeds[0].href.as_str(),
+                feeds[0].kind,
+                feeds[0].title.as_deref()
+            ),
+            ("/rss", FeedKind::Rss, Some("News"))
+        );
+        assert_eq!(feeds[1].kind, FeedKind::Atom);
+
+        let icons = &metadata.icons;
+        assert_eq!(icons.len(), 4);
+        assert_eq!(icons[0].rel, "icon");
+        assert!(icons[0].sizes.is_empty());
+        assert_eq!(
+            icons[1].sizes,
+            [
+                IconSize::Pixels {
+                    width: 16,
+                    height: 16
+                },
+                IconSize::Pixels {
+                    width: 32,
+                    height: 32
+                },
+            ]
+        );
+        assert_eq!(icons[1].media_type.as_deref(), Some("image/png"));
+        assert_eq!(icons[2].sizes, [IconSize::Any]);
+        assert_eq!(icons[3].rel, "apple-touch-icon");
+    }
+}
@@ -5,23 +5,45 @@ use std::borrow::Cow;
 use std::fmt;
 use std::iter::FusedIterator;
 
-use ego_tree::Tree;
-use ego_tree::iter::Nodes;
-use html5ever::serialize::SerializeOpts;
+use ego_tree::{NodeId, NodeRef, Tree};
+#[cfg(feature = "pretty")]
+use html5ever::serialize::Serialize;
+use html5ever::serialize::{SerializeOpts, TraversalScope};
 use html5ever::tree_builder::QuirksMode;
-use html5ever::{QualName, driver, serialize};
+use html5ever::{QualName, serialize};
 use selectors::matching::SelectorCaches;
-use tendril::TendrilSink;
+#[cfg(feature = "url")]
+use url::Url;
+#[cfg(not(feature = "spans"))]
+use {
+    html5ever::TokenizerResult, html5ever::driver, html5ever::tree_builder::TreeSink,
+    tendril::StrTendril,
+};
 
+use crate::element_ref::is_template_contents;
+use crate::error::LimitExceeded;
+#[cfg(feature = "errors")]
+use crate::error::ParseError;
+#[cfg(feature = "pretty")]
+use crate::pretty::{PrettyOptions, PrettySerializer};
 use crate::selector::Selector;
-use crate::{ElementRef, Node};
+use crate::{ElementRef, Node, NodeMut};
 
+#[cfg(feature = "metadata")]
+pub use metadata::{
+    Alternate, Feed, FeedKind, Icon, IconSize, Media, Metadata, OpenGraph, Refresh, Robots, Twitter,
+};
+pub use options::{
+    CharacterReferences, Limit, LimitAction, Limits, ParseOptions, SerializeOptions,
+};
 pub use tree_sink::HtmlTreeSink;
+pub(crate) use tree_sink::LimitedTreeSink;
 
 /// An HTML tree.
 ///
 /// Parsing does not fail hard. Instead, the `quirks_mode` is set and errors are added to the
-/// `errors` field. The `tree` will still be populated as best as possible.
+/// `errors` field, with their kind and location available from [`Html::parse_errors`]. The `tree`
+/// will still be populated as best as possible.
 ///
 /// Implements the `TreeSink` trait from the `html5ever` crate, which allows HTML to be parsed.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,6 +52,9 @@ pub struct Html {
     /// Parse errors.
     pub errors: Vec<Cow<'static, str>>,
 
+    #[cfg(feature = "errors")]
+    pub(crate) parse_errors: Vec<ParseError>,
+
     /// The quirks mode.
     pub quirks_mode: QuirksMode,
 
@@ -43,6 +68,8 @@ impl Html {
         Html {
             #[cfg(feature = "errors")]
             errors: Vec::new(),
+            #[cfg(feature = "errors")]
+            parse_errors: Vec::new(),
             quirks_mode: QuirksMode::NoQuirks,
             tree: Tree::new(Node::Document),
         }
@@ -53,11 +80,26 @@ impl Html {
         Html {
             #[cfg(feature = "errors")]
             errors: Vec::new(),
+            #[cfg(feature = "errors")]
+            parse_errors: Vec::new(),
             quirks_mode: QuirksMode::NoQuirks,
             tree: Tree::new(Node::Fragment),
         }
     }
 
+    /// Returns the parse errors with their kind and location, in the same order as the messages
+    /// in `errors`.
+    #[cfg(feature = "errors")]
+    pub fn parse_errors(&self) -> &[ParseError] {
+        &self.parse_errors
+    }
+
+    #[cfg(feature = "errors")]
+    pub(crate) fn push_error(&mut self, error: ParseError) {
+        self.errors.push(error.message.clone());
+        self.parse_errors.push(error);
+    }
+
     /// Parses a string of HTML as a document.
     ///
     /// This is a convenience method for the following:
@@ -76,28 +118,122 @@ impl Html {
     /// let html = parser.one(document);
     /// # }
     /// ```
+    ///
+    /// With the `spans` feature enabled, the location of each element in `document` is recorded
+    /// as well.
     pub fn parse_document(document: &str) -> Self {
-        let parser =
-            driver::parse_document(HtmlTreeSink::new(Self::new_document()), Default::default());
-        parser.one(document)
+        Self::parse_document_with(document, ParseOptions::default())
+    }
+
+    /// Parses a string of HTML as a document using the given options.
+    ///
+    /// If a limit set to [`LimitAction::Abort`] is exceeded, the document is returned as parsed up
+    /// to that point.
+    pub fn parse_document_with(document: &str, options: ParseOptions) -> Self {
+        Self::parse(document, options, None).0
+    }
+
+    /// Parses a string of HTML as a document, failing if a limit set to [`LimitAction::Abort`]
+    /// is exceeded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use scraper::error::LimitKind;
+    /// use scraper::html::{Limit, Limits, ParseOptions};
+    ///
+    /// let options = ParseOptions::default().limits(Limits {
+    ///     max_depth: Some(Limit::abort(16)),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// let error = Html::try_parse_document(&"<div>".repeat(100), options).unwrap_err();
+    /// assert_eq!(error.kind, LimitKind::Depth);
+    /// ```
+    pub fn try_parse_document(
+        document: &str,
+        options: ParseOptions,
+    ) -> Result<Self, LimitExceeded> {
+        match Self::parse(document, options, None) {
+            (_, Some(error)) => Err(error),
+            (html, None) => Ok(html),
+        }
     }
 
     /// Parses a string of HTML as a fragment.
+    ///
+    /// With the `spans` feature enabled, the location of each element in `fragment` is recorded
+    /// as well.
     pub fn parse_fragment(fragment: &str) -> Self {
-        let parser = driver::parse_fragment(
-            HtmlTreeSink::new(Self::new_fragment()),
-            Default::default(),
-            QualName::new(None, ns!(html), local_name!("body")),
-            Vec::new(),
-            false,
-        );
-        parser.one(fragment)
+        Self::parse_fragment_with(fragment, ParseOptions::default())
+    }
+
+    /// Parses a string of HTML as a fragment using the given options.
+    ///
+    /// If a limit set to [`LimitAction::Abort`] is exceeded, the fragment is returned as parsed up
+    /// to that point.
+    pub fn parse_fragment_with(fragment: &str, options: ParseOptions) -> Self {
+        Self::parse(fragment, options, Some(body())).0
+    }
+
+    /// Parses a string of HTML as a fragment, failing if a limit set to [`LimitAction::Abort`]
+    /// is exceeded.
+    pub fn try_parse_fragment(
+        fragment: &str,
+        options: ParseOptions,
+    ) -> Result<Self, LimitExceeded> {
+        match Self::parse(fragment, options, Some(body())) {
+            (_, Some(error)) => Err(error),
+            (html, None) => Ok(html),
+        }
+    }
+
+    /// Parses a string of HTML as a fragment in the context of an element with the given name.
+    pub(crate) fn parse_fragment_in(fragment: &str, context: QualName) -> Self {
+        Self::parse(fragment, ParseOptions::default(), Some(context)).0
     }
 
-    /// Returns an iterator over elements matching a selector.
+    fn parse(
+        input: &str,
+        options: ParseOptions,
+        context: Option<QualName>,
+    ) -> (Self, Option<LimitExceeded>) {
+        let html = if context.is_some() {
+            Self::new_fragment()
+        } else {
+            Self::new_document()
+        };
+        let sink = LimitedTreeSink::new(html, options.limits);
+
+        #[cfg(feature = "spans")]
+        let result = crate::span::parse(input, sink, options.to_parse_opts(), context);
+
+        #[cfg(not(feature = "spans"))]
+        let result = {
+            let parser = match context {
+                Some(context) => driver::parse_fragment(
+                    sink,
+                    options.to_parse_opts(),
+                    context,
+                    Vec::new(),
+                    false,
+                ),
+                None => driver::parse_document(sink, options.to_parse_opts()),
+            };
+            feed(parser, input)
+        };
+
+        result
+    }
+
+    /// Returns an iterator over elements matching a selector, in document order.
+    ///
+    /// Only nodes attached to the tree root are considered. Elements inside the contents of
+    /// `<template>` elements are skipped unless [`Select::include_template_contents`] is called.
     pub fn select<'a, 'b>(&'a self, selector: &'b Selector) -> Select<'a, 'b> {
         Select {
-            inner: self.tree.nodes(),
+            inner: DocumentOrder::new(&self.tree),
             selector,
             caches: Default::default(),
         }
@@ -114,26 +250,273 @@ impl Html {
         ElementRef::wrap(root_node).unwrap()
     }
 
+    /// Returns the URL that relative URLs in the document are resolved against.
+    ///
+    /// This is the `href` of the first `<base>` element with one, resolved against
+    /// `document_url`, or `document_url` itself. A relative `<base href>` without a document URL
+    /// is ignored.
+    ///
+    /// Requires the `url` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"<base href="/docs/"><a href="intro.html">Intro</a>"#);
+    /// let document_url = Url::parse("https://example.com/index.html").unwrap();
+    /// assert_eq!(
+    ///     html.base_url(Some(&document_url)).unwrap().as_str(),
+    ///     "https://example.com/docs/"
+    /// );
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn base_url(&self, document_url: Option<&Url>) -> Option<Url> {
+        let href = self
+            .root_element()
+            .descendent_elements()
+            .filter(|element| element.value().name() == "base")
+            .find_map(|base| base.value().attr("href"));
+
+        match (href, document_url) {
+            (Some(href), Some(url)) => url.join(href.trim()).ok().or_else(|| Some(url.clone())),
+            (Some(href), None) => Url::parse(href.trim()).ok(),
+            (None, url) => url.cloned(),
+        }
+    }
+
+    /// Returns a mutable handle to the tree root.
+    pub fn root_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::new(self.tree.root_mut())
+    }
+
+    /// Returns a mutable handle to the root `<html>` element.
+    pub fn root_element_mut(&mut self) -> NodeMut<'_> {
+        let root = self.root_element().id();
+        NodeMut::new(self.tree.get_mut(root).unwrap())
+    }
+
+    /// Returns a mutable handle to a node, or `None` if `id` is not a node of this tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::{Html, Node, Selector};
+    ///
+    /// let mut html = Html::parse_fragment("<p>one</p><p>two</p>");
+    /// let p = html.select(&Selector::parse("p").unwrap()).next().unwrap().id();
+    ///
+    /// html.get_mut(p).unwrap().append(Node::new_text(" and a half"));
+    /// assert_eq!(html.root_element().inner_html(), "<p>one and a half</p><p>two</p>");
+    /// ```
+    pub fn get_mut(&mut self, id: NodeId) -> Option<NodeMut<'_>> {
+        self.tree.get_mut(id).map(NodeMut::new)
+    }
+
     /// Serialize entire document into HTML.
     pub fn html(&self) -> String {
         let opts = SerializeOpts {
-            scripting_enabled: false, // It's not clear what this does.
-            traversal_scope: serialize::TraversalScope::IncludeNode,
+            // Escape the contents of `<noscript>` elements like other text.
+            scripting_enabled: false,
+            traversal_scope: TraversalScope::IncludeNode,
             create_missing_parent: false,
         };
         let mut buf = Vec::new();
         serialize(&mut buf, self, opts).unwrap();
         String::from_utf8(buf).unwrap()
     }
+
+    /// Serialize entire document into HTML with the given options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use scraper::html::SerializeOptions;
+    ///
+    /// let html = Html::parse_fragment("<ul>\n  <li class=\"a\">One</li>\n  <li>Two</li>\n</ul>");
+    /// let options = SerializeOptions::default()
+    ///     .omit_optional_tags(true)
+    ///     .minimal_attribute_quotes(true)
+    ///     .collapse_whitespace(true);
+    /// assert_eq!(html.html_with(options), "<ul><li class=a>One<li>Two</ul>");
+    /// ```
+    pub fn html_with(&self, options: SerializeOptions) -> String {
+        crate::node::serializable::serialize_with(
+            self.tree.root(),
+            TraversalScope::IncludeNode,
+            options,
+        )
+    }
+
+    /// Serialize entire document into indented HTML, see [`pretty`](crate::pretty).
+    #[cfg(feature = "pretty")]
+    pub fn pretty(&self) -> String {
+        self.pretty_with(PrettyOptions::default())
+    }
+
+    /// Serialize entire document into indented HTML with the given options.
+    #[cfg(feature = "pretty")]
+    pub fn pretty_with(&self, options: PrettyOptions) -> String {
+        let mut serializer = PrettySerializer::new(Vec::new(), options);
+        Serialize::serialize(self, &mut serializer, TraversalScope::IncludeNode).unwrap();
+        String::from_utf8(serializer.finish().unwrap()).unwrap()
+    }
+}
+
+/// The name of the `<body>` element, which is the context of fragments by default.
+pub(crate) fn body() -> QualName {
+    QualName::new(None, ns!(html), local_name!("body"))
+}
+
+/// Feeds `input` to `parser` in chunks, so that parsing can stop soon after a limit is exhausted.
+#[cfg(not(feature = "spans"))]
+fn feed(parser: driver::Parser<LimitedTreeSink>, input: &str) -> (Html, Option<LimitExceeded>) {
+    const CHUNK_SIZE: usize = 16 * 1024;
+
+    // The sink empties its input queue once a limit stops parsing, which ends the current chunk.
+    let sink = &parser.tokenizer.sink.sink;
+    let mut start = 0;
+    while start < input.len() && !sink.is_stopped() {
+        let mut end = (start + CHUNK_SIZE).min(input.len());
+        while !input.is_char_boundary(end) {
+            end += 1;
+        }
+        sink.input()
+            .push_back(StrTendril::from_slice(&input[start..end]));
+        while !matches!(parser.tokenizer.feed(sink.input()), TokenizerResult::Done) {}
+        start = end;
+    }
+
+    parser.tokenizer.end();
+    let sink = parser.tokenizer.sink.sink;
+    let aborted = sink.aborted();
+    (sink.finish(), aborted)
+}
+
+/// The nodes attached to a tree root, in document order from either end.
+#[derive(Debug, Clone)]
+struct DocumentOrder<'a> {
+    front: Option<NodeRef<'a, Node>>,
+    back: Option<NodeRef<'a, Node>>,
+    /// Upper bound on the number of nodes left, from the size of the arena.
+    remaining: usize,
+    /// Whether the descendants of the contents of `<template>` elements are skipped.
+    skip_template_contents: bool,
+}
+
+impl<'a> DocumentOrder<'a> {
+    fn new(tree: &'a Tree<Node>) -> Self {
+        let root = tree.root();
+        let mut order = Self {
+            front: Some(root),
+            back: None,
+            remaining: tree.nodes().len(),
+            skip_template_contents: true,
+        };
+        order.back = Some(order.last_descendant(root));
+        order
+    }
+
+    /// Stops skipping the contents of `<template>` elements.
+    fn include_template_contents(&mut self) {
+        self.skip_template_contents = false;
+        if let Some(back) = self.back {
+            self.back = Some(self.last_descendant(back));
+        }
+    }
+
+    fn first_child(&self, node: NodeRef<'a, Node>) -> Option<NodeRef<'a, Node>> {
+        if self.skip_template_contents && is_template_contents(node) {
+            return None;
+        }
+        node.first_child()
+    }
+
+    /// Returns the last node of the subtree rooted at `node` in document order.
+    fn last_descendant(&self, mut node: NodeRef<'a, Node>) -> NodeRef<'a, Node> {
+        while let Some(child) = node.last_child() {
+            if self.skip_template_contents && is_template_contents(node) {
+                break;
+            }
+            node = child;
+        }
+        node
+    }
+
+    fn take(&mut self, node: NodeRef<'a, Node>) -> NodeRef<'a, Node> {
+        self.remaining -= 1;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        }
+        node
+    }
+}
+
+impl<'a> Iterator for DocumentOrder<'a> {
+    type Item = NodeRef<'a, Node>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front?;
+        let next = self.first_child(node).or_else(|| {
+            let mut ancestor = Some(node);
+            while let Some(current) = ancestor {
+                if let Some(sibling) = current.next_sibling() {
+                    return Some(sibling);
+                }
+                ancestor = current.parent();
+            }
+            None
+        });
+        let node = self.take(node);
+        if self.front.is_some() {
+            self.front = next;
+        }
+        Some(node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for DocumentOrder<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back?;
+        let prev = match node.prev_sibling() {
+            Some(sibling) => Some(self.last_descendant(sibling)),
+            None => node.parent(),
+        };
+        let node = self.take(node);
+        if self.back.is_some() {
+            self.back = prev;
+        }
+        Some(node)
+    }
 }
 
 /// Iterator over elements matching a selector.
 pub struct Select<'a, 'b> {
-    inner: Nodes<'a, Node>,
+    inner: DocumentOrder<'a>,
     selector: &'b Selector,
     caches: SelectorCaches,
 }
 
+impl Select<'_, '_> {
+    /// Also yields matching elements inside the contents of `<template>` elements.
+    pub fn include_template_contents(mut self) -> Self {
+        self.inner.include_template_contents();
+        self
+    }
+
+    fn accepts(&mut self, element: &ElementRef) -> bool {
+        self.selector
+            .matches_with_scope_and_cache(element, None, &mut self.caches)
+    }
+}
+
 impl fmt::Debug for Select<'_, '_> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("Select")
@@ -158,12 +541,9 @@ impl<'a> Iterator for Select<'a, '_> {
     type Item = ElementRef<'a>;
 
     fn next(&mut self) -> Option<ElementRef<'a>> {
-        for node in self.inner.by_ref() {
+        while let Some(node) = self.inner.next() {
             if let Some(element) = ElementRef::wrap(node)
-                && element.parent().is_some()
-                && self
-                    .selector
-                    .matches_with_scope_and_cache(&element, None, &mut self.caches)
+                && self.accepts(&element)
             {
                 return Some(element);
             }
@@ -180,12 +560,9 @@ impl<'a> Iterator for Select<'a, '_> {
 
 impl DoubleEndedIterator for Select<'_, '_> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        for node in self.inner.by_ref().rev() {
+        while let Some(node) = self.inner.next_back() {
             if let Some(element) = ElementRef::wrap(node)
-                && element.parent().is_some()
-                && self
-                    .selector
-                    .matches_with_scope_and_cache(&element, None, &mut self.caches)
+                && self.accepts(&element)
             {
                 return Some(element);
             }
@@ -196,6 +573,11 @@ impl DoubleEndedIterator for Select<'_, '_> {
 
 impl FusedIterator for Select<'_, '_> {}
 
+#[cfg(feature = "metadata")]
+mod metadata;
+mod options;
+#[cfg(feature = "serde")]
+mod serde;
 mod serializable;
 mod tree_sink;
 
@@ -259,10 +641,175 @@ mod tests {
         assert_eq!(upper, Some(10));
     }
 
+    #[cfg(feature = "errors")]
+    #[test]
+    fn errors_have_kind_and_line() {
+        use crate::error::ParseErrorKind;
+
+        let html = Html::parse_document("<!DOCTYPE html>\n<p a=1 a=2>\n&bogus; x</b>");
+        let errors = html
+            .parse_errors()
+            .iter()
+            .map(|error| (error.kind, error.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                (ParseErrorKind::DuplicateAttribute, 2),
+                (ParseErrorKind::BadCharacterReference, 3),
+                (ParseErrorKind::UnexpectedEndTag, 3),
+            ]
+        );
+    }
+
+    #[cfg(feature = "errors")]
+    #[test]
+    fn exact_errors() {
+        use super::ParseOptions;
+        use crate::error::ParseErrorKind;
+
+        let document = "<!DOCTYPE html><html></html><p>";
+
+        let html = Html::parse_document(document);
+        assert_eq!(html.errors, ["Unexpected token"]);
+        assert_eq!(html.parse_errors()[0].kind, ParseErrorKind::UnexpectedToken);
+
+        let options = ParseOptions::default().exact_errors(true);
+        let html = Html::parse_document_with(document, options);
+        assert_eq!(html.errors.len(), 1);
+        assert_eq!(
+            html.parse_errors()[0].kind,
+            ParseErrorKind::UnexpectedStartTag
+        );
+        assert!(html.errors[0].contains("AfterAfterBody"));
+    }
+
+    fn limited(limits: super::Limits) -> super::ParseOptions {
+        super::ParseOptions::default().limits(limits)
+    }
+
+    #[test]
+    fn limit_depth_truncates_document() {
+        use super::{Limit, Limits};
+
+        let options = limited(Limits {
+            max_depth: Some(Limit::truncate(3)),
+            ..Default::default()
+        });
+        let html = Html::parse_fragment_with("<div><p><b>bold</b> text</p>tail</div>", options);
+        assert_eq!(html.root_element().inner_html(), "<div><p></p></div>");
+    }
+
+    #[test]
+    fn limits_stop_allocating_nodes() {
+        use super::{Limit, Limits};
+
+        let document = "<div>".repeat(100_000);
+        let options = limited(Limits {
+            max_depth: Some(Limit::truncate(10)),
+            ..Default::default()
+        });
+        let html = Html::parse_document_with(&document, options);
+        assert!(html.tree.nodes().count() < 20);
+
+        let document = "<p>x<!-- y -->".repeat(100_000);
+        let options = limited(Limits {
+            max_nodes: Some(Limit::truncate(100)),
+            ..Default::default()
+        });
+        let html = Html::parse_document_with(&document, options);
+        assert!(html.tree.nodes().count() <= 102);
+    }
+
+    #[test]
+    fn limit_nodes_truncates_document() {
+        use super::{Limit, Limits};
+
+        let options = limited(Limits {
+            max_nodes: Some(Limit::truncate(6)),
+            ..Default::default()
+        });
+        let html = Html::parse_fragment_with("<p>one</p><p>two</p><p>three</p>", options);
+        assert_eq!(html.root_element().inner_html(), "<p>one</p><p></p>");
+    }
+
+    #[test]
+    fn limit_attributes() {
+        use super::{Limit, Limits};
+
+        let options = limited(Limits {
+            max_attributes: Some(Limit::truncate(2)),
+            max_attribute_length: Some(Limit::truncate(3)),
+            ..Default::default()
+        });
+        let html = Html::parse_fragment_with(r#"<a b="12345" c="é€" d="x">"#, options);
+        assert_eq!(html.root_element().inner_html(), r#"<a b="123" c="é"></a>"#);
+    }
+
+    #[test]
+    fn limit_text_bytes_truncates_document() {
+        use super::{Limit, Limits};
+
+        let options = limited(Limits {
+            max_text_bytes: Some(Limit::truncate(5)),
+            ..Default::default()
+        });
+        let html = Html::parse_fragment_with("<p>abc</p><p>def</p><p>ghi</p>", options);
+        assert_eq!(html.root_element().inner_html(), "<p>abc</p><p>de</p>");
+        #[cfg(feature = "errors")]
+        {
+            use crate::error::ParseErrorKind;
+
+            let error = html.parse_errors().last().unwrap();
+            assert_eq!(error.kind, ParseErrorKind::LimitExceeded);
+            assert_eq!(error.message, "Maximum text size of 5 exceeded");
+        }
+    }
+
+    #[test]
+    fn limit_abort() {
+        use super::{Limit, Limits};
+        use crate::error::{LimitExceeded, LimitKind};
+
+        let options = limited(Limits {
+            max_depth: Some(Limit::truncate(2)),
+            max_attributes: Some(Limit::abort(1)),
+            ..Default::default()
+        });
+
+        let html = Html::try_parse_fragment("<div><p><b>x</b></p></div>", options).unwrap();
+        assert_eq!(html.root_element().inner_html(), "<div></div>");
+
+        let error = Html::try_parse_fragment("<p a b>x</p>", options).unwrap_err();
+        assert_eq!(
+            error,
+            LimitExceeded {
+                kind: LimitKind::Attributes,
+                max: 1,
+            }
+        );
+
+        let html = Html::parse_fragment_with("<p>x</p><p a b>y</p><p>z</p>", options);
+        assert_eq!(html.root_element().inner_html(), "<p>x</p>");
+    }
+
+    #[test]
+    fn tree_sink_wraps_html() {
+        use crate::HtmlTreeSink;
+        use html5ever::driver;
+        use html5ever::tendril::TendrilSink;
+        use std::cell::RefCell;
+
+        let source = "<title>a</title><p a=1>b";
+        let sink = HtmlTreeSink(RefCell::new(Html::new_document()));
+        let html = driver::parse_document(sink, Default::default()).one(source);
+        assert_eq!(html.html(), Html::parse_document(source).html());
+    }
+
     #[cfg(feature = "atomic")]
     #[test]
-    fn html_is_send() {
-        fn send_sync<S: Send>() {}
+    fn html_is_send_and_sync() {
+        fn send_sync<S: Send + Sync>() {}
         send_sync::<Html>();
     }
 }
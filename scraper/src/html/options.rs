@@ -0,0 +1,229 @@
+use html5ever::driver::ParseOpts;
+use html5ever::tokenizer::TokenizerOpts;
+use html5ever::tree_builder::TreeBuilderOpts;
+
+/// Options for parsing HTML with [`Html::parse_document_with`] and [`Html::parse_fragment_with`].
+///
+/// Options are built from the defaults, so that more can be added without breaking callers:
+///
+/// ```
+/// use scraper::html::ParseOptions;
+///
+/// let options = ParseOptions::default().exact_errors(true);
+/// assert!(options.exact_errors);
+/// ```
+///
+/// [`Html::parse_document_with`]: crate::Html::parse_document_with
+/// [`Html::parse_fragment_with`]: crate::Html::parse_fragment_with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Report parse errors with detailed messages, such as the offending token and insertion
+    /// mode, at some performance cost.
+    pub exact_errors: bool,
+
+    /// Resource limits enforced while building the tree.
+    pub limits: Limits,
+}
+
+impl ParseOptions {
+    /// Sets whether parse errors have detailed messages.
+    pub fn exact_errors(mut self, exact_errors: bool) -> Self {
+        self.exact_errors = exact_errors;
+        self
+    }
+
+    /// Sets the resource limits.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub(crate) fn to_parse_opts(self) -> ParseOpts {
+        ParseOpts {
+            tokenizer: TokenizerOpts {
+                exact_errors: self.exact_errors,
+                ..Default::default()
+            },
+            tree_builder: TreeBuilderOpts {
+                exact_errors: self.exact_errors,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Options for serializing HTML with [`Html::html_with`] and [`ElementRef::html_with`].
+///
+/// The default options write the same HTML as [`Html::html`]. Enabling everything but
+/// `scripting_enabled` and `escape_non_ascii` minifies the HTML without changing how it parses
+/// or renders.
+///
+/// Options are built from the defaults, so that more can be added without breaking callers:
+///
+/// ```
+/// use scraper::html::SerializeOptions;
+///
+/// let options = SerializeOptions::default().omit_optional_tags(true);
+/// assert!(options.omit_optional_tags);
+/// ```
+///
+/// [`Html::html_with`]: crate::Html::html_with
+/// [`ElementRef::html_with`]: crate::ElementRef::html_with
+/// [`Html::html`]: crate::Html::html
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SerializeOptions {
+    /// Write the contents of `<noscript>` elements as raw text, as the parser reads them when
+    /// scripting is enabled, which is the default of [`Html::parse_document`]. Otherwise they are
+    /// escaped like other text.
+    ///
+    /// [`Html::parse_document`]: crate::Html::parse_document
+    pub scripting_enabled: bool,
+
+    /// Leave out the start and end tags that the HTML syntax allows to be omitted, such as
+    /// `</p>`, `</li>` and `<tbody>`. Start tags are only omitted without attributes.
+    pub omit_optional_tags: bool,
+
+    /// Leave attribute values unquoted when they can be, write empty values as bare attribute
+    /// names, and quote values that contain double quotes with single quotes.
+    pub minimal_attribute_quotes: bool,
+
+    /// Collapse runs of whitespace in text to a single space, and remove whitespace next to
+    /// block elements such as `<div>`, except within `<pre>`, `<textarea>`, raw text elements
+    /// and foreign elements.
+    pub collapse_whitespace: bool,
+
+    /// Leave out comments.
+    pub remove_comments: bool,
+
+    /// How escaped characters are written.
+    pub character_references: CharacterReferences,
+
+    /// Escape characters outside of ASCII with numeric character references, for output in a
+    /// legacy encoding. Raw text, comments and names are written as they are.
+    pub escape_non_ascii: bool,
+}
+
+impl SerializeOptions {
+    /// Sets whether `<noscript>` contents are written as raw text.
+    pub fn scripting_enabled(mut self, scripting_enabled: bool) -> Self {
+        self.scripting_enabled = scripting_enabled;
+        self
+    }
+
+    /// Sets whether optional start and end tags are left out.
+    pub fn omit_optional_tags(mut self, omit_optional_tags: bool) -> Self {
+        self.omit_optional_tags = omit_optional_tags;
+        self
+    }
+
+    /// Sets whether attribute values are only quoted where needed.
+    pub fn minimal_attribute_quotes(mut self, minimal_attribute_quotes: bool) -> Self {
+        self.minimal_attribute_quotes = minimal_attribute_quotes;
+        self
+    }
+
+    /// Sets whether whitespace in text is collapsed.
+    pub fn collapse_whitespace(mut self, collapse_whitespace: bool) -> Self {
+        self.collapse_whitespace = collapse_whitespace;
+        self
+    }
+
+    /// Sets whether comments are left out.
+    pub fn remove_comments(mut self, remove_comments: bool) -> Self {
+        self.remove_comments = remove_comments;
+        self
+    }
+
+    /// Sets how escaped characters are written.
+    pub fn character_references(mut self, character_references: CharacterReferences) -> Self {
+        self.character_references = character_references;
+        self
+    }
+
+    /// Sets whether characters outside of ASCII are escaped.
+    pub fn escape_non_ascii(mut self, escape_non_ascii: bool) -> Self {
+        self.escape_non_ascii = escape_non_ascii;
+        self
+    }
+}
+
+/// How characters are escaped, see [`SerializeOptions`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharacterReferences {
+    /// Named references such as `&amp;` and `&nbsp;`.
+    #[default]
+    Named,
+
+    /// Decimal references such as `&#38;` and `&#160;`.
+    Numeric,
+}
+
+/// What to do when a [`Limit`] is exceeded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitAction {
+    /// Drop whatever exceeds the limit, record a parse error and keep going.
+    ///
+    /// Excess attributes are dropped and attribute values are shortened. When an element would be
+    /// nested too deeply, or the total number of nodes or bytes of text is exhausted, the document
+    /// is truncated at that point of the input and no more nodes are allocated.
+    #[default]
+    Truncate,
+
+    /// Stop parsing. [`Html::try_parse_document`] and [`Html::try_parse_fragment`] return an
+    /// error, other parsing methods return the document as parsed up to that point.
+    ///
+    /// [`Html::try_parse_document`]: crate::Html::try_parse_document
+    /// [`Html::try_parse_fragment`]: crate::Html::try_parse_fragment
+    Abort,
+}
+
+/// A maximum together with the action taken when it is exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limit {
+    /// The largest allowed value.
+    pub max: usize,
+
+    /// The action taken when `max` is exceeded.
+    pub action: LimitAction,
+}
+
+impl Limit {
+    /// Creates a limit which truncates the document when exceeded.
+    pub fn truncate(max: usize) -> Self {
+        Self {
+            max,
+            action: LimitAction::Truncate,
+        }
+    }
+
+    /// Creates a limit which aborts parsing when exceeded.
+    pub fn abort(max: usize) -> Self {
+        Self {
+            max,
+            action: LimitAction::Abort,
+        }
+    }
+}
+
+/// Resource limits for parsing untrusted input.
+///
+/// All limits are unset by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The maximum nesting depth of nodes, where children of the root have a depth of 1.
+    pub max_depth: Option<Limit>,
+
+    /// The maximum total number of nodes, including the root.
+    pub max_nodes: Option<Limit>,
+
+    /// The maximum number of attributes on a single element.
+    pub max_attributes: Option<Limit>,
+
+    /// The maximum length of a single attribute value in bytes.
+    pub max_attribute_length: Option<Limit>,
+
+    /// The maximum total size of all text nodes in bytes.
+    pub max_text_bytes: Option<Limit>,
+}
@@ -0,0 +1,334 @@
+//! Serde support for documents and fragments.
+//!
+//! An `Html` serializes as a `(quirks_mode, errors, nodes)` tuple. The nodes of the tree are
+//! listed in document order as `(depth, node)` tuples, starting with the root at depth 0, so the
+//! representation is flat however deeply the document is nested. Parse errors are
+//! `(message, line, column)` tuples, and are empty without the `errors` feature, so the same
+//! representation is read and written with any set of features.
+
+use std::fmt;
+
+use ::serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+use ego_tree::iter::Edge;
+use ego_tree::{NodeId, NodeRef, Tree};
+use html5ever::tree_builder::QuirksMode;
+
+use super::Html;
+#[cfg(feature = "errors")]
+use crate::error::ParseError;
+use crate::node::Node;
+
+impl Serialize for Html {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[cfg(feature = "errors")]
+        let errors = &self.parse_errors;
+        #[cfg(not(feature = "errors"))]
+        let errors: &[()] = &[];
+        (Quirks(self.quirks_mode), errors, Nodes(self.tree.root())).serialize(serializer)
+    }
+}
+
+/// Deserialization fails if the root is not a document or fragment, if a node other than an
+/// element or fragment has children, if a fragment is anything but the first child of a
+/// `<template>` element, or if the depths do not describe a tree.
+impl<'de> Deserialize<'de> for Html {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[cfg(feature = "errors")]
+        type Errors = Vec<ParseError>;
+        #[cfg(not(feature = "errors"))]
+        type Errors = de::IgnoredAny;
+
+        #[cfg_attr(not(feature = "errors"), allow(unused_variables))]
+        let (Quirks(quirks_mode), parse_errors, OwnedTree(tree)) =
+            <(Quirks, Errors, OwnedTree)>::deserialize(deserializer)?;
+
+        Ok(Html {
+            #[cfg(feature = "errors")]
+            errors: parse_errors
+                .iter()
+                .map(|error: &ParseError| error.message.clone())
+                .collect(),
+            #[cfg(feature = "errors")]
+            parse_errors,
+            quirks_mode,
+            tree,
+        })
+    }
+}
+
+struct Nodes<'a>(NodeRef<'a, Node>);
+
+impl Serialize for Nodes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut depth = 0_usize;
+        serializer.collect_seq(self.0.traverse().filter_map(|edge| match edge {
+            Edge::Open(node) => {
+                depth += 1;
+                Some((depth - 1, node.value()))
+            }
+            Edge::Close(_) => {
+                depth -= 1;
+                None
+            }
+        }))
+    }
+}
+
+struct OwnedTree(Tree<Node>);
+
+impl<'de> Deserialize<'de> for OwnedTree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(TreeVisitor)
+    }
+}
+
+struct TreeVisitor;
+
+impl<'de> Visitor<'de> for TreeVisitor {
+    type Value = OwnedTree;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a sequence of nodes with their depths")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut tree = match seq.next_element::<(usize, Node)>()? {
+            Some((0, node @ (Node::Document | Node::Fragment))) => Tree::new(node),
+            Some(_) => {
+                return Err(de::Error::custom(
+                    "the root must be a document or fragment at depth 0",
+                ));
+            }
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+
+        // The open ancestors of the next node, by depth.
+        let mut ancestors: Vec<NodeId> = vec![tree.root().id()];
+        while let Some((depth, node)) = seq.next_element::<(usize, Node)>()? {
+            if depth == 0 || depth > ancestors.len() {
+                return Err(de::Error::custom(format!(
+                    "a node at depth {depth} cannot follow a node at depth {}",
+                    ancestors.len() - 1
+                )));
+            }
+            ancestors.truncate(depth);
+
+            let mut parent = tree.get_mut(ancestors[depth - 1]).unwrap();
+            let first_child = !parent.has_children();
+            match (parent.value(), &node) {
+                (_, Node::Document) => {
+                    return Err(de::Error::custom("a document cannot be a child"));
+                }
+                (Node::Element(element), Node::Fragment)
+                    if first_child
+                        && element.name.ns == ns!(html)
+                        && element.name() == "template" => {}
+                (_, Node::Fragment) => {
+                    return Err(de::Error::custom(
+                        "only `<template>` elements have a fragment child, as their first child",
+                    ));
+                }
+                (Node::Document | Node::Fragment | Node::Element(_), _) => {}
+                _ => {
+                    return Err(de::Error::custom(
+                        "only documents, fragments and elements have children",
+                    ));
+                }
+            }
+            ancestors.push(parent.append(node).id());
+        }
+
+        Ok(OwnedTree(tree))
+    }
+}
+
+const QUIRKS_MODES: &[&str] = &["quirks", "limited_quirks", "no_quirks"];
+
+struct Quirks(QuirksMode);
+
+impl Serialize for Quirks {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let index = match self.0 {
+            QuirksMode::Quirks => 0,
+            QuirksMode::LimitedQuirks => 1,
+            QuirksMode::NoQuirks => 2,
+        };
+        serializer.serialize_unit_variant("QuirksMode", index, QUIRKS_MODES[index as usize])
+    }
+}
+
+impl<'de> Deserialize<'de> for Quirks {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("QuirksMode", QUIRKS_MODES, QuirksVisitor)
+    }
+}
+
+struct QuirksVisitor;
+
+impl<'de> Visitor<'de> for QuirksVisitor {
+    type Value = Quirks;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a quirks mode")
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        use de::VariantAccess;
+
+        let (index, access) = data.variant_seed(QuirksIndex)?;
+        access.unit_variant()?;
+        Ok(Quirks(match index {
+            0 => QuirksMode::Quirks,
+            1 => QuirksMode::LimitedQuirks,
+            _ => QuirksMode::NoQuirks,
+        }))
+    }
+}
+
+struct QuirksIndex;
+
+impl<'de> de::DeserializeSeed<'de> for QuirksIndex {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl Visitor<'_> for QuirksIndex {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a quirks mode")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match usize::try_from(v) {
+            Ok(index) if index < QUIRKS_MODES.len() => Ok(index),
+            _ => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        QUIRKS_MODES
+            .iter()
+            .position(|mode| *mode == v)
+            .ok_or_else(|| E::unknown_variant(v, QUIRKS_MODES))
+    }
+}
+
+/// Parse errors serialize as their message, line and column, and their kind is classified again
+/// when they are deserialized.
+#[cfg(feature = "errors")]
+impl Serialize for ParseError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&*self.message, self.line, self.column).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "errors")]
+impl<'de> Deserialize<'de> for ParseError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (message, line, column) = <(String, u64, Option<u64>)>::deserialize(deserializer)?;
+        let mut error = ParseError::new(message.into(), line);
+        error.column = column;
+        Ok(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Html, Selector};
+
+    fn round_trip(html: &Html) -> Html {
+        let json = serde_json::to_string(html).unwrap();
+        let copy: Html = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&copy).unwrap(), json);
+        copy
+    }
+
+    #[test]
+    fn document() {
+        let html = Html::parse_document(
+            r##"<!DOCTYPE html><html><head><title>T</title></head><body>
+            <!-- note --><p id="a" class="x y" data-z="1" title="t">Text &amp; more</p>
+            <svg><use xlink:href="#icon"/></svg><template><b>inert</b></template>
+            </body></html>"##,
+        );
+        let copy = round_trip(&html);
+        assert_eq!(copy.html(), html.html());
+        assert_eq!(copy.quirks_mode, html.quirks_mode);
+
+        let p = copy
+            .select(&Selector::parse("p.y#a").unwrap())
+            .next()
+            .unwrap();
+        assert_eq!(p.value().id(), Some("a"));
+        assert!(p.value().classes().eq(["x", "y"]));
+        let names = p.value().attrs().map(|(name, _)| name).collect::<Vec<_>>();
+        #[cfg(feature = "deterministic")]
+        assert_eq!(names, ["id", "class", "data-z", "title"]);
+        #[cfg(not(feature = "deterministic"))]
+        assert_eq!(names, ["class", "data-z", "id", "title"]);
+        assert!(
+            copy.select(&Selector::parse("template b").unwrap())
+                .next()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn fragment() {
+        let html = Html::parse_fragment("<td>cell</td><?pi data?><p>a<br>b");
+        let copy = round_trip(&html);
+        assert_eq!(copy.root_element().html(), html.root_element().html());
+        assert!(copy.tree.root().value().is_fragment());
+    }
+
+    #[cfg(feature = "errors")]
+    #[test]
+    fn errors() {
+        let html = Html::parse_document("<p></div>");
+        assert!(!html.errors.is_empty());
+        let copy = round_trip(&html);
+        assert_eq!(copy.errors, html.errors);
+        assert_eq!(copy.parse_errors(), html.parse_errors());
+    }
+
+    #[test]
+    fn compact_representation() {
+        let html = Html::parse_fragment(r#"<a href="/">A</a>"#);
+        assert_eq!(
+            serde_json::to_string(&html).unwrap(),
+            r#"["no_quirks",[],[[0,"fragment"],[1,{"element":[[null,"html","html"],[]]}],[2,{"element":[[null,"html","a"],[[[null,"","href"],"/"]]]}],[3,{"text":"A"}]]]"#
+        );
+    }
+
+    #[test]
+    fn invalid_trees() {
+        for json in [
+            r#"["no_quirks",[],[]]"#,
+            r#"["no_quirks",[],[[0,{"text":"a"}]]]"#,
+            r#"["no_quirks",[],[[1,"document"]]]"#,
+            r#"["no_quirks",[],[[0,"document"],[1,{"text":"a"}],[2,{"text":"b"}]]]"#,
+            r#"["no_quirks",[],[[0,"document"],[1,"document"]]]"#,
+            r#"["no_quirks",[],[[0,"document"],[0,"document"]]]"#,
+            r#"["no_quirks",[],[[0,"document"],[2,{"text":"a"}]]]"#,
+            r#"["no_quirks",[],[[0,"document"],[1,"fragment"]]]"#,
+            r#"["no_quirks",[],[[0,"document"],[1,{"element":[[null,"html","p"],[]]}],[2,"fragment"]]]"#,
+            r#"["no_quirks",[],[[0,"document"],[1,{"element":[[null,"html","template"],[]]}],[2,"fragment"],[2,"fragment"]]]"#,
+        ] {
+            assert!(serde_json::from_str::<Html>(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn deeply_nested() {
+        let depth = 10_000;
+        let html = Html::parse_fragment(&"<span>".repeat(depth));
+        let copy = round_trip(&html);
+        assert_eq!(copy.root_element().html(), html.root_element().html());
+        assert_eq!(copy.tree.nodes().count(), depth + 2);
+    }
+}
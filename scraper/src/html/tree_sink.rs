@@ -1,15 +1,23 @@
-use super::Html;
+use super::{Html, Limit, LimitAction, Limits};
+use crate::error::{LimitExceeded, LimitKind};
 use crate::node::{Comment, Doctype, Element, Node, ProcessingInstruction, Text};
-use crate::tendril_util::make as make_tendril;
+use crate::tendril_util::{make as make_tendril, push as push_str};
 use ego_tree::NodeId;
 use html5ever::Attribute;
 use html5ever::QualName;
 use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::BufferQueue;
 use html5ever::tree_builder::{ElementFlags, NodeOrText, QuirksMode, TreeSink};
 use std::borrow::Cow;
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
+
+#[cfg(feature = "errors")]
+use crate::error::{ParseError, ParseErrorKind};
 
 /// Wraps `Html` instances as sinks to drive parsing
+///
+/// The sink enforces no [`Limits`] and does not keep track of lines, so the parse errors it
+/// records have a [`line`](crate::error::ParseError::line) of 0.
 #[derive(Debug)]
 pub struct HtmlTreeSink(pub RefCell<Html>);
 
@@ -20,7 +28,6 @@ impl HtmlTreeSink {
     }
 }
 
-/// Note: does not support the `<template>` element.
 impl TreeSink for HtmlTreeSink {
     type Output = Html;
     type Handle = NodeId;
@@ -33,7 +40,7 @@ impl TreeSink for HtmlTreeSink {
     // Signal a parse error.
     fn parse_error(&self, msg: Cow<'static, str>) {
         #[cfg(feature = "errors")]
-        self.0.borrow_mut().errors.push(msg);
+        self.0.borrow_mut().push_error(ParseError::new(msg, 0));
         #[cfg(not(feature = "errors"))]
         let _ = msg;
     }
@@ -81,7 +88,6 @@ impl TreeSink for HtmlTreeSink {
         _flags: ElementFlags,
     ) -> Self::Handle {
         let fragment = name.expanded() == expanded_name!(html "template");
-
         let mut this = self.0.borrow_mut();
         let mut node = this.tree.orphan(Node::Element(Element::new(name, attrs)));
 
@@ -139,18 +145,18 @@ impl TreeSink for HtmlTreeSink {
             }
 
             NodeOrText::AppendText(text) => {
-                let text = make_tendril(text);
-
                 let did_concat = parent.last_child().is_some_and(|mut n| match n.value() {
                     Node::Text(t) => {
-                        t.text.push_tendril(&text);
+                        push_str(&mut t.text, &text);
                         true
                     }
                     _ => false,
                 });
 
                 if !did_concat {
-                    parent.append(Node::Text(Text { text }));
+                    parent.append(Node::Text(Text {
+                        text: make_tendril(text),
+                    }));
                 }
             }
         }
@@ -179,18 +185,18 @@ impl TreeSink for HtmlTreeSink {
                 }
 
                 NodeOrText::AppendText(text) => {
-                    let text = make_tendril(text);
-
                     let did_concat = sibling.prev_sibling().is_some_and(|mut n| match n.value() {
                         Node::Text(t) => {
-                            t.text.push_tendril(&text);
+                            push_str(&mut t.text, &text);
                             true
                         }
                         _ => false,
                     });
 
                     if !did_concat {
-                        sibling.insert_before(Node::Text(Text { text }));
+                        sibling.insert_before(Node::Text(Text {
+                            text: make_tendril(text),
+                        }));
                     }
                 }
             }
@@ -295,3 +301,386 @@ impl TreeSink for HtmlTreeSink {
         }
     }
 }
+
+/// Wraps an [`HtmlTreeSink`] to enforce resource limits and keep track of the line being parsed.
+#[derive(Debug)]
+pub(crate) struct LimitedTreeSink {
+    pub(crate) sink: HtmlTreeSink,
+    /// The line of the input currently being parsed.
+    #[cfg(feature = "errors")]
+    line: Cell<u64>,
+    limits: Limits,
+    nodes: Cell<usize>,
+    text_bytes: Cell<usize>,
+    exceeded: RefCell<Vec<LimitKind>>,
+    stopped: Cell<bool>,
+    aborted: Cell<Option<LimitExceeded>>,
+    input: BufferQueue,
+}
+
+impl LimitedTreeSink {
+    /// Wrap a `Html` instance as a sink to drive parsing, enforcing the given resource limits
+    pub(crate) fn new(html: Html, limits: Limits) -> Self {
+        let nodes = html.tree.nodes().count();
+        Self {
+            sink: HtmlTreeSink::new(html),
+            #[cfg(feature = "errors")]
+            line: Cell::new(1),
+            limits,
+            nodes: Cell::new(nodes),
+            text_bytes: Cell::new(0),
+            exceeded: RefCell::new(Vec::new()),
+            stopped: Cell::new(false),
+            aborted: Cell::new(None),
+            input: BufferQueue::default(),
+        }
+    }
+
+    /// Returns true once no more input should be parsed, because a limit on the depth, the total
+    /// number of nodes or bytes of text was exhausted or an aborting limit was exceeded.
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopped.get()
+    }
+
+    /// Returns the first aborting limit that was exceeded.
+    pub(crate) fn aborted(&self) -> Option<LimitExceeded> {
+        self.aborted.get()
+    }
+
+    fn exceed(&self, kind: LimitKind, limit: Limit) {
+        let stops = matches!(
+            kind,
+            LimitKind::Depth | LimitKind::Nodes | LimitKind::TextBytes
+        );
+        if limit.action == LimitAction::Abort || stops {
+            self.stopped.set(true);
+            self.input.replace_with(BufferQueue::default());
+        }
+
+        let error = LimitExceeded {
+            kind,
+            max: limit.max,
+        };
+        if limit.action == LimitAction::Abort && self.aborted.get().is_none() {
+            self.aborted.set(Some(error));
+        }
+
+        // Only the first time a limit is exceeded is reported.
+        let mut exceeded = self.exceeded.borrow_mut();
+        if exceeded.contains(&kind) {
+            return;
+        }
+        exceeded.push(kind);
+
+        #[cfg(feature = "errors")]
+        {
+            let mut error = ParseError::new(Cow::Owned(error.to_string()), self.line.get());
+            error.kind = ParseErrorKind::LimitExceeded;
+            self.sink.0.borrow_mut().push_error(error);
+        }
+    }
+
+    /// Counts newly created nodes, returning false if they exceed the limit on the number of nodes.
+    fn count_nodes(&self, count: usize) -> bool {
+        let nodes = self.nodes.get() + count;
+        self.nodes.set(nodes);
+
+        if let Some(limit) = self.limits.max_nodes
+            && nodes > limit.max
+        {
+            self.exceed(LimitKind::Nodes, limit);
+            return false;
+        }
+        true
+    }
+
+    /// The input queue which the tokenizer is fed from. It is emptied once parsing stops, so that
+    /// the rest of the current chunk is not parsed.
+    pub(crate) fn input(&self) -> &BufferQueue {
+        &self.input
+    }
+
+    /// Applies the limits to a node or text about to be attached to `parent`. `merges` is true if
+    /// text would be merged into an existing text node.
+    fn admit(
+        &self,
+        parent: NodeId,
+        merges: bool,
+        child: NodeOrText<NodeId>,
+    ) -> Option<NodeOrText<NodeId>> {
+        if self.is_stopped() {
+            return None;
+        }
+
+        match child {
+            NodeOrText::AppendNode(id) => {
+                let is_element = self
+                    .sink
+                    .0
+                    .borrow()
+                    .tree
+                    .get(id)
+                    .unwrap()
+                    .value()
+                    .is_element();
+                (!is_element || self.admit_depth(parent)).then_some(NodeOrText::AppendNode(id))
+            }
+            NodeOrText::AppendText(text) => {
+                if !merges && !self.count_nodes(1) {
+                    return None;
+                }
+                let text = self.admit_text(text);
+                (!text.is_empty()).then_some(NodeOrText::AppendText(text))
+            }
+        }
+    }
+
+    /// Returns the part of `text` that fits into the limit on the total size of text.
+    fn admit_text(&self, text: StrTendril) -> StrTendril {
+        let bytes = self.text_bytes.get() + text.len();
+        self.text_bytes.set(bytes);
+
+        match self.limits.max_text_bytes {
+            Some(limit) if bytes > limit.max => {
+                self.exceed(LimitKind::TextBytes, limit);
+                let fits = text.len() - (bytes - limit.max);
+                StrTendril::from_slice(truncate(&text, fits))
+            }
+            _ => text,
+        }
+    }
+
+    /// Applies the limits on the number and length of attributes.
+    fn admit_attrs(&self, existing: usize, mut attrs: Vec<Attribute>) -> Vec<Attribute> {
+        if let Some(limit) = self.limits.max_attributes
+            && existing + attrs.len() > limit.max
+        {
+            self.exceed(LimitKind::Attributes, limit);
+            attrs.truncate(limit.max.saturating_sub(existing));
+        }
+
+        if let Some(limit) = self.limits.max_attribute_length {
+            for attr in &mut attrs {
+                if attr.value.len() > limit.max {
+                    self.exceed(LimitKind::AttributeLength, limit);
+                    attr.value = StrTendril::from_slice(truncate(&attr.value, limit.max));
+                }
+            }
+        }
+
+        attrs
+    }
+
+    /// Returns true if an element may be attached to `parent` without exceeding the depth limit.
+    fn admit_depth(&self, parent: NodeId) -> bool {
+        let Some(limit) = self.limits.max_depth else {
+            return true;
+        };
+
+        let depth = {
+            let this = self.sink.0.borrow();
+            let parent = this.tree.get(parent).unwrap();
+            parent.ancestors().take(limit.max).count() + 1
+        };
+        if depth > limit.max {
+            self.exceed(LimitKind::Depth, limit);
+            return false;
+        }
+        true
+    }
+}
+
+/// Returns the longest prefix of `s` which is at most `len` bytes long.
+fn truncate(s: &str, mut len: usize) -> &str {
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    &s[..len]
+}
+
+impl TreeSink for LimitedTreeSink {
+    type Output = Html;
+    type Handle = NodeId;
+    type ElemName<'a> = Ref<'a, QualName>;
+
+    fn finish(self) -> Html {
+        self.sink.finish()
+    }
+
+    fn parse_error(&self, msg: Cow<'static, str>) {
+        #[cfg(feature = "errors")]
+        self.sink
+            .0
+            .borrow_mut()
+            .push_error(ParseError::new(msg, self.line.get()));
+        #[cfg(not(feature = "errors"))]
+        let _ = msg;
+    }
+
+    #[cfg(feature = "errors")]
+    fn set_current_line(&self, line_number: u64) {
+        self.line.set(line_number);
+    }
+
+    fn set_quirks_mode(&self, mode: QuirksMode) {
+        self.sink.set_quirks_mode(mode);
+    }
+
+    fn get_document(&self) -> Self::Handle {
+        self.sink.get_document()
+    }
+
+    fn same_node(&self, x: &Self::Handle, y: &Self::Handle) -> bool {
+        self.sink.same_node(x, y)
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a Self::Handle) -> Ref<'a, QualName> {
+        self.sink.elem_name(target)
+    }
+
+    fn create_element(
+        &self,
+        name: QualName,
+        attrs: Vec<Attribute>,
+        flags: ElementFlags,
+    ) -> Self::Handle {
+        let fragment = name.expanded() == expanded_name!(html "template");
+        // Once parsing stops, only the rest of the current token is parsed. The parser still needs
+        // a distinct node for each element, but not their attributes.
+        let attrs = if self.is_stopped() || !self.count_nodes(1 + usize::from(fragment)) {
+            Vec::new()
+        } else {
+            self.admit_attrs(0, attrs)
+        };
+        self.sink.create_element(name, attrs, flags)
+    }
+
+    fn create_comment(&self, text: StrTendril) -> Self::Handle {
+        let text = if self.is_stopped() || !self.count_nodes(1) {
+            StrTendril::new()
+        } else {
+            text
+        };
+        self.sink.create_comment(text)
+    }
+
+    fn append_doctype_to_document(
+        &self,
+        name: StrTendril,
+        public_id: StrTendril,
+        system_id: StrTendril,
+    ) {
+        if self.is_stopped() || !self.count_nodes(1) {
+            return;
+        }
+        self.sink
+            .append_doctype_to_document(name, public_id, system_id);
+    }
+
+    fn append(&self, parent: &Self::Handle, child: NodeOrText<Self::Handle>) {
+        let merges = {
+            let this = self.sink.0.borrow();
+            let parent = this.tree.get(*parent).unwrap();
+            parent.last_child().is_some_and(|n| n.value().is_text())
+        };
+        if let Some(child) = self.admit(*parent, merges, child) {
+            self.sink.append(parent, child);
+        }
+    }
+
+    fn append_before_sibling(&self, sibling: &Self::Handle, new_node: NodeOrText<Self::Handle>) {
+        if self.is_stopped() {
+            return;
+        }
+
+        let (parent, merges) = {
+            let this = self.sink.0.borrow();
+            let sibling = this.tree.get(*sibling).unwrap();
+            let merges = sibling.prev_sibling().is_some_and(|n| n.value().is_text());
+            (sibling.parent().map(|n| n.id()), merges)
+        };
+        let new_node = match parent {
+            Some(parent) => match self.admit(parent, merges, new_node) {
+                Some(new_node) => new_node,
+                None => return,
+            },
+            None => new_node,
+        };
+        self.sink.append_before_sibling(sibling, new_node);
+    }
+
+    fn remove_from_parent(&self, target: &Self::Handle) {
+        if !self.is_stopped() {
+            self.sink.remove_from_parent(target);
+        }
+    }
+
+    fn reparent_children(&self, node: &Self::Handle, new_parent: &Self::Handle) {
+        if !self.is_stopped() {
+            self.sink.reparent_children(node, new_parent);
+        }
+    }
+
+    fn add_attrs_if_missing(&self, target: &Self::Handle, attrs: Vec<Attribute>) {
+        if self.is_stopped() {
+            return;
+        }
+
+        let mut attrs = attrs;
+        if self.limits.max_attributes.is_some() || self.limits.max_attribute_length.is_some() {
+            let this = self.sink.0.borrow();
+            let element = this
+                .tree
+                .get(*target)
+                .unwrap()
+                .value()
+                .as_element()
+                .unwrap();
+            attrs.retain(|attr| element.attrs.iter().all(|(name, _)| *name != attr.name));
+            let existing = element.attrs.len();
+            drop(this);
+            attrs = self.admit_attrs(existing, attrs);
+        }
+        self.sink.add_attrs_if_missing(target, attrs);
+    }
+
+    fn get_template_contents(&self, target: &Self::Handle) -> Self::Handle {
+        self.sink.get_template_contents(target)
+    }
+
+    fn mark_script_already_started(&self, node: &Self::Handle) {
+        self.sink.mark_script_already_started(node);
+    }
+
+    fn create_pi(&self, target: StrTendril, data: StrTendril) -> Self::Handle {
+        let data = if self.is_stopped() || !self.count_nodes(1) {
+            StrTendril::new()
+        } else {
+            data
+        };
+        self.sink.create_pi(target, data)
+    }
+
+    fn append_based_on_parent_node(
+        &self,
+        element: &Self::Handle,
+        prev_element: &Self::Handle,
+        child: NodeOrText<Self::Handle>,
+    ) {
+        let has_parent = self
+            .sink
+            .0
+            .borrow()
+            .tree
+            .get(*element)
+            .unwrap()
+            .parent()
+            .is_some();
+
+        if has_parent {
+            self.append_before_sibling(element, child)
+        } else {
+            self.append(prev_element, child)
+        }
+    }
+}
@@ -14,19 +14,55 @@
 #[macro_use]
 extern crate html5ever;
 
+#[cfg(all(test, feature = "derive"))]
+extern crate self as scraper;
+
 pub use crate::element_ref::ElementRef;
 pub use crate::html::{Html, HtmlTreeSink};
 pub use crate::node::Node;
+pub use crate::node_mut::NodeMut;
 pub use crate::selector::Selector;
 
 pub use selectors::{Element, attr::CaseSensitivity};
 
 pub mod element_ref;
 pub mod error;
+#[cfg(feature = "forms")]
+pub mod forms;
 pub mod html;
+#[cfg(feature = "links")]
+pub mod links;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+#[cfg(feature = "microformats")]
+pub mod microformats;
 pub mod node;
+pub mod node_mut;
+#[cfg(feature = "rayon")]
+pub mod par;
+#[cfg(feature = "pretty")]
+pub mod pretty;
+#[cfg(feature = "readability")]
+pub mod readability;
+#[cfg(any(feature = "readability", feature = "sanitize"))]
+mod rebuild;
+#[cfg(feature = "sanitize")]
+pub mod sanitize;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod scrape;
 pub mod selectable;
 pub mod selector;
+#[cfg(feature = "spans")]
+pub mod span;
+#[cfg(any(feature = "links", feature = "sanitize"))]
+mod srcset;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "structured")]
+pub mod structured;
+#[cfg(feature = "table")]
+pub mod table;
 
 #[cfg(feature = "atomic")]
 pub(crate) mod tendril_util {
@@ -34,9 +70,20 @@ pub(crate) mod tendril_util {
     /// Atomic equivalent to the default `StrTendril` type.
     pub type StrTendril = tendril::Tendril<tendril::fmt::UTF8, tendril::Atomic>;
 
-    /// Convert a standard tendril into an atomic one.
-    pub fn make(s: tendril::StrTendril) -> StrTendril {
-        s.into_send().into()
+    /// The string type of node text and attribute values.
+    ///
+    /// With the `atomic` feature, this is a `String` rather than a [`StrTendril`]: tendrils
+    /// cannot be shared between threads, so nodes own plain strings to keep `Html` `Sync`.
+    pub type NodeStr = String;
+
+    /// Convert a standard tendril into a node string.
+    pub fn make(s: tendril::StrTendril) -> NodeStr {
+        String::from(&*s)
+    }
+
+    /// Append `s` to a node string.
+    pub fn push(dest: &mut NodeStr, s: &str) {
+        dest.push_str(s);
     }
 }
 
@@ -46,13 +93,24 @@ pub(crate) mod tendril_util {
     /// Primary string tendril type.
     pub type StrTendril = tendril::StrTendril;
 
+    /// The string type of node text and attribute values.
+    ///
+    /// With the `atomic` feature, this is a `String` rather than a [`StrTendril`]: tendrils
+    /// cannot be shared between threads, so nodes own plain strings to keep `Html` `Sync`.
+    pub type NodeStr = StrTendril;
+
     /// Return unaltered.
-    pub fn make(s: StrTendril) -> StrTendril {
+    pub fn make(s: StrTendril) -> NodeStr {
         s
     }
+
+    /// Append `s` to a node string.
+    pub fn push(dest: &mut NodeStr, s: &str) {
+        dest.push_slice(s);
+    }
 }
 
-pub use tendril_util::StrTendril;
+pub use tendril_util::{NodeStr, StrTendril};
 
 #[cfg(test)]
 mod test;
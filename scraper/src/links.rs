@@ -0,0 +1,379 @@
+//! Link extraction.
+//!
+//! Requires the `links` feature.
+//!
+//! Finds every URL a document refers to, such as the `href` of `<a>` and `<link>`, the `src` and
+//! `srcset` of images, `<script src>`, `<iframe src>` and `<form action>`, and resolves it against
+//! the document URL and `<base>` with the [`url`] crate.
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//! use scraper::links::{self, LinkKind};
+//! use url::Url;
+//!
+//! let html = Html::parse_document(
+//!     r#"<base href="/docs/">
+//!     <a href="intro.html" rel="next">Introduction</a>
+//!     <img src="logo.png" srcset="logo-2x.png 2x" alt="Logo">"#,
+//! );
+//! let url = Url::parse("https://example.com/").unwrap();
+//! let links = links::extract(&html, Some(&url));
+//!
+//! assert_eq!(links[0].kind, LinkKind::Anchor);
+//! assert_eq!(links[0].url.as_ref().unwrap().as_str(), "https://example.com/docs/intro.html");
+//! assert_eq!(links[0].rel, ["next"]);
+//! assert_eq!(links[0].text.as_deref(), Some("Introduction"));
+//!
+//! let images = links.iter().filter(|link| link.kind == LinkKind::Image);
+//! assert_eq!(images.count(), 2);
+//! ```
+
+use std::collections::HashSet;
+
+use ego_tree::iter::Edge;
+use url::Url;
+
+use crate::element_ref::is_template_contents;
+use crate::srcset::srcset_candidates;
+use crate::{ElementRef, Html};
+
+/// Options for link extraction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkExtractor {
+    /// The URL of the document, which relative URLs and `<base href>` are resolved against.
+    pub document_url: Option<Url>,
+
+    /// Normalize URLs by removing the fragment and an empty query, so that URLs of the same
+    /// resource compare equal.
+    pub normalize: bool,
+
+    /// Keep only the first link to each URL.
+    ///
+    /// Links whose URL could not be resolved are compared by their URL as written.
+    pub dedup: bool,
+}
+
+/// What a link refers to, by the element and attribute it comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    /// `<a href>`.
+    Anchor,
+
+    /// `<area href>`.
+    Area,
+
+    /// `<link href>`, such as a stylesheet, icon or alternate version.
+    Link,
+
+    /// An image: `<img src>`, `<img srcset>`, `<picture>` sources, `<input type="image" src>`
+    /// and `<video poster>`.
+    Image,
+
+    /// Audio or video: `<audio src>`, `<video src>`, `<source src>` and `<track src>`.
+    Media,
+
+    /// `<script src>`.
+    Script,
+
+    /// `<iframe src>` and `<frame src>`.
+    Frame,
+
+    /// `<embed src>` and `<object data>`.
+    Embed,
+
+    /// A form submission: `<form action>` and the `formaction` of buttons.
+    Form,
+
+    /// The source of a quotation or edit: the `cite` of `<blockquote>`, `<q>`, `<ins>` and
+    /// `<del>`.
+    Citation,
+}
+
+/// A reference from a document to a URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link<'a> {
+    /// The element the link comes from.
+    pub element: ElementRef<'a>,
+
+    /// The attribute the link comes from, such as `href` or `srcset`.
+    pub attribute: &'static str,
+
+    /// What the link refers to.
+    pub kind: LinkKind,
+
+    /// The URL as written, without surrounding whitespace.
+    pub raw: String,
+
+    /// The absolute URL, or `None` if the URL is relative and there is no absolute base to resolve
+    /// it against, or if it is invalid.
+    pub url: Option<Url>,
+
+    /// The `rel` tokens of `<a>`, `<area>`, `<link>` and `<form>`, lowercased.
+    pub rel: Vec<String>,
+
+    /// The text of an `<a>`, with whitespace collapsed, or the `alt` of an `<area>` or image.
+    pub text: Option<String>,
+}
+
+/// Returns the links of a document, resolved against `document_url` and `<base>`.
+///
+/// Equivalent to [`LinkExtractor::extract`] without normalization or deduplication.
+pub fn extract<'a>(html: &'a Html, document_url: Option<&Url>) -> Vec<Link<'a>> {
+    LinkExtractor {
+        document_url: document_url.cloned(),
+        ..Default::default()
+    }
+    .extract(html)
+}
+
+impl LinkExtractor {
+    /// Returns the links of a document in document order, with the candidates of a `srcset` in
+    /// the order they are listed.
+    ///
+    /// Elements inside `<template>` contents are skipped, as are empty `srcset`s and missing
+    /// attributes. An empty `href`, which refers to the document itself, is kept.
+    pub fn extract<'a>(&self, html: &'a Html) -> Vec<Link<'a>> {
+        let base = html.base_url(self.document_url.as_ref());
+        let mut links = Vec::new();
+        let mut seen = HashSet::new();
+        let mut skipped = None;
+
+        for edge in html.tree.root().traverse() {
+            match edge {
+                Edge::Open(node) if skipped.is_none() => {
+                    if is_template_contents(node) {
+                        skipped = Some(node.id());
+                        continue;
+                    }
+                    let Some(element) = ElementRef::wrap(node) else {
+                        continue;
+                    };
+                    for (attribute, kind, raw) in references(element) {
+                        let mut url = match &base {
+                            Some(base) => base.join(&raw).ok(),
+                            None => Url::parse(&raw).ok(),
+                        };
+                        if self.normalize
+                            && let Some(url) = &mut url
+                        {
+                            normalize(url);
+                        }
+                        if self.dedup {
+                            let key = url.as_ref().map_or(raw.as_str(), Url::as_str);
+                            if !seen.insert(key.to_owned()) {
+                                continue;
+                            }
+                        }
+
+                        links.push(Link {
+                            element,
+                            attribute,
+                            kind,
+                            raw,
+                            url,
+                            rel: rel(element),
+                            text: text(element),
+                        });
+                    }
+                }
+                Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+                _ => {}
+            }
+        }
+
+        links
+    }
+}
+
+/// Returns the attribute, kind and URL of each reference from an element.
+fn references(element: ElementRef<'_>) -> Vec<(&'static str, LinkKind, String)> {
+    let value = element.value();
+    let in_picture = || {
+        element
+            .parent()
+            .and_then(|parent| parent.value().as_element().map(|p| p.name() == "picture"))
+            .unwrap_or(false)
+    };
+
+    let attributes: &[(&'static str, LinkKind)] = match value.name() {
+        "a" => &[("href", LinkKind::Anchor)],
+        "area" => &[("href", LinkKind::Area)],
+        "link" => &[("href", LinkKind::Link)],
+        "img" => &[("src", LinkKind::Image), ("srcset", LinkKind::Image)],
+        "input"
+            if value
+                .attr("type")
+                .is_some_and(|ty| ty.eq_ignore_ascii_case("image")) =>
+        {
+            &[("src", LinkKind::Image), ("formaction", LinkKind::Form)]
+        }
+        "input" | "button" => &[("formaction", LinkKind::Form)],
+        "source" if in_picture() => &[("srcset", LinkKind::Image)],
+        "source" => &[("src", LinkKind::Media)],
+        "audio" | "track" => &[("src", LinkKind::Media)],
+        "video" => &[("src", LinkKind::Media), ("poster", LinkKind::Image)],
+        "script" => &[("src", LinkKind::Script)],
+        "iframe" | "frame" => &[("src", LinkKind::Frame)],
+        "embed" => &[("src", LinkKind::Embed)],
+        "object" => &[("data", LinkKind::Embed)],
+        "form" => &[("action", LinkKind::Form)],
+        "blockquote" | "q" | "ins" | "del" => &[("cite", LinkKind::Citation)],
+        _ => &[],
+    };
+
+    let mut references = Vec::new();
+    for &(attribute, kind) in attributes {
+        let Some(raw) = value.attr(attribute) else {
+            continue;
+        };
+        if attribute == "srcset" {
+            for (url, _) in srcset_candidates(raw) {
+                references.push((attribute, kind, url.to_owned()));
+            }
+        } else {
+            references.push((attribute, kind, raw.trim().to_owned()));
+        }
+    }
+    references
+}
+
+/// Removes the fragment and an empty query.
+fn normalize(url: &mut Url) {
+    url.set_fragment(None);
+    if url.query() == Some("") {
+        url.set_query(None);
+    }
+}
+
+fn rel(element: ElementRef<'_>) -> Vec<String> {
+    let value = element.value();
+    if !matches!(value.name(), "a" | "area" | "link" | "form") {
+        return Vec::new();
+    }
+    value
+        .attr("rel")
+        .unwrap_or_default()
+        .split_ascii_whitespace()
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+fn text(element: ElementRef<'_>) -> Option<String> {
+    let value = element.value();
+    match value.name() {
+        "a" => {
+            let text = element.text().collect::<String>();
+            Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+        "area" | "img" | "input" => value.attr("alt").map(|alt| alt.trim().to_owned()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{LinkExtractor, LinkKind, extract};
+    use crate::Html;
+
+    #[test]
+    fn kinds_and_resolution() {
+        let html = Html::parse_document(
+            r#"<head><base href="sub/"><link rel="Stylesheet" href="a.css">
+            <script src="//cdn.example/a.js"></script></head>
+            <body>
+            <a href=" ../up ">Up
+                one</a>
+            <a>No href</a>
+            <area href="map" alt="Map">
+            <picture><source srcset="a.webp"><img src="a.png" alt="A"></picture>
+            <video src="v.mp4" poster="v.jpg"><source src="v.webm"><track src="v.vtt"></video>
+            <iframe src="frame"></iframe><object data="obj"></object>
+            <form action="" rel="noopener"><button formaction="alt">Go</button></form>
+            <blockquote cite="https://quote.example/"></blockquote>
+            <a href="mailto:ann@example.com">Mail</a>
+            <template><a href="hidden">Hidden</a></template>
+            </body>"#,
+        );
+        let url = Url::parse("https://example.com/dir/page?q").unwrap();
+        let links = extract(&html, Some(&url));
+
+        let summary = links
+            .iter()
+            .map(|link| (link.kind, link.url.as_ref().unwrap().as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (LinkKind::Link, "https://example.com/dir/sub/a.css"),
+                (LinkKind::Script, "https://cdn.example/a.js"),
+                (LinkKind::Anchor, "https://example.com/dir/up"),
+                (LinkKind::Area, "https://example.com/dir/sub/map"),
+                (LinkKind::Image, "https://example.com/dir/sub/a.webp"),
+                (LinkKind::Image, "https://example.com/dir/sub/a.png"),
+                (LinkKind::Media, "https://example.com/dir/sub/v.mp4"),
+                (LinkKind::Image, "https://example.com/dir/sub/v.jpg"),
+                (LinkKind::Media, "https://example.com/dir/sub/v.webm"),
+                (LinkKind::Media, "https://example.com/dir/sub/v.vtt"),
+                (LinkKind::Frame, "https://example.com/dir/sub/frame"),
+                (LinkKind::Embed, "https://example.com/dir/sub/obj"),
+                (LinkKind::Form, "https://example.com/dir/sub/"),
+                (LinkKind::Form, "https://example.com/dir/sub/alt"),
+                (LinkKind::Citation, "https://quote.example/"),
+                (LinkKind::Anchor, "mailto:ann@example.com"),
+            ]
+        );
+
+        assert_eq!(links[0].rel, ["stylesheet"]);
+        assert_eq!(links[2].raw, "../up");
+        assert_eq!(links[2].text.as_deref(), Some("Up one"));
+        assert_eq!(links[3].text.as_deref(), Some("Map"));
+        assert_eq!(links[5].text.as_deref(), Some("A"));
+        assert_eq!(links[12].rel, ["noopener"]);
+        assert_eq!(links[13].attribute, "formaction");
+    }
+
+    #[test]
+    fn unresolved() {
+        let html = Html::parse_fragment(r#"<a href="/rel">R</a><a href="https://a.example">A</a>"#);
+        let links = extract(&html, None);
+        assert_eq!(links[0].url, None);
+        assert_eq!(links[0].raw, "/rel");
+        assert_eq!(
+            links[1].url.as_ref().map(Url::as_str),
+            Some("https://a.example/")
+        );
+    }
+
+    #[test]
+    fn normalize_and_dedup() {
+        let html = Html::parse_fragment(
+            r#"<a href="/a#top">1</a><a href="/a?">2</a><a href="/A">3</a>
+            <a href="HTTPS://EXAMPLE.COM:443/a">4</a><a href="rel">5</a><a href="rel">6</a>"#,
+        );
+        let extractor = LinkExtractor {
+            document_url: Some(Url::parse("https://example.com/").unwrap()),
+            normalize: true,
+            dedup: true,
+        };
+        let links = extractor.extract(&html);
+        let texts = links
+            .iter()
+            .map(|link| link.text.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["1", "3", "5"]);
+        assert_eq!(
+            links[0].url.as_ref().unwrap().as_str(),
+            "https://example.com/a"
+        );
+
+        let extractor = LinkExtractor {
+            dedup: true,
+            ..Default::default()
+        };
+        assert_eq!(extractor.extract(&html).len(), 5);
+    }
+}
@@ -0,0 +1,855 @@
+//! Conversion of HTML to Markdown.
+//!
+//! Requires the `markdown` feature.
+//!
+//! The output is CommonMark with the GitHub Flavored Markdown extensions for tables and
+//! strikethrough.
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//! use scraper::markdown::Converter;
+//!
+//! let fragment = Html::parse_fragment(
+//!     r#"<h2>Notes</h2><ul><li><a href="/one">One</a></li><li><em>Two</em></li></ul>"#,
+//! );
+//! let markdown = Converter::default().convert(&fragment);
+//! assert_eq!(markdown, "## Notes\n\n- [One](/one)\n- *Two*");
+//! ```
+
+use ego_tree::NodeRef;
+use url::Url;
+
+use crate::{ElementRef, Html, Node};
+
+/// How links and images refer to their URLs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkStyle {
+    /// `[text](url)`.
+    #[default]
+    Inline,
+
+    /// `[text][1]`, with the URLs listed as `[1]: url` at the end.
+    Reference,
+}
+
+/// What to do with elements that have no Markdown equivalent and are not simple containers such
+/// as `<div>` and `<span>`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnknownElements {
+    /// Convert the contents of the element.
+    #[default]
+    Convert,
+
+    /// Leave out the element and its contents.
+    Drop,
+
+    /// Keep the element as raw HTML, which Markdown allows.
+    Html,
+}
+
+/// Converts HTML to Markdown.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Converter {
+    /// How links and images refer to their URLs.
+    pub link_style: LinkStyle,
+
+    /// What to do with elements that have no Markdown equivalent.
+    pub unknown_elements: UnknownElements,
+
+    /// The URL that relative link and image URLs are resolved against, if any.
+    pub base_url: Option<Url>,
+}
+
+impl Converter {
+    /// Converts a document or fragment to Markdown.
+    ///
+    /// The `<head>` of a document, scripts, styles and form controls are left out.
+    pub fn convert(&self, html: &Html) -> String {
+        let mut writer = Writer::new(self);
+        let blocks = writer.blocks(*html.root_element());
+        writer.finish(blocks)
+    }
+
+    /// Converts an element, including the element itself, to Markdown.
+    pub fn convert_element(&self, element: ElementRef<'_>) -> String {
+        let mut writer = Writer::new(self);
+        let mut blocks = Vec::new();
+        writer.child(*element, &mut blocks, &mut String::new());
+        writer.finish(blocks)
+    }
+}
+
+/// Elements that are left out with their contents.
+const DROPPED: &[&str] = &[
+    "audio", "base", "button", "canvas", "embed", "head", "iframe", "input", "link", "math",
+    "meta", "noscript", "object", "script", "select", "style", "svg", "template", "textarea",
+    "title", "video",
+];
+
+/// Elements converted to their own Markdown blocks.
+const BLOCKS: &[&str] = &[
+    "blockquote",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "menu",
+    "ol",
+    "p",
+    "pre",
+    "table",
+    "ul",
+];
+
+/// Block elements whose contents are converted as they are.
+const BLOCK_CONTAINERS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "body",
+    "caption",
+    "center",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "hgroup",
+    "html",
+    "legend",
+    "main",
+    "nav",
+    "search",
+    "section",
+    "summary",
+];
+
+/// Elements converted to inline Markdown.
+const INLINES: &[&str] = &[
+    "a", "b", "br", "code", "del", "em", "i", "img", "kbd", "s", "samp", "strike", "strong", "tt",
+];
+
+/// Inline elements whose contents are converted as they are.
+const INLINE_CONTAINERS: &[&str] = &[
+    "abbr", "bdi", "bdo", "big", "cite", "data", "dfn", "font", "ins", "label", "mark", "output",
+    "q", "small", "span", "sub", "sup", "time", "u", "var", "wbr",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Dropped,
+    Block,
+    Inline,
+    Unknown,
+}
+
+fn kind(name: &str) -> Kind {
+    if DROPPED.contains(&name) {
+        Kind::Dropped
+    } else if BLOCKS.contains(&name) || BLOCK_CONTAINERS.contains(&name) {
+        Kind::Block
+    } else if INLINES.contains(&name) || INLINE_CONTAINERS.contains(&name) {
+        Kind::Inline
+    } else {
+        Kind::Unknown
+    }
+}
+
+/// Where inline content ends up, which decides how line breaks and pipes are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    Paragraph,
+    Heading,
+    TableCell,
+}
+
+/// A converted block, and whether it is a list.
+#[derive(Debug)]
+struct Block {
+    text: String,
+    list: bool,
+}
+
+impl Block {
+    fn new(text: String) -> Self {
+        Block { text, list: false }
+    }
+}
+
+struct Writer<'a> {
+    converter: &'a Converter,
+    /// The URLs and titles of reference links, numbered from 1.
+    references: Vec<(String, Option<String>)>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(converter: &'a Converter) -> Self {
+        Writer {
+            converter,
+            references: Vec::new(),
+        }
+    }
+
+    fn finish(self, blocks: Vec<Block>) -> String {
+        let mut output = join(&blocks, false);
+        if !self.references.is_empty() {
+            if !output.is_empty() {
+                output.push_str("\n\n");
+            }
+            let references = self
+                .references
+                .iter()
+                .enumerate()
+                .map(|(index, (url, title))| {
+                    format!(
+                        "[{}]: {}{}",
+                        index + 1,
+                        destination(url),
+                        title_suffix(title)
+                    )
+                })
+                .collect::<Vec<_>>();
+            output.push_str(&references.join("\n"));
+        }
+        output
+    }
+
+    /// Converts the children of `parent` to blocks, gathering inline content into paragraphs.
+    fn blocks(&mut self, parent: NodeRef<'_, Node>) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut inline = String::new();
+        for child in parent.children() {
+            self.child(child, &mut blocks, &mut inline);
+        }
+        flush(&mut inline, &mut blocks);
+        blocks
+    }
+
+    fn child(&mut self, node: NodeRef<'_, Node>, blocks: &mut Vec<Block>, inline: &mut String) {
+        match node.value() {
+            Node::Text(text) => escape(text, Context::Paragraph, inline),
+            Node::Element(_) => {
+                let element = ElementRef::wrap(node).unwrap();
+                match self.kind(element) {
+                    Kind::Block => {
+                        flush(inline, blocks);
+                        blocks.extend(self.block(element));
+                    }
+                    Kind::Inline => self.inline(element, Context::Paragraph, inline),
+                    Kind::Unknown => {
+                        if self.converter.unknown_elements == UnknownElements::Html {
+                            inline.push_str(&element.html());
+                        }
+                    }
+                    Kind::Dropped => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the kind of an element, with unknown elements that are converted treated as block
+    /// containers if they have block children, or as inline containers otherwise.
+    ///
+    /// Only the children are looked at, so that nested unknown elements are classified in linear
+    /// time. An unknown element whose blocks are further down is inline, and its blocks are
+    /// flattened.
+    fn kind(&self, element: ElementRef<'_>) -> Kind {
+        match kind(element.value().name()) {
+            Kind::Unknown if self.converter.unknown_elements == UnknownElements::Convert => {
+                let has_blocks = element.children().any(|node| {
+                    node.value()
+                        .as_element()
+                        .is_some_and(|element| kind(element.name()) == Kind::Block)
+                });
+                if has_blocks {
+                    Kind::Block
+                } else {
+                    Kind::Inline
+                }
+            }
+            kind => kind,
+        }
+    }
+
+    fn block(&mut self, element: ElementRef<'_>) -> Vec<Block> {
+        let name = element.value().name();
+        let text = match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                let text = self.inline_text(element, Context::Heading);
+                if text.is_empty() {
+                    return Vec::new();
+                }
+                format!("{} {}", "#".repeat(level), text)
+            }
+            "p" => self.inline_text(element, Context::Paragraph),
+            "hr" => "---".to_owned(),
+            "pre" => code_block(element),
+            "blockquote" => {
+                let text = join(&self.blocks(*element), false);
+                text.lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_owned()
+                        } else {
+                            format!("> {line}")
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            "ul" | "ol" | "menu" => {
+                return self.list(element).into_iter().collect();
+            }
+            "li" => self.list_item(element, "- "),
+            "table" => self.table(element),
+            _ => return self.blocks(*element),
+        };
+
+        if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![Block::new(text)]
+        }
+    }
+
+    fn list(&mut self, list: ElementRef<'_>) -> Option<Block> {
+        let ordered = list.value().name() == "ol";
+        let start = list
+            .attr("start")
+            .and_then(|start| start.trim().parse::<usize>().ok())
+            .unwrap_or(1);
+
+        let items = list
+            .child_elements()
+            .filter(|child| child.value().name() == "li")
+            .enumerate()
+            .map(|(index, item)| {
+                let marker = if ordered {
+                    format!("{}. ", start + index)
+                } else {
+                    "- ".to_owned()
+                };
+                self.list_item(item, &marker)
+            })
+            .collect::<Vec<_>>();
+
+        (!items.is_empty()).then(|| Block {
+            text: items.join("\n"),
+            list: true,
+        })
+    }
+
+    fn list_item(&mut self, item: ElementRef<'_>, marker: &str) -> String {
+        let content = join(&self.blocks(*item), true);
+        if content.is_empty() {
+            return marker.trim_end().to_owned();
+        }
+
+        let indent = " ".repeat(marker.len());
+        let mut text = String::new();
+        for (index, line) in content.lines().enumerate() {
+            if index == 0 {
+                text.push_str(marker);
+            } else {
+                text.push('\n');
+                if !line.is_empty() {
+                    text.push_str(&indent);
+                }
+            }
+            text.push_str(line);
+        }
+        text
+    }
+
+    fn table(&mut self, table: ElementRef<'_>) -> String {
+        let mut rows = Vec::new();
+        for child in table.child_elements() {
+            match child.value().name() {
+                "tr" => rows.push(child),
+                "thead" | "tbody" | "tfoot" => rows.extend(
+                    child
+                        .child_elements()
+                        .filter(|row| row.value().name() == "tr"),
+                ),
+                _ => {}
+            }
+        }
+
+        let mut alignments = Vec::new();
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let cells = row
+                    .child_elements()
+                    .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                    .collect::<Vec<_>>();
+                if alignments.is_empty() {
+                    alignments = cells.iter().map(|cell| cell.attr("align")).collect();
+                }
+                cells
+                    .into_iter()
+                    .map(|cell| self.inline_text(cell, Context::TableCell))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+
+        let line = |cells: &[String]| {
+            let mut line = String::from("|");
+            for column in 0..columns {
+                line.push(' ');
+                line.push_str(cells.get(column).map_or("", String::as_str));
+                line.push_str(" |");
+            }
+            line
+        };
+        let delimiter = (0..columns)
+            .map(|column| match alignments.get(column).copied().flatten() {
+                Some(align) if align.eq_ignore_ascii_case("left") => ":---".to_owned(),
+                Some(align) if align.eq_ignore_ascii_case("center") => ":---:".to_owned(),
+                Some(align) if align.eq_ignore_ascii_case("right") => "---:".to_owned(),
+                _ => "---".to_owned(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut lines = vec![line(&rows[0]), line(&delimiter)];
+        lines.extend(rows[1..].iter().map(|row| line(row)));
+        lines.join("\n")
+    }
+
+    /// Converts the contents of an element to inline Markdown.
+    fn inline_text(&mut self, element: ElementRef<'_>, context: Context) -> String {
+        let mut text = String::new();
+        self.inline_children(element, context, &mut text);
+        finish_inline(&text)
+    }
+
+    fn inline_children(&mut self, element: ElementRef<'_>, context: Context, out: &mut String) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => escape(text, context, out),
+                Node::Element(_) => {
+                    let child = ElementRef::wrap(child).unwrap();
+                    match self.kind(child) {
+                        Kind::Dropped => {}
+                        Kind::Unknown => {
+                            if self.converter.unknown_elements == UnknownElements::Html {
+                                out.push_str(&child.html());
+                            }
+                        }
+                        // Block elements inside inline content can only be flattened.
+                        Kind::Block => {
+                            out.push(' ');
+                            self.inline_children(child, context, out);
+                            out.push(' ');
+                        }
+                        Kind::Inline => self.inline(child, context, out),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn inline(&mut self, element: ElementRef<'_>, context: Context, out: &mut String) {
+        match element.value().name() {
+            "br" => out.push_str(match context {
+                Context::Paragraph => "\\\n",
+                Context::Heading => " ",
+                Context::TableCell => "<br>",
+            }),
+            "em" | "i" => self.delimited(element, "*", context, out),
+            "strong" | "b" => self.delimited(element, "**", context, out),
+            "del" | "s" | "strike" => self.delimited(element, "~~", context, out),
+            "code" | "kbd" | "samp" | "tt" => {
+                let text = element.text().collect::<String>();
+                let span = code_span(&text);
+                // Tables are split into cells before code spans are parsed, so pipes in code
+                // spans need escaping too, and the backslashes are removed with the split.
+                if context == Context::TableCell {
+                    out.push_str(&span.replace('|', "\\|"));
+                } else {
+                    out.push_str(&span);
+                }
+            }
+            "a" => self.link(element, context, out),
+            "img" => self.image(element, out),
+            _ => self.inline_children(element, context, out),
+        }
+    }
+
+    fn delimited(
+        &mut self,
+        element: ElementRef<'_>,
+        delimiter: &str,
+        context: Context,
+        out: &mut String,
+    ) {
+        let mut inner = String::new();
+        self.inline_children(element, context, &mut inner);
+
+        let trimmed = inner.trim();
+        if trimmed.is_empty() {
+            out.push_str(&inner);
+            return;
+        }
+        // Delimiters next to whitespace do not count as emphasis, so move it outside.
+        let leading = &inner[..inner.len() - inner.trim_start().len()];
+        let trailing = &inner[inner.trim_end().len()..];
+        out.push_str(leading);
+        out.push_str(delimiter);
+        out.push_str(trimmed);
+        out.push_str(delimiter);
+        out.push_str(trailing);
+    }
+
+    fn link(&mut self, element: ElementRef<'_>, context: Context, out: &mut String) {
+        let Some(href) = element.attr("href").filter(|href| !href.trim().is_empty()) else {
+            self.inline_children(element, context, out);
+            return;
+        };
+
+        let mut text = String::new();
+        self.inline_children(element, context, &mut text);
+        let text = text.trim();
+
+        let url = self.resolve(href);
+        let title = element.attr("title").map(str::to_owned);
+        out.push('[');
+        out.push_str(text);
+        out.push(']');
+        self.target(url, title, out);
+    }
+
+    fn image(&mut self, element: ElementRef<'_>, out: &mut String) {
+        let Some(src) = element.attr("src").filter(|src| !src.trim().is_empty()) else {
+            return;
+        };
+
+        let mut alt = String::new();
+        escape(
+            element.attr("alt").unwrap_or_default(),
+            Context::Paragraph,
+            &mut alt,
+        );
+
+        let url = self.resolve(src);
+        let title = element.attr("title").map(str::to_owned);
+        out.push_str("![");
+        out.push_str(alt.trim());
+        out.push(']');
+        self.target(url, title, out);
+    }
+
+    /// Writes the destination of a link or image.
+    fn target(&mut self, url: String, title: Option<String>, out: &mut String) {
+        match self.converter.link_style {
+            LinkStyle::Inline => {
+                out.push('(');
+                out.push_str(&destination(&url));
+                out.push_str(&title_suffix(&title));
+                out.push(')');
+            }
+            LinkStyle::Reference => {
+                let reference = (url, title);
+                let index = match self.references.iter().position(|r| *r == reference) {
+                    Some(index) => index,
+                    None => {
+                        self.references.push(reference);
+                        self.references.len() - 1
+                    }
+                };
+                out.push_str(&format!("[{}]", index + 1));
+            }
+        }
+    }
+
+    fn resolve(&self, url: &str) -> String {
+        let url = url.trim();
+        match &self.converter.base_url {
+            Some(base) => base.join(url).map_or_else(|_| url.to_owned(), String::from),
+            None => url.to_owned(),
+        }
+    }
+}
+
+/// Ends the paragraph of inline content gathered so far, if any.
+fn flush(inline: &mut String, blocks: &mut Vec<Block>) {
+    let text = finish_inline(inline);
+    if !text.is_empty() {
+        blocks.push(Block::new(text));
+    }
+    inline.clear();
+}
+
+/// Joins blocks with blank lines, except before lists that continue a list item's text when
+/// `in_item` is true.
+fn join(blocks: &[Block], in_item: bool) -> String {
+    let mut text = String::new();
+    for (index, block) in blocks.iter().enumerate() {
+        if index > 0 {
+            let tight = in_item && block.list && !blocks[index - 1].list;
+            text.push_str(if tight { "\n" } else { "\n\n" });
+        }
+        text.push_str(&block.text);
+    }
+    text
+}
+
+/// Appends text, escaping Markdown syntax and turning whitespace into spaces.
+fn escape(text: &str, context: Context, out: &mut String) {
+    for (idx, c) in text.char_indices() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '&' if is_reference(&text[idx + 1..]) => out.push_str("\\&"),
+            '|' if context == Context::TableCell => out.push_str("\\|"),
+            c if c.is_ascii_whitespace() => out.push(' '),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Returns true if the text following an `&` would be read as an entity or numeric character
+/// reference, such as `copy;`, `#169;` or `#xA9;`.
+fn is_reference(rest: &str) -> bool {
+    let (len, rest) = match rest.strip_prefix('#') {
+        Some(rest) => match rest.strip_prefix(['x', 'X']) {
+            Some(rest) => (rest.bytes().take_while(u8::is_ascii_hexdigit).count(), rest),
+            None => (rest.bytes().take_while(u8::is_ascii_digit).count(), rest),
+        },
+        None if rest.starts_with(|c: char| c.is_ascii_alphabetic()) => (
+            rest.bytes().take_while(u8::is_ascii_alphanumeric).count(),
+            rest,
+        ),
+        None => return false,
+    };
+    len > 0 && rest[len..].starts_with(';')
+}
+
+/// Collapses the whitespace of inline content and escapes what would start a block.
+fn finish_inline(text: &str) -> String {
+    let mut lines = Vec::new();
+    for line in text.split('\n') {
+        let mut collapsed = String::with_capacity(line.len());
+        for c in line.trim().chars() {
+            if c != ' ' || !collapsed.ends_with(' ') {
+                collapsed.push(c);
+            }
+        }
+        lines.push(escape_line_start(collapsed));
+    }
+
+    let mut text = lines.join("\n");
+    // A hard line break at the end of a paragraph would be a literal backslash.
+    while let Some(rest) = text.strip_suffix('\\') {
+        if rest.ends_with('\\') && !rest.ends_with("\\\\\\") {
+            break;
+        }
+        text = rest.trim_end().to_owned();
+    }
+    text
+}
+
+/// Escapes the start of a line that would otherwise be read as a heading, quote, list item or
+/// thematic break.
+fn escape_line_start(line: String) -> String {
+    if line.starts_with(['#', '>', '+', '-', '=', '~']) {
+        return format!("\\{line}");
+    }
+
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 && matches!(line.as_bytes().get(digits), Some(b'.' | b')')) {
+        return format!("{}\\{}", &line[..digits], &line[digits..]);
+    }
+    line
+}
+
+fn code_span(text: &str) -> String {
+    let text = text.split_ascii_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return String::new();
+    }
+
+    let fence = "`".repeat(longest_run(&text, '`') + 1);
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{padding}{text}{padding}{fence}")
+}
+
+fn code_block(pre: ElementRef<'_>) -> String {
+    let language = std::iter::once(pre)
+        .chain(
+            pre.child_elements()
+                .filter(|child| child.value().name() == "code"),
+        )
+        .flat_map(|element| element.value().classes())
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default();
+
+    let text = pre.text().collect::<String>();
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+    let fence = "`".repeat((longest_run(text, '`') + 1).max(3));
+    format!("{fence}{language}\n{text}\n{fence}")
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+fn destination(url: &str) -> String {
+    if url.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_owned()
+    }
+}
+
+fn title_suffix(title: &Option<String>) -> String {
+    match title {
+        Some(title) => format!(" \"{}\"", title.replace('\\', "\\\\").replace('"', "\\\"")),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{Converter, LinkStyle, UnknownElements};
+    use crate::{Html, Selector};
+
+    fn convert(html: &str) -> String {
+        Converter::default().convert(&Html::parse_fragment(html))
+    }
+
+    #[test]
+    fn inline_formatting_and_links() {
+        let converter = Converter {
+            base_url: Some(Url::parse("https://example.com/a/").unwrap()),
+            ..Default::default()
+        };
+        let html = Html::parse_document(
+            r#"<title>ignored</title><h1>The <i>Title</i></h1>
+            <p>Some <em>emphasis</em>, <strong> strong </strong> and
+            <a href="/x" title="T">a link</a>.<br>Next <img src="i.png" alt="pic"> <code>a`b</code>
+            <del>old</del></p>"#,
+        );
+        assert_eq!(
+            converter.convert(&html),
+            "# The *Title*\n\n\
+             Some *emphasis*, **strong** and [a link](https://example.com/x \"T\").\\\n\
+             Next ![pic](https://example.com/a/i.png) ``a`b`` ~~old~~"
+        );
+    }
+
+    #[test]
+    fn nested_lists() {
+        assert_eq!(
+            convert(
+                "<ul><li>one<ul><li>nested</li></ul></li><li>two</li></ul>\
+                 <ol start=\"3\"><li><p>a</p><p>b</p></li><li></li></ol>"
+            ),
+            "- one\n  - nested\n- two\n\n3. a\n\n   b\n4."
+        );
+    }
+
+    #[test]
+    fn quotes_and_code_blocks() {
+        assert_eq!(
+            convert(
+                "<blockquote><p>q1</p><p>q2</p></blockquote>\
+                 <pre><code class=\"language-rust\">fn main() {\n    ```\n}\n</code></pre>"
+            ),
+            "> q1\n>\n> q2\n\n````rust\nfn main() {\n    ```\n}\n````"
+        );
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            convert(
+                "<table><thead><tr><th>a</th><th align=\"right\">b|c</th></tr></thead>\
+                 <tbody><tr><td>1</td><td>2<br>3</td></tr>\
+                 <tr><td><code>x|y</code></td></tr></tbody></table>"
+            ),
+            "| a | b\\|c |\n| --- | ---: |\n| 1 | 2<br>3 |\n| `x\\|y` |  |"
+        );
+    }
+
+    #[test]
+    fn reference_links_and_unknown_elements() {
+        let html = Html::parse_fragment(
+            r#"<p><a href="https://a.com">A</a> and <a href="https://a.com">again</a>
+            <x-foo>custom *stars*</x-foo></p><script>x()</script>"#,
+        );
+        let mut converter = Converter {
+            link_style: LinkStyle::Reference,
+            ..Default::default()
+        };
+        assert_eq!(
+            converter.convert(&html),
+            "[A][1] and [again][1] custom \\*stars\\*\n\n[1]: https://a.com"
+        );
+
+        converter.unknown_elements = UnknownElements::Drop;
+        assert_eq!(
+            converter.convert(&html),
+            "[A][1] and [again][1]\n\n[1]: https://a.com"
+        );
+
+        converter.unknown_elements = UnknownElements::Html;
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+        assert_eq!(
+            converter.convert_element(p),
+            "[A][1] and [again][1] <x-foo>custom *stars*</x-foo>\n\n[1]: https://a.com"
+        );
+    }
+
+    #[test]
+    fn block_starts_are_escaped() {
+        assert_eq!(
+            convert("<p>1. not a list</p><p># not a heading</p><div>- nor <b>this</b></div>"),
+            "1\\. not a list\n\n\\# not a heading\n\n\\- nor **this**"
+        );
+    }
+
+    #[test]
+    fn references_are_escaped() {
+        assert_eq!(
+            convert("<p>&amp;copy; &amp;#169; &amp;#xA9; AT&amp;T &amp;amp &amp;#; &amp; x</p>"),
+            "\\&copy; \\&#169; \\&#xA9; AT&T &amp &#; & x"
+        );
+    }
+}
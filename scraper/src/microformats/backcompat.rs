@@ -0,0 +1,200 @@
+//! Classic microformats, parsed as their microformats2 equivalents.
+
+use super::parse::Prefix;
+use crate::node::Element;
+
+/// Classic root class names and the microformats2 root class names they map to.
+const ROOTS: &[(&str, &str)] = &[
+    ("adr", "h-adr"),
+    ("geo", "h-geo"),
+    ("hentry", "h-entry"),
+    ("hfeed", "h-feed"),
+    ("hproduct", "h-product"),
+    ("hreview", "h-review"),
+    ("vcard", "h-card"),
+    ("vevent", "h-event"),
+];
+
+/// Classic property class names and the microformats2 properties they map to.
+type Mapping = &'static [(&'static str, Prefix, &'static str)];
+
+/// The property mapping of each classic root.
+const PROPERTIES: &[(&str, Mapping)] = &[
+    (
+        "adr",
+        &[
+            ("post-office-box", Prefix::P, "post-office-box"),
+            ("extended-address", Prefix::P, "extended-address"),
+            ("street-address", Prefix::P, "street-address"),
+            ("locality", Prefix::P, "locality"),
+            ("region", Prefix::P, "region"),
+            ("postal-code", Prefix::P, "postal-code"),
+            ("country-name", Prefix::P, "country-name"),
+        ],
+    ),
+    (
+        "geo",
+        &[
+            ("latitude", Prefix::P, "latitude"),
+            ("longitude", Prefix::P, "longitude"),
+        ],
+    ),
+    (
+        "hentry",
+        &[
+            ("entry-title", Prefix::P, "name"),
+            ("entry-summary", Prefix::P, "summary"),
+            ("entry-content", Prefix::E, "content"),
+            ("published", Prefix::Dt, "published"),
+            ("updated", Prefix::Dt, "updated"),
+            ("author", Prefix::P, "author"),
+            ("category", Prefix::P, "category"),
+            ("geo", Prefix::P, "geo"),
+        ],
+    ),
+    (
+        "hfeed",
+        &[
+            ("author", Prefix::P, "author"),
+            ("photo", Prefix::U, "photo"),
+            ("url", Prefix::U, "url"),
+            ("category", Prefix::P, "category"),
+        ],
+    ),
+    (
+        "hproduct",
+        &[
+            ("fn", Prefix::P, "name"),
+            ("photo", Prefix::U, "photo"),
+            ("brand", Prefix::P, "brand"),
+            ("category", Prefix::P, "category"),
+            ("description", Prefix::P, "description"),
+            ("identifier", Prefix::U, "identifier"),
+            ("url", Prefix::U, "url"),
+            ("review", Prefix::P, "review"),
+            ("price", Prefix::P, "price"),
+        ],
+    ),
+    (
+        "hreview",
+        &[
+            ("summary", Prefix::P, "name"),
+            ("description", Prefix::E, "content"),
+            ("item", Prefix::P, "item"),
+            ("reviewer", Prefix::P, "author"),
+            ("dtreviewed", Prefix::Dt, "published"),
+            ("rating", Prefix::P, "rating"),
+            ("best", Prefix::P, "best"),
+            ("worst", Prefix::P, "worst"),
+        ],
+    ),
+    (
+        "vcard",
+        &[
+            ("fn", Prefix::P, "name"),
+            ("honorific-prefix", Prefix::P, "honorific-prefix"),
+            ("given-name", Prefix::P, "given-name"),
+            ("additional-name", Prefix::P, "additional-name"),
+            ("family-name", Prefix::P, "family-name"),
+            ("honorific-suffix", Prefix::P, "honorific-suffix"),
+            ("nickname", Prefix::P, "nickname"),
+            ("email", Prefix::U, "email"),
+            ("logo", Prefix::U, "logo"),
+            ("photo", Prefix::U, "photo"),
+            ("url", Prefix::U, "url"),
+            ("uid", Prefix::U, "uid"),
+            ("category", Prefix::P, "category"),
+            ("adr", Prefix::P, "adr"),
+            ("extended-address", Prefix::P, "extended-address"),
+            ("street-address", Prefix::P, "street-address"),
+            ("locality", Prefix::P, "locality"),
+            ("region", Prefix::P, "region"),
+            ("postal-code", Prefix::P, "postal-code"),
+            ("country-name", Prefix::P, "country-name"),
+            ("label", Prefix::P, "label"),
+            ("geo", Prefix::P, "geo"),
+            ("latitude", Prefix::P, "latitude"),
+            ("longitude", Prefix::P, "longitude"),
+            ("tel", Prefix::P, "tel"),
+            ("note", Prefix::P, "note"),
+            ("bday", Prefix::Dt, "bday"),
+            ("key", Prefix::U, "key"),
+            ("org", Prefix::P, "org"),
+            ("organization-name", Prefix::P, "organization-name"),
+            ("organization-unit", Prefix::P, "organization-unit"),
+            ("title", Prefix::P, "job-title"),
+            ("role", Prefix::P, "role"),
+        ],
+    ),
+    (
+        "vevent",
+        &[
+            ("summary", Prefix::P, "name"),
+            ("dtstart", Prefix::Dt, "start"),
+            ("dtend", Prefix::Dt, "end"),
+            ("duration", Prefix::Dt, "duration"),
+            ("description", Prefix::P, "description"),
+            ("url", Prefix::U, "url"),
+            ("category", Prefix::P, "category"),
+            ("location", Prefix::P, "location"),
+            ("geo", Prefix::P, "geo"),
+        ],
+    ),
+];
+
+/// Classic roots whose `rel=bookmark` and `rel=tag` links are their `url` and `category`.
+const REL_ROOTS: &[&str] = &["hentry", "hfeed", "hreview"];
+
+/// Returns the classic root class names of an element.
+pub(super) fn roots(element: &Element) -> Vec<&'static str> {
+    ROOTS
+        .iter()
+        .filter(|(class, _)| element.classes().any(|c| c == *class))
+        .map(|(class, _)| *class)
+        .collect()
+}
+
+/// Returns the microformats2 root class names of classic roots, sorted.
+pub(super) fn types(roots: &[&str]) -> Vec<String> {
+    let mut types = ROOTS
+        .iter()
+        .filter(|(class, _)| roots.contains(class))
+        .map(|(_, ty)| (*ty).to_owned())
+        .collect::<Vec<_>>();
+    types.sort();
+    types
+}
+
+/// Returns the microformats2 properties of an element inside an item with classic roots.
+pub(super) fn properties(roots: &[&str], element: &Element) -> Vec<(Prefix, String)> {
+    let mut properties = Vec::new();
+    for (root, mapping) in PROPERTIES {
+        if !roots.contains(root) {
+            continue;
+        }
+        for (class, prefix, name) in *mapping {
+            if element.classes().any(|c| c == *class) {
+                push(&mut properties, *prefix, name);
+            }
+        }
+    }
+
+    if roots.iter().any(|root| REL_ROOTS.contains(root)) {
+        let rels = element.attr("rel").unwrap_or_default();
+        for rel in rels.split_ascii_whitespace() {
+            if rel.eq_ignore_ascii_case("bookmark") {
+                push(&mut properties, Prefix::U, "url");
+            } else if rel.eq_ignore_ascii_case("tag") {
+                push(&mut properties, Prefix::P, "category");
+            }
+        }
+    }
+
+    properties
+}
+
+fn push(properties: &mut Vec<(Prefix, String)>, prefix: Prefix, name: &str) {
+    if !properties.iter().any(|(p, n)| *p == prefix && n == name) {
+        properties.push((prefix, name.to_owned()));
+    }
+}
@@ -0,0 +1,380 @@
+//! Microformats2 parsing.
+//!
+//! Requires the `microformats` feature.
+//!
+//! Implements the [microformats2 parsing specification][spec] on top of [`Html`]: root class
+//! detection, `p-`, `u-`, `dt-` and `e-` properties with the value class pattern, implied `name`,
+//! `photo` and `url` properties, the classic microformats such as `hentry` and `vcard`, and
+//! `rel` links. With the `serde` feature, the result serializes to the canonical JSON.
+//!
+//! [spec]: https://microformats.org/wiki/microformats2-parsing
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//! use scraper::microformats::{self, PropertyValue};
+//!
+//! let html = Html::parse_document(
+//!     r#"<a class="h-card" href="https://alice.example/"><img src="me.png" alt="">Alice</a>"#,
+//! );
+//! let document = microformats::parse(&html, None);
+//!
+//! let card = &document.items[0];
+//! assert_eq!(card.types, ["h-card"]);
+//! assert_eq!(card.properties["name"], [PropertyValue::Text("Alice".to_owned())]);
+//! assert_eq!(
+//!     card.properties["url"],
+//!     [PropertyValue::Text("https://alice.example/".to_owned())]
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+
+use ego_tree::iter::Edge;
+use url::Url;
+
+use crate::element_ref::is_template_contents;
+use crate::{ElementRef, Html};
+
+mod backcompat;
+mod parse;
+mod value_class;
+
+/// The microformats of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Document {
+    /// The top-level items.
+    pub items: Vec<Item>,
+
+    /// The URLs of the links with each `rel` value, in document order.
+    pub rels: BTreeMap<String, Vec<String>>,
+
+    /// The details of each URL linked to with a `rel`.
+    pub rel_urls: BTreeMap<String, RelUrl>,
+}
+
+/// A microformat, such as an `h-card`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Item {
+    /// The root class names, such as `h-card`, sorted and without duplicates.
+    pub types: Vec<String>,
+
+    /// The `id` of the root element.
+    pub id: Option<String>,
+
+    /// The values of each property, in document order.
+    pub properties: BTreeMap<String, Vec<PropertyValue>>,
+
+    /// Nested items that are not property values.
+    pub children: Vec<Item>,
+
+    /// The plain value of an item that is a property value, such as its name for a `p-`
+    /// property.
+    pub value: Option<String>,
+
+    /// The HTML of an item that is the value of an `e-` property.
+    pub html: Option<String>,
+}
+
+/// The value of a property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyValue {
+    /// The value of a `p-`, `u-` or `dt-` property.
+    Text(String),
+
+    /// The value of a `u-` property of an image with alternative text.
+    Image {
+        /// The URL of the image.
+        value: String,
+
+        /// The alternative text.
+        alt: String,
+    },
+
+    /// The value of an `e-` property.
+    Html {
+        /// The inner HTML of the element.
+        html: String,
+
+        /// The text of the element.
+        value: String,
+    },
+
+    /// A nested item.
+    Item(Item),
+}
+
+impl PropertyValue {
+    /// Returns the plain value of a property: the text, the URL of an image, or the value of a
+    /// nested item.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::Text(value)
+            | PropertyValue::Image { value, .. }
+            | PropertyValue::Html { value, .. } => Some(value),
+            PropertyValue::Item(item) => item.value.as_deref(),
+        }
+    }
+}
+
+/// The details of a URL linked to with a `rel`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelUrl {
+    /// The `rel` values of the links to the URL.
+    pub rels: Vec<String>,
+
+    /// The text of the first link to the URL with text.
+    pub text: Option<String>,
+
+    /// The first `hreflang` of the links to the URL.
+    pub hreflang: Option<String>,
+
+    /// The first `media` of the links to the URL.
+    pub media: Option<String>,
+
+    /// The first `title` of the links to the URL.
+    pub title: Option<String>,
+
+    /// The first `type` of the links to the URL.
+    pub media_type: Option<String>,
+}
+
+/// Parses the microformats of a document.
+///
+/// Relative URLs are resolved against the document's `<base>` and `document_url`.
+pub fn parse(html: &Html, document_url: Option<&Url>) -> Document {
+    let parser = parse::Parser::new(html.base_url(document_url));
+    let mut document = Document {
+        items: parser.items(html.root_element()),
+        ..Default::default()
+    };
+
+    for link in elements(html.root_element()) {
+        let element = link.value();
+        let (Some(rel), Some(href)) = (element.attr("rel"), element.attr("href")) else {
+            continue;
+        };
+        if !matches!(element.name(), "a" | "area" | "link") {
+            continue;
+        }
+
+        let url = parser.resolve(href);
+        let rel_url = document.rel_urls.entry(url.clone()).or_default();
+        for rel in rel.split_ascii_whitespace() {
+            let rel = rel.to_ascii_lowercase();
+            let urls = document.rels.entry(rel.clone()).or_default();
+            if !urls.contains(&url) {
+                urls.push(url.clone());
+            }
+            if !rel_url.rels.contains(&rel) {
+                rel_url.rels.push(rel);
+            }
+        }
+
+        let attr = |name: &str| element.attr(name).map(str::to_owned);
+        if rel_url.text.is_none() {
+            rel_url.text = Some(parser.text(link)).filter(|text| !text.is_empty());
+        }
+        rel_url.hreflang = rel_url.hreflang.take().or_else(|| attr("hreflang"));
+        rel_url.media = rel_url.media.take().or_else(|| attr("media"));
+        rel_url.title = rel_url.title.take().or_else(|| attr("title"));
+        rel_url.media_type = rel_url.media_type.take().or_else(|| attr("type"));
+    }
+
+    document
+}
+
+/// Returns the descendant elements of `root`, including `root`, in document order, skipping
+/// template contents.
+fn elements(root: ElementRef<'_>) -> Vec<ElementRef<'_>> {
+    let mut elements = Vec::new();
+    let mut skipped = None;
+    for edge in root.traverse() {
+        match edge {
+            Edge::Open(node) if skipped.is_none() => {
+                if is_template_contents(node) {
+                    skipped = Some(node.id());
+                } else if let Some(element) = ElementRef::wrap(node) {
+                    elements.push(element);
+                }
+            }
+            Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+            _ => {}
+        }
+    }
+    elements
+}
+
+/// Serializes the document as the canonical microformats2 JSON, with `items`, `rels` and
+/// `rel-urls`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Document {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("items", &self.items)?;
+        map.serialize_entry("rels", &self.rels)?;
+        map.serialize_entry("rel-urls", &self.rel_urls)?;
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Item {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", &self.types)?;
+        if let Some(id) = &self.id {
+            map.serialize_entry("id", id)?;
+        }
+        map.serialize_entry("properties", &self.properties)?;
+        if !self.children.is_empty() {
+            map.serialize_entry("children", &self.children)?;
+        }
+        if let Some(value) = &self.value {
+            map.serialize_entry("value", value)?;
+        }
+        if let Some(html) = &self.html {
+            map.serialize_entry("html", html)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PropertyValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        match self {
+            PropertyValue::Text(text) => serializer.serialize_str(text),
+            PropertyValue::Image { value, alt } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("value", value)?;
+                map.serialize_entry("alt", alt)?;
+                map.end()
+            }
+            PropertyValue::Html { html, value } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("html", html)?;
+                map.serialize_entry("value", value)?;
+                map.end()
+            }
+            PropertyValue::Item(item) => item.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RelUrl {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("rels", &self.rels)?;
+        for (key, value) in [
+            ("text", &self.text),
+            ("hreflang", &self.hreflang),
+            ("media", &self.media),
+            ("title", &self.title),
+            ("type", &self.media_type),
+        ] {
+            if let Some(value) = value {
+                map.serialize_entry(key, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{PropertyValue, RelUrl, parse};
+    use crate::Html;
+
+    #[test]
+    fn rels() {
+        let html = Html::parse_document(
+            r#"<link rel="Me authorization_endpoint" href="https://auth.example/">
+            <a rel="me" href="/about" hreflang="en">About me</a>
+            <a rel="me nofollow" href="/about" title="Again">Again</a>
+            <a rel="tag">No href</a>"#,
+        );
+        let url = Url::parse("https://site.example/").unwrap();
+        let document = parse(&html, Some(&url));
+
+        assert_eq!(
+            document.rels["me"],
+            ["https://auth.example/", "https://site.example/about"]
+        );
+        assert_eq!(document.rels["nofollow"], ["https://site.example/about"]);
+        assert!(!document.rels.contains_key("tag"));
+        assert_eq!(
+            document.rel_urls["https://site.example/about"],
+            RelUrl {
+                rels: vec!["me".to_owned(), "nofollow".to_owned()],
+                text: Some("About me".to_owned()),
+                hreflang: Some("en".to_owned()),
+                title: Some("Again".to_owned()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn as_str() {
+        let html = Html::parse_fragment(
+            r#"<div class="h-entry"><div class="p-author h-card">Ann</div>
+            <img class="u-photo" src="/a.png" alt="A"></div>"#,
+        );
+        let entry = &parse(&html, None).items[0];
+        assert_eq!(entry.properties["author"][0].as_str(), Some("Ann"));
+        assert_eq!(
+            entry.properties["photo"][0],
+            PropertyValue::Image {
+                value: "/a.png".to_owned(),
+                alt: "A".to_owned()
+            }
+        );
+        assert_eq!(entry.properties["photo"][0].as_str(), Some("/a.png"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let html = Html::parse_fragment(
+            r#"<article class="h-entry" id="post"><h1 class="p-name">Hi</h1>
+            <div class="e-content"><b>Bold</b> text</div>
+            <span class="p-author h-card">Ann</span>
+            <div class="h-cite">Quoted</div></article>
+            <a rel="me" href="https://ann.example/">Ann</a>"#,
+        );
+        assert_eq!(
+            serde_json::to_value(parse(&html, None)).unwrap(),
+            serde_json::json!({
+                "items": [{
+                    "type": ["h-entry"],
+                    "id": "post",
+                    "properties": {
+                        "name": ["Hi"],
+                        "content": [{"html": "<b>Bold</b> text", "value": "Bold text"}],
+                        "author": [{
+                            "type": ["h-card"],
+                            "properties": {"name": ["Ann"]},
+                            "value": "Ann",
+                        }],
+                    },
+                    "children": [{"type": ["h-cite"], "properties": {"name": ["Quoted"]}}],
+                }],
+                "rels": {"me": ["https://ann.example/"]},
+                "rel-urls": {"https://ann.example/": {"rels": ["me"], "text": "Ann"}},
+            })
+        );
+    }
+}
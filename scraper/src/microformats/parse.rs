@@ -0,0 +1,662 @@
+//! Parsing of items and their properties.
+
+use ego_tree::iter::Edge;
+use url::Url;
+
+use super::{Item, PropertyValue, backcompat, value_class};
+use crate::ElementRef;
+use crate::element_ref::is_template_contents;
+use crate::node::Element;
+
+/// The prefix of a property class name, which decides how its value is parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Prefix {
+    /// Plain text.
+    P,
+    /// A URL.
+    U,
+    /// A date and time.
+    Dt,
+    /// Embedded HTML.
+    E,
+}
+
+/// The root class names of an item.
+#[derive(Debug, Clone)]
+enum Roots {
+    /// Microformats2 root class names, such as `h-card`.
+    Mf2(Vec<String>),
+    /// Classic root class names, such as `vcard`.
+    Classic(Vec<&'static str>),
+}
+
+impl Roots {
+    /// Returns the roots of an element, preferring microformats2 root class names to classic
+    /// ones.
+    fn of(element: &Element) -> Option<Roots> {
+        let mut types = element
+            .classes()
+            .filter_map(|class| name(class, "h-"))
+            .map(|name| format!("h-{name}"))
+            .collect::<Vec<_>>();
+        if !types.is_empty() {
+            types.sort();
+            types.dedup();
+            return Some(Roots::Mf2(types));
+        }
+
+        let classic = backcompat::roots(element);
+        (!classic.is_empty()).then_some(Roots::Classic(classic))
+    }
+
+    fn types(&self) -> Vec<String> {
+        match self {
+            Roots::Mf2(types) => types.clone(),
+            Roots::Classic(roots) => backcompat::types(roots),
+        }
+    }
+
+    /// Returns the properties of an element inside an item with these roots.
+    fn properties(&self, element: &Element) -> Vec<(Prefix, String)> {
+        match self {
+            Roots::Mf2(_) => {
+                let mut properties = Vec::new();
+                for class in element.classes() {
+                    let property = [
+                        ("p-", Prefix::P),
+                        ("u-", Prefix::U),
+                        ("dt-", Prefix::Dt),
+                        ("e-", Prefix::E),
+                    ]
+                    .into_iter()
+                    .find_map(|(prefix, kind)| name(class, prefix).map(|name| (kind, name)));
+                    if let Some((prefix, name)) = property
+                        && !properties.iter().any(|(p, n)| *p == prefix && n == name)
+                    {
+                        properties.push((prefix, name.to_owned()));
+                    }
+                }
+                properties
+            }
+            Roots::Classic(roots) => backcompat::properties(roots, element),
+        }
+    }
+}
+
+/// Returns the name of a microformats2 class name with the given prefix, such as `card` for
+/// `h-card`.
+///
+/// Names are lowercase letters and hyphens, optionally after a vendor prefix of letters and
+/// digits, such as `x-` in `h-x-card`.
+fn name<'a>(class: &'a str, prefix: &str) -> Option<&'a str> {
+    let name = class.strip_prefix(prefix)?;
+    let mut parts = name.split('-');
+    let first = parts.next()?;
+    let letters = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase());
+    let rest = parts.collect::<Vec<_>>();
+    let valid = if rest.is_empty() {
+        letters(first)
+    } else {
+        !first.is_empty()
+            && first
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+            && rest.iter().all(|part| letters(part))
+    };
+    valid.then_some(name)
+}
+
+/// Returns true if an element has a microformats2 root or property class name.
+pub(super) fn has_mf2_class(element: &Element) -> bool {
+    element.classes().any(|class| {
+        ["h-", "p-", "u-", "dt-", "e-"]
+            .iter()
+            .any(|prefix| name(class, prefix).is_some())
+    })
+}
+
+/// What an item's properties include, which decides whether properties are implied.
+#[derive(Debug, Default)]
+struct Found {
+    p_or_e: bool,
+    u: bool,
+    nested: bool,
+    /// The date of the last `dt-` property, for `dt-` properties with only a time.
+    date: Option<String>,
+}
+
+pub(super) struct Parser {
+    base: Option<Url>,
+}
+
+impl Parser {
+    pub(super) fn new(base: Option<Url>) -> Self {
+        Parser { base }
+    }
+
+    pub(super) fn resolve(&self, url: &str) -> String {
+        let url = url.trim();
+        self.base
+            .as_ref()
+            .and_then(|base| base.join(url).ok())
+            .map_or_else(|| url.to_owned(), String::from)
+    }
+
+    /// Returns the items rooted at or below an element, without those nested in other items.
+    pub(super) fn items(&self, root: ElementRef<'_>) -> Vec<Item> {
+        let mut items = Vec::new();
+        self.walk(root, true, |element, skip| {
+            if let Some(roots) = Roots::of(element.value()) {
+                items.push(self.item(element, roots));
+                *skip = true;
+            }
+        });
+        items
+    }
+
+    /// Calls `visit` for `root`, if `include_root` is true, and its descendant elements, skipping
+    /// template contents and the descendants of elements for which `visit` sets its flag.
+    fn walk<'a>(
+        &self,
+        root: ElementRef<'a>,
+        include_root: bool,
+        mut visit: impl FnMut(ElementRef<'a>, &mut bool),
+    ) {
+        let mut skipped = None;
+        for edge in root.traverse() {
+            match edge {
+                Edge::Open(node) if skipped.is_none() => {
+                    if node == *root && !include_root {
+                        continue;
+                    }
+                    if is_template_contents(node) {
+                        skipped = Some(node.id());
+                        continue;
+                    }
+                    if let Some(element) = ElementRef::wrap(node) {
+                        let mut skip = false;
+                        visit(element, &mut skip);
+                        if skip {
+                            skipped = Some(node.id());
+                        }
+                    }
+                }
+                Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+                _ => {}
+            }
+        }
+    }
+
+    fn item(&self, root: ElementRef<'_>, roots: Roots) -> Item {
+        let mut item = Item {
+            types: roots.types(),
+            id: root.value().id().map(str::to_owned),
+            ..Default::default()
+        };
+        let mut found = Found::default();
+
+        self.walk(root, false, |element, skip| {
+            let properties = roots.properties(element.value());
+            for (prefix, _) in &properties {
+                match prefix {
+                    Prefix::P | Prefix::E => found.p_or_e = true,
+                    Prefix::U => found.u = true,
+                    Prefix::Dt => {}
+                }
+            }
+
+            if let Some(nested_roots) = Roots::of(element.value()) {
+                *skip = true;
+                found.nested = true;
+                let nested = self.item(element, nested_roots);
+                if properties.is_empty() {
+                    item.children.push(nested);
+                    return;
+                }
+                for (prefix, name) in properties {
+                    let value = self.nested_value(element, &nested, prefix, &mut found);
+                    push(&mut item, &name, PropertyValue::Item(value));
+                }
+                return;
+            }
+
+            for (prefix, name) in properties {
+                let value = self.value(element, prefix, &mut found);
+                push(&mut item, &name, value);
+            }
+        });
+
+        if let Roots::Mf2(_) = roots
+            && !found.nested
+        {
+            if !found.p_or_e && !item.properties.contains_key("name") {
+                let name = self.implied_name(root);
+                push(&mut item, "name", PropertyValue::Text(name));
+            }
+            if !found.u
+                && !item.properties.contains_key("photo")
+                && let Some(photo) = self.implied_photo(root)
+            {
+                push(&mut item, "photo", photo);
+            }
+            if !found.u
+                && !item.properties.contains_key("url")
+                && let Some(url) = self.implied_url(root)
+            {
+                push(&mut item, "url", PropertyValue::Text(url));
+            }
+        }
+
+        item
+    }
+
+    /// Returns a nested item with the plain value it has as a property.
+    fn nested_value(
+        &self,
+        element: ElementRef<'_>,
+        nested: &Item,
+        prefix: Prefix,
+        found: &mut Found,
+    ) -> Item {
+        let first = |name: &str| {
+            nested
+                .properties
+                .get(name)
+                .and_then(|values| values.first())
+                .and_then(PropertyValue::as_str)
+                .map(str::to_owned)
+        };
+
+        let mut nested = nested.clone();
+        match prefix {
+            Prefix::P => {
+                nested.value = first("name").or_else(|| Some(self.p_value(element)));
+            }
+            Prefix::U => {
+                nested.value =
+                    first("url").or_else(|| self.u_value(element).as_str().map(str::to_owned));
+            }
+            Prefix::Dt | Prefix::E => {
+                if let PropertyValue::Text(value) | PropertyValue::Html { value, .. } =
+                    self.value(element, prefix, found)
+                {
+                    nested.value = Some(value);
+                }
+                if prefix == Prefix::E {
+                    nested.html = Some(element.inner_html().trim().to_owned());
+                }
+            }
+        }
+        nested
+    }
+
+    fn value(&self, element: ElementRef<'_>, prefix: Prefix, found: &mut Found) -> PropertyValue {
+        match prefix {
+            Prefix::P => PropertyValue::Text(self.p_value(element)),
+            Prefix::U => self.u_value(element),
+            Prefix::Dt => {
+                let mut value = self.dt_value(element);
+                if value_class::is_date(value.get(..10).unwrap_or(&value)) {
+                    found.date = Some(value[..10].to_owned());
+                } else if let Some(date) = &found.date
+                    && value_class::parse_time(&value).is_some()
+                {
+                    value = format!("{date} {value}");
+                }
+                PropertyValue::Text(value)
+            }
+            Prefix::E => PropertyValue::Html {
+                html: element.inner_html().trim().to_owned(),
+                value: self.text(element),
+            },
+        }
+    }
+
+    fn p_value(&self, element: ElementRef<'_>) -> String {
+        if let Some(value) = value_class::text(element) {
+            return value;
+        }
+
+        let value = element.value();
+        let attr = match value.name() {
+            "abbr" | "link" => value.attr("title"),
+            "data" | "input" => value.attr("value"),
+            "img" | "area" => value.attr("alt"),
+            _ => None,
+        };
+        attr.map_or_else(|| self.text(element), str::to_owned)
+    }
+
+    fn u_value(&self, element: ElementRef<'_>) -> PropertyValue {
+        let value = element.value();
+        let url = match value.name() {
+            "a" | "area" | "link" => value.attr("href"),
+            "img" => {
+                if let Some(src) = value.attr("src") {
+                    return self.image(src, value.attr("alt"));
+                }
+                None
+            }
+            "audio" | "iframe" | "source" => value.attr("src"),
+            "video" => value.attr("src").or_else(|| value.attr("poster")),
+            "object" => value.attr("data"),
+            _ => None,
+        };
+        if let Some(url) = url {
+            return PropertyValue::Text(self.resolve(url));
+        }
+        if let Some(url) = value_class::text(element) {
+            return PropertyValue::Text(self.resolve(&url));
+        }
+
+        let attr = match value.name() {
+            "abbr" => value.attr("title"),
+            "data" | "input" => value.attr("value"),
+            _ => None,
+        };
+        PropertyValue::Text(attr.map_or_else(|| self.text(element), str::to_owned))
+    }
+
+    fn dt_value(&self, element: ElementRef<'_>) -> String {
+        if let Some(value) = value_class::datetime(element) {
+            return value;
+        }
+
+        let value = element.value();
+        let attr = match value.name() {
+            "time" | "ins" | "del" => value.attr("datetime"),
+            "abbr" => value.attr("title"),
+            "data" | "input" => value.attr("value"),
+            _ => None,
+        };
+        attr.map_or_else(|| self.text(element), str::to_owned)
+    }
+
+    fn image(&self, src: &str, alt: Option<&str>) -> PropertyValue {
+        let value = self.resolve(src);
+        match alt {
+            Some(alt) => PropertyValue::Image {
+                value,
+                alt: alt.to_owned(),
+            },
+            None => PropertyValue::Text(value),
+        }
+    }
+
+    /// Returns the text of an element without `<script>` and `<style>` contents, with images
+    /// replaced by their alternative text or URL, and with surrounding whitespace removed.
+    pub(super) fn text(&self, element: ElementRef<'_>) -> String {
+        let mut text = String::new();
+        let mut skipped = None;
+        for edge in element.traverse() {
+            match edge {
+                Edge::Open(node) if skipped.is_none() => {
+                    if let Some(t) = node.value().as_text() {
+                        text.push_str(t);
+                        continue;
+                    }
+                    if is_template_contents(node) {
+                        skipped = Some(node.id());
+                        continue;
+                    }
+                    let Some(child) = node.value().as_element() else {
+                        continue;
+                    };
+                    match child.name() {
+                        "script" | "style" => skipped = Some(node.id()),
+                        "img" => match (child.attr("alt"), child.attr("src")) {
+                            (Some(alt), _) => text.push_str(alt),
+                            (None, Some(src)) => {
+                                text.push(' ');
+                                text.push_str(&self.resolve(src));
+                                text.push(' ');
+                            }
+                            (None, None) => {}
+                        },
+                        _ => {}
+                    }
+                }
+                Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+                _ => {}
+            }
+        }
+        text.trim().to_owned()
+    }
+
+    fn implied_name(&self, root: ElementRef<'_>) -> String {
+        let from = |element: ElementRef<'_>, non_empty: bool| {
+            let value = element.value();
+            let attr = match value.name() {
+                "img" | "area" => value.attr("alt"),
+                "abbr" => value.attr("title"),
+                _ => None,
+            };
+            attr.filter(|attr| !non_empty || !attr.is_empty())
+                .map(str::to_owned)
+        };
+
+        from(root, false)
+            .or_else(|| {
+                let child = only_child(root)?;
+                from(child, true).or_else(|| from(only_child(child)?, true))
+            })
+            .unwrap_or_else(|| self.text(root))
+    }
+
+    fn implied_photo(&self, root: ElementRef<'_>) -> Option<PropertyValue> {
+        let from = |element: ElementRef<'_>| {
+            let value = element.value();
+            match value.name() {
+                "img" => value
+                    .attr("src")
+                    .map(|src| self.image(src, value.attr("alt"))),
+                "object" => value
+                    .attr("data")
+                    .map(|data| PropertyValue::Text(self.resolve(data))),
+                _ => None,
+            }
+        };
+        let of_type = |parent: ElementRef<'_>| {
+            ["img", "object"]
+                .iter()
+                .find_map(|name| only_of_type(parent, name).and_then(from))
+        };
+
+        from(root)
+            .or_else(|| of_type(root))
+            .or_else(|| only_child(root).and_then(of_type))
+    }
+
+    fn implied_url(&self, root: ElementRef<'_>) -> Option<String> {
+        let from = |element: ElementRef<'_>| {
+            let value = element.value();
+            matches!(value.name(), "a" | "area")
+                .then(|| value.attr("href"))
+                .flatten()
+                .map(|href| self.resolve(href))
+        };
+        let of_type = |parent: ElementRef<'_>| {
+            ["a", "area"]
+                .iter()
+                .find_map(|name| only_of_type(parent, name).and_then(from))
+        };
+
+        from(root)
+            .or_else(|| of_type(root))
+            .or_else(|| only_child(root).and_then(of_type))
+    }
+}
+
+fn push(item: &mut Item, name: &str, value: PropertyValue) {
+    item.properties
+        .entry(name.to_owned())
+        .or_default()
+        .push(value);
+}
+
+/// Returns the only child element of an element, if it is not an item root.
+fn only_child(element: ElementRef<'_>) -> Option<ElementRef<'_>> {
+    let mut children = element.child_elements();
+    let child = children.next()?;
+    (children.next().is_none() && Roots::of(child.value()).is_none()).then_some(child)
+}
+
+/// Returns the only child element with a name, if it is not an item root.
+fn only_of_type<'a>(element: ElementRef<'a>, name: &str) -> Option<ElementRef<'a>> {
+    let mut children = element
+        .child_elements()
+        .filter(|child| child.value().name() == name);
+    let child = children.next()?;
+    (children.next().is_none() && Roots::of(child.value()).is_none()).then_some(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::name;
+    use crate::Html;
+    use crate::microformats::{PropertyValue, parse};
+
+    fn text(value: &str) -> PropertyValue {
+        PropertyValue::Text(value.to_owned())
+    }
+
+    #[test]
+    fn class_names() {
+        assert_eq!(name("h-card", "h-"), Some("card"));
+        assert_eq!(name("h-review-aggregate", "h-"), Some("review-aggregate"));
+        assert_eq!(name("h-x1-card", "h-"), Some("x1-card"));
+        assert_eq!(name("h-Card", "h-"), None);
+        assert_eq!(name("h-card-", "h-"), None);
+        assert_eq!(name("h-", "h-"), None);
+        assert_eq!(name("h-1", "h-"), None);
+    }
+
+    #[test]
+    fn implied_properties() {
+        let html = Html::parse_fragment(
+            r#"<img class="h-card" src="/a.png" alt="Ann">
+            <div class="h-card"><a href="/bob"><img src="/b.png"></a></div>
+            <div class="h-card"><abbr title="Carol Doe">CD</abbr></div>
+            <div class="h-card"><p class="p-name">Dan</p><a href="/dan">site</a></div>
+            <div class="h-card"><span class="p-org">Org</span><a class="u-url" href="/e">E</a></div>"#,
+        );
+        let items = parse(&html, None).items;
+        assert_eq!(items.len(), 5);
+
+        assert_eq!(items[0].properties["name"], [text("Ann")]);
+        assert_eq!(
+            items[0].properties["photo"],
+            [PropertyValue::Image {
+                value: "/a.png".to_owned(),
+                alt: "Ann".to_owned()
+            }]
+        );
+
+        assert_eq!(items[1].properties["name"], [text("/b.png")]);
+        assert_eq!(items[1].properties["photo"], [text("/b.png")]);
+        assert_eq!(items[1].properties["url"], [text("/bob")]);
+
+        assert_eq!(items[2].properties["name"], [text("Carol Doe")]);
+
+        assert_eq!(items[3].properties["name"], [text("Dan")]);
+        assert_eq!(items[3].properties["url"], [text("/dan")]);
+
+        assert!(!items[4].properties.contains_key("name"));
+        assert!(!items[4].properties.contains_key("photo"));
+    }
+
+    #[test]
+    fn property_values() {
+        let html = Html::parse_fragment(
+            r#"<div class="h-event">
+            <span class="p-name">Party <script>x()</script><img alt="!"></span>
+            <abbr class="p-summary" title="Full summary">Sum</abbr>
+            <data class="p-count" value="3">three</data>
+            <span class="p-location"><span class="value">Main</span> <b class="value">St</b></span>
+            <time class="dt-start" datetime="2024-05-01 18:00">May 1</time>
+            <span class="dt-end"><span class="value">9pm</span></span>
+            <span class="dt-published">
+                <span class="value">2024-04-01</span> at <span class="value">10:30</span>
+                <span class="value">-07:00</span>
+            </span>
+            <a class="u-url u-uid" href="/party">link</a>
+            <span class="u-photo">/fallback.png</span>
+            <div class="e-content"> <p>Hello <i>all</i></p> </div>
+            </div>"#,
+        );
+        let event = &parse(&html, None).items[0];
+        let properties = &event.properties;
+        assert_eq!(properties["name"], [text("Party !")]);
+        assert_eq!(properties["summary"], [text("Full summary")]);
+        assert_eq!(properties["count"], [text("3")]);
+        assert_eq!(properties["location"], [text("MainSt")]);
+        assert_eq!(properties["start"], [text("2024-05-01 18:00")]);
+        assert_eq!(properties["end"], [text("2024-05-01 21:00")]);
+        assert_eq!(properties["published"], [text("2024-04-01 10:30-0700")]);
+        assert_eq!(properties["url"], [text("/party")]);
+        assert_eq!(properties["uid"], [text("/party")]);
+        assert_eq!(properties["photo"], [text("/fallback.png")]);
+        assert_eq!(
+            properties["content"],
+            [PropertyValue::Html {
+                html: "<p>Hello <i>all</i></p>".to_owned(),
+                value: "Hello all".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn nested_items() {
+        let html = Html::parse_fragment(
+            r#"<div class="h-entry">
+            <div class="p-author h-card"><a class="u-url" href="/ann">Ann</a></div>
+            <a class="u-in-reply-to h-cite" href="/post"><span class="p-name">A post</span></a>
+            <div class="h-card">Child</div>
+            </div>"#,
+        );
+        let entry = &parse(&html, None).items[0];
+        assert!(!entry.properties.contains_key("name"));
+
+        let PropertyValue::Item(author) = &entry.properties["author"][0] else {
+            panic!("author is not an item");
+        };
+        assert_eq!(author.value.as_deref(), Some("Ann"));
+
+        let PropertyValue::Item(cite) = &entry.properties["in-reply-to"][0] else {
+            panic!("in-reply-to is not an item");
+        };
+        assert_eq!(cite.types, ["h-cite"]);
+        assert_eq!(cite.value.as_deref(), Some("/post"));
+
+        assert_eq!(entry.children.len(), 1);
+        assert_eq!(entry.children[0].properties["name"], [text("Child")]);
+    }
+
+    #[test]
+    fn classic_microformats() {
+        let html = Html::parse_fragment(
+            r#"<div class="hentry">
+            <h2 class="entry-title"><a rel="bookmark" href="/p/1">Title</a></h2>
+            <span class="author vcard"><span class="fn">Ann</span></span>
+            <abbr class="published" title="2024-01-01T10:00">Jan 1</abbr>
+            <a rel="tag" href="/tags/rust">rust</a>
+            <div class="entry-content"><p>Body</p></div>
+            <span class="h-ignored p-ignored">mf2 classes</span>
+            </div>"#,
+        );
+        let entry = &parse(&html, None).items[0];
+        assert_eq!(entry.types, ["h-entry"]);
+        assert_eq!(entry.properties["name"], [text("Title")]);
+        assert_eq!(entry.properties["url"], [text("/p/1")]);
+        assert_eq!(entry.properties["published"], [text("2024-01-01T10:00")]);
+        assert_eq!(entry.properties["category"], [text("rust")]);
+        assert!(!entry.properties.contains_key("ignored"));
+
+        let PropertyValue::Item(author) = &entry.properties["author"][0] else {
+            panic!("author is not an item");
+        };
+        assert_eq!(author.types, ["h-card"]);
+        assert_eq!(author.value.as_deref(), Some("Ann"));
+        assert_eq!(entry.properties["content"][0].as_str(), Some("Body"));
+    }
+}
@@ -0,0 +1,221 @@
+//! The value class pattern, which takes a property value from parts of an element marked with
+//! `class="value"` or `class="value-title"`.
+
+use ego_tree::iter::Edge;
+
+use super::parse::has_mf2_class;
+use crate::ElementRef;
+use crate::element_ref::is_template_contents;
+
+/// Returns the value elements of a property element, skipping those inside nested properties and
+/// items.
+fn value_elements(element: ElementRef<'_>) -> Vec<ElementRef<'_>> {
+    let mut values = Vec::new();
+    let mut skipped = None;
+    for edge in element.traverse() {
+        match edge {
+            Edge::Open(node) if skipped.is_none() && node != *element => {
+                if is_template_contents(node) {
+                    skipped = Some(node.id());
+                    continue;
+                }
+                let Some(child) = ElementRef::wrap(node) else {
+                    continue;
+                };
+                let classes = child.value();
+                if classes
+                    .classes()
+                    .any(|c| c == "value" || c == "value-title")
+                {
+                    values.push(child);
+                    skipped = Some(node.id());
+                } else if has_mf2_class(classes) {
+                    skipped = Some(node.id());
+                }
+            }
+            Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+            _ => {}
+        }
+    }
+    values
+}
+
+/// Returns the value of a value element, with `datetime` used for date and time elements.
+fn part(element: ElementRef<'_>, datetime: bool) -> String {
+    let value = element.value();
+    let text = || element.text().collect::<String>();
+    if value.classes().any(|c| c == "value-title") {
+        return value.attr("title").unwrap_or_default().to_owned();
+    }
+    match value.name() {
+        "img" | "area" => value.attr("alt").unwrap_or_default().to_owned(),
+        "data" => value.attr("value").map_or_else(text, str::to_owned),
+        "abbr" => value.attr("title").map_or_else(text, str::to_owned),
+        "del" | "ins" | "time" if datetime => {
+            value.attr("datetime").map_or_else(text, str::to_owned)
+        }
+        _ => text(),
+    }
+}
+
+/// Returns the value of a `p-` or `u-` property from its value elements, if it has any.
+pub(super) fn text(element: ElementRef<'_>) -> Option<String> {
+    let values = value_elements(element);
+    (!values.is_empty()).then(|| values.into_iter().map(|value| part(value, false)).collect())
+}
+
+/// Returns the value of a `dt-` property from its value elements, if it has any.
+///
+/// The first date, time and time zone found are combined into `YYYY-MM-DD HH:MM:SS-XXYY`, with
+/// times in 12-hour notation converted and missing parts left out.
+pub(super) fn datetime(element: ElementRef<'_>) -> Option<String> {
+    let parts = value_elements(element)
+        .into_iter()
+        .map(|value| part(value, true).trim().to_owned())
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        return None;
+    }
+
+    let mut date = None;
+    let mut time = None;
+    let mut zone = None;
+    for part in &parts {
+        if let Some((d, t)) = part.split_once(['T', 't', ' '])
+            && is_date(d)
+            && date.is_none()
+            && time.is_none()
+            && let Some((t, z)) = parse_time(t)
+        {
+            date = Some(d.to_owned());
+            time = Some(t);
+            zone = zone.or(z);
+        } else if is_date(part) {
+            date = date.or_else(|| Some(part.clone()));
+        } else if let Some(z) = parse_zone(part) {
+            zone = zone.or(Some(z));
+        } else if let Some((t, z)) = parse_time(part) {
+            time = time.or(Some(t));
+            zone = zone.or(z);
+        }
+    }
+
+    Some(match (date, time) {
+        (Some(date), Some(time)) => format!("{date} {time}{}", zone.unwrap_or_default()),
+        (Some(date), None) => date,
+        (None, Some(time)) => format!("{time}{}", zone.unwrap_or_default()),
+        (None, None) => parts.concat(),
+    })
+}
+
+/// Returns true for `YYYY-MM-DD` and ordinal `YYYY-DDD` dates.
+pub(super) fn is_date(value: &str) -> bool {
+    let digits = |s: &str, n: usize| s.len() == n && s.bytes().all(|b| b.is_ascii_digit());
+    let parts = value.split('-').collect::<Vec<_>>();
+    match parts[..] {
+        [year, month, day] => digits(year, 4) && digits(month, 2) && digits(day, 2),
+        [year, day] => digits(year, 4) && digits(day, 3),
+        _ => false,
+    }
+}
+
+/// Parses a time zone of `Z`, `±HH`, `±HHMM` or `±HH:MM`, returning it without the colon.
+fn parse_zone(value: &str) -> Option<String> {
+    if value.eq_ignore_ascii_case("z") {
+        return Some("Z".to_owned());
+    }
+    let digits = value
+        .strip_prefix(['+', '-'])?
+        .chars()
+        .filter(|c| *c != ':')
+        .collect::<String>();
+    (matches!(digits.len(), 2 | 4) && digits.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| format!("{}{digits}", &value[..1]))
+}
+
+/// Parses a time such as `14:30`, `2:30:15pm` or `7 p.m.`, with an optional time zone,
+/// returning it in 24-hour `HH:MM` or `HH:MM:SS` notation.
+pub(super) fn parse_time(value: &str) -> Option<(String, Option<String>)> {
+    let lower = value.trim().to_ascii_lowercase();
+
+    let (lower, zone) = match lower.find(['z', '+', '-']) {
+        Some(index) if index > 0 => (
+            lower[..index].trim().to_owned(),
+            Some(parse_zone(&value.trim()[index..])?),
+        ),
+        _ => (lower.clone(), None),
+    };
+
+    let (clock, meridiem) = if let Some(clock) = lower
+        .strip_suffix("am")
+        .or_else(|| lower.strip_suffix("a.m."))
+    {
+        (clock.trim_end(), Some(false))
+    } else if let Some(clock) = lower
+        .strip_suffix("pm")
+        .or_else(|| lower.strip_suffix("p.m."))
+    {
+        (clock.trim_end(), Some(true))
+    } else {
+        (lower.as_str(), None)
+    };
+
+    let fields = clock.split(':').collect::<Vec<_>>();
+    if fields.is_empty()
+        || fields.len() > 3
+        || fields.iter().any(|field| {
+            field.is_empty() || field.len() > 2 || !field.bytes().all(|b| b.is_ascii_digit())
+        })
+    {
+        return None;
+    }
+
+    let mut hour = fields[0].parse::<u32>().ok()?;
+    match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => {
+            hour = hour % 12 + if pm { 12 } else { 0 };
+        }
+        Some(_) => return None,
+        // A bare hour is only a time with am or pm.
+        None if fields.len() == 1 => return None,
+        None => {}
+    }
+    if hour > 23 {
+        return None;
+    }
+
+    let mut time = format!("{hour:02}:{}", fields.get(1).copied().unwrap_or("00"));
+    if let Some(seconds) = fields.get(2) {
+        time.push(':');
+        time.push_str(seconds);
+    }
+    Some((time, zone))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_date, parse_time};
+
+    #[test]
+    fn dates_and_times() {
+        assert!(is_date("2024-02-29"));
+        assert!(is_date("2024-060"));
+        assert!(!is_date("2024-2-29"));
+
+        let time = parse_time;
+        assert_eq!(time("14:30"), Some(("14:30".to_owned(), None)));
+        assert_eq!(time("2:30:15pm"), Some(("14:30:15".to_owned(), None)));
+        assert_eq!(time("12 a.m."), Some(("00:00".to_owned(), None)));
+        assert_eq!(
+            time("9:00-08:00"),
+            Some(("09:00".to_owned(), Some("-0800".to_owned())))
+        );
+        assert_eq!(
+            time("10:00Z"),
+            Some(("10:00".to_owned(), Some("Z".to_owned())))
+        );
+        assert_eq!(time("13pm"), None);
+        assert_eq!(time("noon"), None);
+        assert_eq!(time("5"), None);
+    }
+}
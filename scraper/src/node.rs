@@ -4,9 +4,14 @@ use std::fmt;
 use std::ops::Deref;
 use std::slice::Iter as SliceIter;
 
-use crate::{CaseSensitivity, StrTendril};
+use crate::CaseSensitivity;
+use crate::error::InvalidClass;
+use crate::tendril_util::NodeStr;
 use html5ever::{Attribute, LocalName, QualName};
+#[cfg(not(feature = "atomic"))]
 use std::cell::OnceCell;
+#[cfg(feature = "atomic")]
+use std::sync::OnceLock as OnceCell;
 
 /// An HTML node.
 // `Element` is usally the most common variant and hence boxing it
@@ -37,6 +42,26 @@ pub enum Node {
 }
 
 impl Node {
+    /// Creates an HTML element without attributes.
+    pub fn new_element(name: &str) -> Self {
+        Node::Element(Element::new(
+            QualName::new(None, ns!(html), LocalName::from(name)),
+            Vec::new(),
+        ))
+    }
+
+    /// Creates a text node.
+    pub fn new_text(text: &str) -> Self {
+        Node::Text(Text { text: text.into() })
+    }
+
+    /// Creates a comment.
+    pub fn new_comment(comment: &str) -> Self {
+        Node::Comment(Comment {
+            comment: comment.into(),
+        })
+    }
+
     /// Returns true if node is the document root.
     pub fn is_document(&self) -> bool {
         matches!(*self, Node::Document)
@@ -127,13 +152,13 @@ impl fmt::Debug for Node {
 #[derive(Clone, PartialEq, Eq)]
 pub struct Doctype {
     /// The doctype name.
-    pub name: StrTendril,
+    pub name: NodeStr,
 
     /// The doctype public ID.
-    pub public_id: StrTendril,
+    pub public_id: NodeStr,
 
     /// The doctype system ID.
-    pub system_id: StrTendril,
+    pub system_id: NodeStr,
 }
 
 impl Doctype {
@@ -169,7 +194,7 @@ impl fmt::Debug for Doctype {
 #[derive(Clone, PartialEq, Eq)]
 pub struct Comment {
     /// The comment text.
-    pub comment: StrTendril,
+    pub comment: NodeStr,
 }
 
 impl Deref for Comment {
@@ -190,7 +215,7 @@ impl fmt::Debug for Comment {
 #[derive(Clone, PartialEq, Eq)]
 pub struct Text {
     /// The text.
-    pub text: StrTendril,
+    pub text: NodeStr,
 }
 
 impl Deref for Text {
@@ -209,13 +234,13 @@ impl fmt::Debug for Text {
 
 /// A Map of attributes that preserves the order of the attributes.
 #[cfg(feature = "deterministic")]
-pub type Attributes = indexmap::IndexMap<QualName, StrTendril>;
+pub type Attributes = indexmap::IndexMap<QualName, NodeStr>;
 
 /// A Map of attributes that doesn't preserve the order of the attributes.
 /// Please enable the `deterministic` feature for order-preserving
 /// (de)serialization.
 #[cfg(not(feature = "deterministic"))]
-pub type Attributes = Vec<(QualName, StrTendril)>;
+pub type Attributes = Vec<(QualName, NodeStr)>;
 
 /// An HTML element.
 #[derive(Clone, PartialEq, Eq)]
@@ -224,11 +249,17 @@ pub struct Element {
     pub name: QualName,
 
     /// The element attributes.
+    ///
+    /// Editing these directly leaves the cached results of [`Element::id`] and
+    /// [`Element::classes`] stale; prefer methods such as [`Element::set_attr`].
     pub attrs: Attributes,
 
-    id: OnceCell<Option<StrTendril>>,
+    id: OnceCell<Option<NodeStr>>,
 
     classes: OnceCell<Box<[LocalName]>>,
+
+    #[cfg(feature = "spans")]
+    pub(crate) source_span: Option<crate::span::SourceSpan>,
 }
 
 impl Element {
@@ -248,6 +279,8 @@ impl Element {
             name,
             id: OnceCell::new(),
             classes: OnceCell::new(),
+            #[cfg(feature = "spans")]
+            source_span: None,
         }
     }
 
@@ -297,7 +330,7 @@ impl Element {
 
     /// Returns the value of an attribute.
     pub fn attr(&self, attr: &str) -> Option<&str> {
-        let qualname = QualName::new(None, ns!(), LocalName::from(attr));
+        let qualname = Self::attr_name(attr);
 
         #[cfg(not(feature = "deterministic"))]
         let value = self
@@ -318,6 +351,168 @@ impl Element {
             inner: self.attrs.iter(),
         }
     }
+
+    /// Sets the value of an attribute without a namespace, adding it if it is missing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::Node;
+    ///
+    /// let mut node = Node::new_element("a");
+    /// let Node::Element(a) = &mut node else { unreachable!() };
+    ///
+    /// a.set_attr("href", "/home");
+    /// a.set_id("home");
+    /// a.add_class("nav").unwrap();
+    /// assert_eq!(a.attr("href"), Some("/home"));
+    /// assert_eq!(a.id(), Some("home"));
+    /// assert!(a.classes().eq(["nav"]));
+    /// ```
+    pub fn set_attr(&mut self, name: &str, value: &str) {
+        self.set_attr_ns(Self::attr_name(name), value);
+    }
+
+    /// Sets the value of an attribute with a qualified name, adding it if it is missing.
+    ///
+    /// This is needed for namespaced attributes such as `xlink:href`.
+    pub fn set_attr_ns(&mut self, name: QualName, value: &str) {
+        let value = NodeStr::from(value);
+
+        #[cfg(not(feature = "deterministic"))]
+        match self.attrs.binary_search_by(|attr| attr.0.cmp(&name)) {
+            Ok(idx) => self.attrs[idx].1 = value,
+            Err(idx) => self.attrs.insert(idx, (name, value)),
+        }
+
+        #[cfg(feature = "deterministic")]
+        self.attrs.insert(name, value);
+
+        self.reset_caches();
+    }
+
+    /// Removes an attribute without a namespace, returning its value if it was present.
+    pub fn remove_attr(&mut self, name: &str) -> Option<NodeStr> {
+        self.remove_attr_ns(&Self::attr_name(name))
+    }
+
+    /// Removes an attribute with a qualified name, returning its value if it was present.
+    pub fn remove_attr_ns(&mut self, name: &QualName) -> Option<NodeStr> {
+        #[cfg(not(feature = "deterministic"))]
+        let value = self
+            .attrs
+            .binary_search_by(|attr| attr.0.cmp(name))
+            .ok()
+            .map(|idx| self.attrs.remove(idx).1);
+
+        #[cfg(feature = "deterministic")]
+        let value = self.attrs.shift_remove(name);
+
+        if value.is_some() {
+            self.reset_caches();
+        }
+        value
+    }
+
+    /// Removes an attribute without a namespace if it is present, or adds it with an empty value
+    /// if it is not. Returns true if the attribute is present afterwards.
+    pub fn toggle_attr(&mut self, name: &str) -> bool {
+        if self.remove_attr(name).is_some() {
+            false
+        } else {
+            self.set_attr(name, "");
+            true
+        }
+    }
+
+    /// Sets the element ID.
+    pub fn set_id(&mut self, id: &str) {
+        self.set_attr("id", id);
+    }
+
+    /// Adds a class if the element does not have it already.
+    ///
+    /// Like the DOM's `classList`, this rewrites the `class` attribute as the element's classes
+    /// separated by single spaces. Fails if `class` is empty or contains whitespace.
+    pub fn add_class(&mut self, class: &str) -> Result<(), InvalidClass> {
+        Self::check_class(class)?;
+        self.edit_classes(class, |classes, class| {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        });
+        Ok(())
+    }
+
+    /// Removes a class, returning true if the element had it.
+    pub fn remove_class(&mut self, class: &str) -> bool {
+        if Self::check_class(class).is_err() {
+            return false;
+        }
+
+        let mut removed = false;
+        self.edit_classes(class, |classes, class| {
+            let len = classes.len();
+            classes.retain(|c| *c != class);
+            removed = classes.len() != len;
+        });
+        removed
+    }
+
+    /// Removes a class if the element has it, or adds it if it does not. Returns true if the
+    /// element has the class afterwards.
+    ///
+    /// Fails if `class` is empty or contains whitespace.
+    pub fn toggle_class(&mut self, class: &str) -> Result<bool, InvalidClass> {
+        Self::check_class(class)?;
+        let mut added = false;
+        self.edit_classes(class, |classes, class| {
+            let len = classes.len();
+            classes.retain(|c| *c != class);
+            if classes.len() == len {
+                classes.push(class);
+                added = true;
+            }
+        });
+        Ok(added)
+    }
+
+    fn check_class(class: &str) -> Result<(), InvalidClass> {
+        if class.is_empty() || class.contains(|c: char| c.is_ascii_whitespace()) {
+            return Err(InvalidClass {
+                class: class.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    fn edit_classes(&mut self, class: &str, edit: impl for<'c> FnOnce(&mut Vec<&'c str>, &'c str)) {
+        let value = self.attr("class").unwrap_or_default().to_owned();
+        let mut classes = Vec::new();
+        for c in value.split_ascii_whitespace() {
+            if !classes.contains(&c) {
+                classes.push(c);
+            }
+        }
+
+        edit(&mut classes, class);
+        self.set_attr("class", &classes.join(" "));
+    }
+
+    fn attr_name(name: &str) -> QualName {
+        QualName::new(None, ns!(), LocalName::from(name))
+    }
+
+    fn reset_caches(&mut self) {
+        self.id.take();
+        self.classes.take();
+    }
+
+    /// Returns the location of the element in the parsed input, if it was recorded.
+    #[cfg(feature = "spans")]
+    pub fn source_span(&self) -> Option<crate::span::SourceSpan> {
+        self.source_span
+    }
 }
 
 /// Iterator over classes.
@@ -337,11 +532,11 @@ impl<'a> Iterator for Classes<'a> {
 
 /// An iterator over a node's attributes.
 #[cfg(feature = "deterministic")]
-pub type AttributesIter<'a> = indexmap::map::Iter<'a, QualName, StrTendril>;
+pub type AttributesIter<'a> = indexmap::map::Iter<'a, QualName, NodeStr>;
 
 /// An iterator over a node's attributes.
 #[cfg(not(feature = "deterministic"))]
-pub type AttributesIter<'a> = SliceIter<'a, (QualName, StrTendril)>;
+pub type AttributesIter<'a> = SliceIter<'a, (QualName, NodeStr)>;
 
 /// Iterator over attributes.
 #[allow(missing_debug_implementations)]
@@ -372,9 +567,9 @@ impl fmt::Debug for Element {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProcessingInstruction {
     /// The PI target.
-    pub target: StrTendril,
+    pub target: NodeStr,
     /// The PI data.
-    pub data: StrTendril,
+    pub data: NodeStr,
 }
 
 impl Deref for ProcessingInstruction {
@@ -385,4 +580,120 @@ impl Deref for ProcessingInstruction {
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde;
 pub(crate) mod serializable;
+
+#[cfg(test)]
+mod tests {
+    use html5ever::{LocalName, QualName};
+
+    use super::Node;
+    use crate::{Html, Selector};
+
+    fn element() -> Node {
+        let html = Html::parse_fragment(r#"<p id="a" class="x  y x" title="t">"#);
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+        Node::Element(p.value().clone())
+    }
+
+    #[test]
+    fn attribute_edits_reset_caches() {
+        let mut node = element();
+        let Node::Element(p) = &mut node else {
+            unreachable!()
+        };
+        assert_eq!(p.id(), Some("a"));
+        assert_eq!(p.classes().count(), 2);
+
+        p.set_id("b");
+        p.set_attr("zz", "1");
+        p.set_attr("aa", "2");
+        assert_eq!(p.id(), Some("b"));
+        for (name, value) in [
+            ("aa", "2"),
+            ("class", "x  y x"),
+            ("title", "t"),
+            ("zz", "1"),
+        ] {
+            assert_eq!(p.attr(name), Some(value));
+        }
+
+        assert_eq!(p.remove_attr("id").as_deref(), Some("b"));
+        assert_eq!(p.remove_attr("id"), None);
+        assert_eq!(p.id(), None);
+
+        assert!(p.toggle_attr("hidden"));
+        assert_eq!(p.attr("hidden"), Some(""));
+        assert!(!p.toggle_attr("hidden"));
+        assert_eq!(p.attr("hidden"), None);
+    }
+
+    #[test]
+    fn class_edits() {
+        let mut node = element();
+        let Node::Element(p) = &mut node else {
+            unreachable!()
+        };
+
+        p.add_class("z").unwrap();
+        p.add_class("x").unwrap();
+        assert_eq!(p.attr("class"), Some("x y z"));
+        assert!(p.classes().eq(["x", "y", "z"]));
+
+        assert!(p.remove_class("y"));
+        assert!(!p.remove_class("y"));
+        assert_eq!(p.toggle_class("x"), Ok(false));
+        assert_eq!(p.toggle_class("w"), Ok(true));
+        assert_eq!(p.attr("class"), Some("z w"));
+        assert!(p.classes().eq(["w", "z"]));
+    }
+
+    #[test]
+    fn class_with_whitespace() {
+        let mut node = element();
+        let Node::Element(p) = &mut node else {
+            unreachable!()
+        };
+
+        let error = p.add_class("a b").unwrap_err();
+        assert_eq!(error.class, "a b");
+        assert!(p.toggle_class("").is_err());
+        assert!(!p.remove_class("x y"));
+        assert_eq!(p.attr("class"), Some("x  y x"));
+    }
+
+    #[test]
+    fn namespaced_attributes() {
+        let mut html = Html::parse_fragment(r##"<svg><use xlink:href="#a"/></svg>"##);
+        let id = html
+            .select(&Selector::parse("use").unwrap())
+            .next()
+            .unwrap()
+            .id();
+
+        let href = QualName::new(
+            Some(namespace_prefix!("xlink")),
+            ns!(xlink),
+            LocalName::from("href"),
+        );
+        let mut node = html.get_mut(id).unwrap();
+        let element = node.as_element_mut().unwrap();
+        element.set_attr_ns(href.clone(), "#b");
+        element.set_attr("width", "1");
+        assert_eq!(element.attrs().count(), 2);
+        assert!(
+            html.root_element()
+                .inner_html()
+                .contains(r##"xlink:href="#b""##)
+        );
+
+        let mut node = html.get_mut(id).unwrap();
+        let element = node.as_element_mut().unwrap();
+        assert_eq!(element.remove_attr_ns(&href).as_deref(), Some("#b"));
+        assert_eq!(
+            html.root_element().inner_html(),
+            r#"<svg><use width="1"></use></svg>"#
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Serde support for nodes.
+//!
+//! Nodes serialize as externally tagged enums with compact tuple payloads. Qualified names are
+//! `(prefix, namespace, local)` tuples in which the well-known namespaces are abbreviated, such as
+//! `(None, "html", "div")`.
+
+use std::fmt;
+
+use ::serde::de::{self, Deserialize, Deserializer, EnumAccess, VariantAccess, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+use html5ever::{Attribute, LocalName, Namespace, Prefix, QualName};
+
+use super::{Comment, Doctype, Element, Node, ProcessingInstruction, Text};
+use crate::tendril_util::NodeStr;
+
+const VARIANTS: &[&str] = &[
+    "document",
+    "fragment",
+    "doctype",
+    "comment",
+    "text",
+    "element",
+    "processing_instruction",
+];
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Node::Document => serializer.serialize_unit_variant("Node", 0, VARIANTS[0]),
+            Node::Fragment => serializer.serialize_unit_variant("Node", 1, VARIANTS[1]),
+            Node::Doctype(doctype) => {
+                serializer.serialize_newtype_variant("Node", 2, VARIANTS[2], doctype)
+            }
+            Node::Comment(comment) => {
+                serializer.serialize_newtype_variant("Node", 3, VARIANTS[3], comment)
+            }
+            Node::Text(text) => serializer.serialize_newtype_variant("Node", 4, VARIANTS[4], text),
+            Node::Element(element) => {
+                serializer.serialize_newtype_variant("Node", 5, VARIANTS[5], element)
+            }
+            Node::ProcessingInstruction(pi) => {
+                serializer.serialize_newtype_variant("Node", 6, VARIANTS[6], pi)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Node", VARIANTS, NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an HTML node")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (variant, access): (Variant, _) = data.variant()?;
+        Ok(match variant {
+            Variant::Document => access.unit_variant().map(|()| Node::Document)?,
+            Variant::Fragment => access.unit_variant().map(|()| Node::Fragment)?,
+            Variant::Doctype => Node::Doctype(access.newtype_variant()?),
+            Variant::Comment => Node::Comment(access.newtype_variant()?),
+            Variant::Text => Node::Text(access.newtype_variant()?),
+            Variant::Element => Node::Element(access.newtype_variant()?),
+            Variant::ProcessingInstruction => {
+                Node::ProcessingInstruction(access.newtype_variant()?)
+            }
+        })
+    }
+}
+
+enum Variant {
+    Document,
+    Fragment,
+    Doctype,
+    Comment,
+    Text,
+    Element,
+    ProcessingInstruction,
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(VariantVisitor)
+    }
+}
+
+struct VariantVisitor;
+
+impl Visitor<'_> for VariantVisitor {
+    type Value = Variant;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a node type")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match VARIANTS.get(v as usize) {
+            Some(variant) => self.visit_str(variant),
+            None => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(match v {
+            "document" => Variant::Document,
+            "fragment" => Variant::Fragment,
+            "doctype" => Variant::Doctype,
+            "comment" => Variant::Comment,
+            "text" => Variant::Text,
+            "element" => Variant::Element,
+            "processing_instruction" => Variant::ProcessingInstruction,
+            _ => return Err(E::unknown_variant(v, VARIANTS)),
+        })
+    }
+}
+
+impl Serialize for Doctype {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.name(), self.public_id(), self.system_id()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Doctype {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (name, public_id, system_id) = <(String, String, String)>::deserialize(deserializer)?;
+        Ok(Doctype {
+            name: NodeStr::from(name),
+            public_id: NodeStr::from(public_id),
+            system_id: NodeStr::from(system_id),
+        })
+    }
+}
+
+impl Serialize for Comment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.comment)
+    }
+}
+
+impl<'de> Deserialize<'de> for Comment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let comment = String::deserialize(deserializer)?;
+        Ok(Comment {
+            comment: NodeStr::from(comment),
+        })
+    }
+}
+
+impl Serialize for Text {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Text {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Ok(Text {
+            text: NodeStr::from(text),
+        })
+    }
+}
+
+impl Serialize for ProcessingInstruction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&*self.target, &*self.data).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProcessingInstruction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (target, data) = <(String, String)>::deserialize(deserializer)?;
+        Ok(ProcessingInstruction {
+            target: NodeStr::from(target),
+            data: NodeStr::from(data),
+        })
+    }
+}
+
+/// Elements serialize as their name and attributes, in the order of [`Element::attrs`].
+impl Serialize for Element {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let attrs = self
+            .attrs
+            .iter()
+            .map(|(name, value)| (Name(name), &**value))
+            .collect::<Vec<_>>();
+        (Name(&self.name), attrs).serialize(serializer)
+    }
+}
+
+/// Elements are rebuilt with [`Element::new`], so attributes are stored as if they were parsed
+/// and the cached ID and classes are computed afresh.
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (OwnedName(name), attrs) =
+            <(OwnedName, Vec<(OwnedName, String)>)>::deserialize(deserializer)?;
+        let attrs = attrs
+            .into_iter()
+            .map(|(OwnedName(name), value)| Attribute {
+                name,
+                value: value.into(),
+            })
+            .collect();
+        Ok(Element::new(name, attrs))
+    }
+}
+
+/// Abbreviations of the namespaces of HTML documents.
+fn namespaces() -> [(Namespace, &'static str); 6] {
+    [
+        (ns!(html), "html"),
+        (ns!(svg), "svg"),
+        (ns!(mathml), "mathml"),
+        (ns!(xlink), "xlink"),
+        (ns!(xml), "xml"),
+        (ns!(xmlns), "xmlns"),
+    ]
+}
+
+struct Name<'a>(&'a QualName);
+
+impl Serialize for Name<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let QualName { prefix, ns, local } = self.0;
+        let namespaces = namespaces();
+        let ns = namespaces
+            .iter()
+            .find(|(namespace, _)| namespace == ns)
+            .map_or(&**ns, |(_, abbreviation)| abbreviation);
+        (prefix.as_deref(), ns, &**local).serialize(serializer)
+    }
+}
+
+struct OwnedName(QualName);
+
+impl<'de> Deserialize<'de> for OwnedName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (prefix, ns, local) = <(Option<String>, String, String)>::deserialize(deserializer)?;
+        let ns = namespaces()
+            .into_iter()
+            .find(|(_, abbreviation)| *abbreviation == ns)
+            .map_or_else(|| Namespace::from(ns), |(namespace, _)| namespace);
+        Ok(OwnedName(QualName::new(
+            prefix.map(Prefix::from),
+            ns,
+            LocalName::from(local),
+        )))
+    }
+}
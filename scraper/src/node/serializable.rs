@@ -1,9 +1,15 @@
+use std::borrow::Cow;
+use std::fmt::Write;
 use std::io::Error;
 
 use ego_tree::{NodeRef, iter::Edge};
 use html5ever::serialize::{Serializer, TraversalScope};
+use html5ever::{LocalName, QualName};
 
-use crate::Node;
+use crate::element_ref::is_template_contents;
+use crate::html::{CharacterReferences, SerializeOptions};
+use crate::node::Element;
+use crate::{ElementRef, Node};
 
 /// Serialize an HTML node using html5ever serializer.
 pub(crate) fn serialize<S: Serializer>(
@@ -11,9 +17,19 @@ pub(crate) fn serialize<S: Serializer>(
     serializer: &mut S,
     traversal_scope: TraversalScope,
 ) -> Result<(), Error> {
+    // A `<template>` element is serialized with its contents instead of its children.
+    let mut skipped = None;
+
     for edge in self_node.traverse() {
         match edge {
             Edge::Open(node) => {
+                if skipped.is_some() {
+                    continue;
+                }
+                if node != self_node && is_ignored_template_child(node) {
+                    skipped = Some(node);
+                    continue;
+                }
                 if node == self_node && traversal_scope == TraversalScope::ChildrenOnly(None) {
                     continue;
                 }
@@ -37,6 +53,12 @@ pub(crate) fn serialize<S: Serializer>(
             }
 
             Edge::Close(node) => {
+                if skipped.is_some() {
+                    if skipped == Some(node) {
+                        skipped = None;
+                    }
+                    continue;
+                }
                 if node == self_node && traversal_scope == TraversalScope::ChildrenOnly(None) {
                     continue;
                 }
@@ -50,3 +72,762 @@ pub(crate) fn serialize<S: Serializer>(
 
     Ok(())
 }
+
+fn is_ignored_template_child(node: NodeRef<Node>) -> bool {
+    node.parent().is_some_and(|parent| {
+        ElementRef::wrap(parent).is_some_and(|parent| parent.template_contents().is_some())
+            && !is_template_contents(node)
+    })
+}
+
+/// Serialize an HTML node with the given options.
+pub(crate) fn serialize_with(
+    node: NodeRef<Node>,
+    traversal_scope: TraversalScope,
+    options: SerializeOptions,
+) -> String {
+    let mut writer = Writer {
+        options,
+        output: String::new(),
+    };
+    match traversal_scope {
+        TraversalScope::IncludeNode => {
+            let preserve = node.ancestors().any(preserves_whitespace_in);
+            let children = writer.children(&[node], None, preserve);
+            writer.siblings(&children, None, preserve);
+        }
+        TraversalScope::ChildrenOnly(_) => {
+            let preserve = node.ancestors().any(preserves_whitespace_in);
+            writer.contents(node, preserve || preserves_whitespace_in(node));
+        }
+    }
+    writer.output
+}
+
+/// A node to be written, with its text after whitespace is collapsed.
+struct Child<'a> {
+    node: NodeRef<'a, Node>,
+    text: Cow<'a, str>,
+}
+
+impl Child<'_> {
+    fn element(&self) -> Option<&QualName> {
+        self.node.value().as_element().map(|element| &element.name)
+    }
+
+    fn is(&self, names: &[LocalName]) -> bool {
+        self.element()
+            .is_some_and(|name| name.ns == ns!(html) && names.contains(&name.local))
+    }
+
+    fn is_comment(&self) -> bool {
+        self.node.value().is_comment()
+    }
+
+    fn starts_with_whitespace(&self) -> bool {
+        self.node.value().is_text() && self.text.starts_with(|c: char| c.is_ascii_whitespace())
+    }
+}
+
+struct Writer {
+    options: SerializeOptions,
+    output: String,
+}
+
+impl Writer {
+    /// Writes the children of a node, or the contents of a template.
+    fn contents(&mut self, node: NodeRef<Node>, preserve: bool) {
+        let nodes = child_nodes(node);
+        let children = self.children(&nodes, Some(node), preserve);
+        self.siblings(&children, Some(node), preserve);
+    }
+
+    /// Filters comments and collapses whitespace.
+    fn children<'a>(
+        &self,
+        nodes: &[NodeRef<'a, Node>],
+        parent: Option<NodeRef<'a, Node>>,
+        preserve: bool,
+    ) -> Vec<Child<'a>> {
+        let nodes = nodes
+            .iter()
+            .filter(|node| !(self.options.remove_comments && node.value().is_comment()))
+            .collect::<Vec<_>>();
+
+        let mut children = Vec::new();
+        for (index, node) in nodes.iter().enumerate() {
+            let text = match node.value() {
+                Node::Text(text) if self.options.collapse_whitespace && !preserve => {
+                    let start = match index.checked_sub(1) {
+                        Some(index) => is_block(*nodes[index]),
+                        None => parent.is_none_or(is_block),
+                    };
+                    let end = match nodes.get(index + 1) {
+                        Some(node) => is_block(**node),
+                        None => parent.is_none_or(is_block),
+                    };
+                    let text = collapse(text, start, end);
+                    if text.is_empty() {
+                        continue;
+                    }
+                    Cow::Owned(text)
+                }
+                Node::Text(text) => Cow::Borrowed(&**text),
+                _ => Cow::Borrowed(""),
+            };
+            children.push(Child { node: **node, text });
+        }
+        children
+    }
+
+    fn siblings(&mut self, children: &[Child], parent: Option<NodeRef<Node>>, preserve: bool) {
+        let mut end_omitted = false;
+        for (index, child) in children.iter().enumerate() {
+            let previous = index
+                .checked_sub(1)
+                .map(|index| (&children[index], end_omitted));
+            end_omitted = self.node(child, previous, children.get(index + 1), parent, preserve);
+        }
+    }
+
+    /// Writes a node and returns true if its end tag was omitted.
+    fn node(
+        &mut self,
+        child: &Child,
+        previous: Option<(&Child, bool)>,
+        next: Option<&Child>,
+        parent: Option<NodeRef<Node>>,
+        preserve: bool,
+    ) -> bool {
+        match child.node.value() {
+            Node::Document | Node::Fragment => self.contents(child.node, preserve),
+            Node::Doctype(doctype) => {
+                self.output.push_str("<!DOCTYPE ");
+                self.output.push_str(doctype.name());
+                self.output.push('>');
+            }
+            Node::Comment(comment) => {
+                self.output.push_str("<!--");
+                self.output.push_str(comment);
+                self.output.push_str("-->");
+            }
+            Node::ProcessingInstruction(pi) => {
+                self.output.push_str("<?");
+                self.output.push_str(&pi.target);
+                self.output.push(' ');
+                self.output.push_str(&pi.data);
+                self.output.push('>');
+            }
+            Node::Text(_) => {
+                if parent.is_some_and(|parent| self.is_raw_text(parent)) {
+                    self.output.push_str(&child.text);
+                } else {
+                    self.escape(&child.text, false);
+                }
+            }
+            Node::Element(element) => {
+                return self.element(child, element, previous, next, parent, preserve);
+            }
+        }
+        false
+    }
+
+    fn element(
+        &mut self,
+        child: &Child,
+        element: &Element,
+        previous: Option<(&Child, bool)>,
+        next: Option<&Child>,
+        parent: Option<NodeRef<Node>>,
+        preserve: bool,
+    ) -> bool {
+        let preserve = preserve || preserves_whitespace_in(child.node);
+        let void = is_void(&element.name);
+        let children = if void {
+            Vec::new()
+        } else {
+            self.children(&child_nodes(child.node), Some(child.node), preserve)
+        };
+
+        let omit = self.options.omit_optional_tags && element.name.ns == ns!(html);
+        if !(omit
+            && element.attrs.is_empty()
+            && start_tag_optional(element, children.first(), previous))
+        {
+            self.start_tag(element);
+        }
+        if void {
+            return false;
+        }
+
+        self.siblings(&children, Some(child.node), preserve);
+
+        if omit && end_tag_optional(element, next, parent) {
+            return true;
+        }
+        self.output.push_str("</");
+        self.output.push_str(&element.name.local);
+        self.output.push('>');
+        false
+    }
+
+    fn start_tag(&mut self, element: &Element) {
+        self.output.push('<');
+        self.output.push_str(&element.name.local);
+        for (name, value) in element.attrs.iter() {
+            self.output.push(' ');
+            match name.ns {
+                ns!() => (),
+                ns!(xml) => self.output.push_str("xml:"),
+                ns!(xmlns) => {
+                    if name.local != local_name!("xmlns") {
+                        self.output.push_str("xmlns:");
+                    }
+                }
+                ns!(xlink) => self.output.push_str("xlink:"),
+                _ => self.output.push_str("unknown_namespace:"),
+            }
+            self.output.push_str(&name.local);
+
+            if !self.options.minimal_attribute_quotes {
+                self.output.push_str("=\"");
+                self.escape(value, true);
+                self.output.push('"');
+            } else if value.is_empty() {
+                // The attribute is written as its name alone.
+            } else if !value.contains(|c: char| {
+                c.is_ascii_whitespace() || matches!(c, '"' | '\'' | '=' | '<' | '>' | '`')
+            }) {
+                self.output.push('=');
+                self.escape(value, false);
+            } else if value.contains('"') && !value.contains('\'') {
+                self.output.push_str("='");
+                self.escape(value, false);
+                self.output.push('\'');
+            } else {
+                self.output.push_str("=\"");
+                self.escape(value, true);
+                self.output.push('"');
+            }
+        }
+        self.output.push('>');
+    }
+
+    fn is_raw_text(&self, parent: NodeRef<Node>) -> bool {
+        let Some(element) = parent.value().as_element() else {
+            return false;
+        };
+        if element.name.ns != ns!(html) {
+            return false;
+        }
+        match element.name.local {
+            local_name!("style")
+            | local_name!("script")
+            | local_name!("xmp")
+            | local_name!("iframe")
+            | local_name!("noembed")
+            | local_name!("noframes")
+            | local_name!("plaintext") => true,
+            local_name!("noscript") => self.options.scripting_enabled,
+            _ => false,
+        }
+    }
+
+    fn escape(&mut self, text: &str, attr_mode: bool) {
+        let named = self.options.character_references == CharacterReferences::Named;
+        for c in text.chars() {
+            let reference = match c {
+                '&' if named => "&amp;",
+                '\u{00A0}' if named => "&nbsp;",
+                '"' if attr_mode && named => "&quot;",
+                '<' if named => "&lt;",
+                '>' if named => "&gt;",
+                '&' | '\u{00A0}' | '<' | '>' => "",
+                '"' if attr_mode => "",
+                c if self.options.escape_non_ascii && !c.is_ascii() => "",
+                c => {
+                    self.output.push(c);
+                    continue;
+                }
+            };
+            if reference.is_empty() {
+                write!(self.output, "&#{};", u32::from(c)).unwrap();
+            } else {
+                self.output.push_str(reference);
+            }
+        }
+    }
+}
+
+/// Returns the children of a node, or the contents of a template.
+fn child_nodes(node: NodeRef<Node>) -> Vec<NodeRef<Node>> {
+    let contents = ElementRef::wrap(node).and_then(|element| element.template_contents());
+    contents.unwrap_or(node).children().collect()
+}
+
+/// Collapses runs of whitespace to a single space, and removes whitespace at the start and end
+/// of the text if it is next to a block.
+fn collapse(text: &str, start: bool, end: bool) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && !(start && collapsed.is_empty()) {
+            collapsed.push(' ');
+        }
+        space = false;
+        collapsed.push(c);
+    }
+    if space && !end && !(start && collapsed.is_empty()) {
+        collapsed.push(' ');
+    }
+    collapsed
+}
+
+/// Returns true for nodes that whitespace next to is not rendered.
+fn is_block(node: NodeRef<Node>) -> bool {
+    match node.value() {
+        Node::Document | Node::Fragment | Node::Doctype(_) => true,
+        Node::Element(element) => kind(&element.name) == Kind::Block,
+        _ => false,
+    }
+}
+
+/// How an element is laid out when whitespace is collapsed or the HTML is pretty-printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// On lines of its own, with its children laid out in turn.
+    Block,
+
+    /// Written as it is, on lines of its own unless it is next to inline content without
+    /// whitespace in between.
+    Verbatim,
+
+    /// Within inline content.
+    Inline,
+}
+
+pub(crate) fn kind(name: &QualName) -> Kind {
+    if name.ns != ns!(html) {
+        return Kind::Verbatim;
+    }
+    match name.local {
+        local_name!("pre")
+        | local_name!("textarea")
+        | local_name!("script")
+        | local_name!("style")
+        | local_name!("template")
+        | local_name!("noscript")
+        | local_name!("xmp")
+        | local_name!("iframe")
+        | local_name!("noembed")
+        | local_name!("noframes")
+        | local_name!("plaintext")
+        | local_name!("listing")
+        | local_name!("meta")
+        | local_name!("link")
+        | local_name!("base") => Kind::Verbatim,
+
+        local_name!("html")
+        | local_name!("head")
+        | local_name!("title")
+        | local_name!("body")
+        | local_name!("address")
+        | local_name!("article")
+        | local_name!("aside")
+        | local_name!("blockquote")
+        | local_name!("center")
+        | local_name!("details")
+        | local_name!("dialog")
+        | local_name!("dir")
+        | local_name!("div")
+        | local_name!("dl")
+        | local_name!("dt")
+        | local_name!("dd")
+        | local_name!("fieldset")
+        | local_name!("figcaption")
+        | local_name!("figure")
+        | local_name!("footer")
+        | local_name!("form")
+        | local_name!("frameset")
+        | local_name!("h1")
+        | local_name!("h2")
+        | local_name!("h3")
+        | local_name!("h4")
+        | local_name!("h5")
+        | local_name!("h6")
+        | local_name!("header")
+        | local_name!("hgroup")
+        | local_name!("hr")
+        | local_name!("legend")
+        | local_name!("li")
+        | local_name!("main")
+        | local_name!("menu")
+        | local_name!("nav")
+        | local_name!("ol")
+        | local_name!("optgroup")
+        | local_name!("option")
+        | local_name!("p")
+        | local_name!("search")
+        | local_name!("section")
+        | local_name!("summary")
+        | local_name!("table")
+        | local_name!("caption")
+        | local_name!("colgroup")
+        | local_name!("col")
+        | local_name!("thead")
+        | local_name!("tbody")
+        | local_name!("tfoot")
+        | local_name!("tr")
+        | local_name!("td")
+        | local_name!("th")
+        | local_name!("ul") => Kind::Block,
+
+        _ => Kind::Inline,
+    }
+}
+
+/// Returns true for elements whose whitespace is rendered or is not text.
+fn preserves_whitespace_in(node: NodeRef<Node>) -> bool {
+    node.value().as_element().is_some_and(|element| {
+        element.name.ns != ns!(html)
+            || matches!(
+                element.name.local,
+                local_name!("pre")
+                    | local_name!("textarea")
+                    | local_name!("listing")
+                    | local_name!("plaintext")
+                    | local_name!("xmp")
+                    | local_name!("script")
+                    | local_name!("style")
+                    | local_name!("iframe")
+                    | local_name!("noembed")
+                    | local_name!("noframes")
+                    | local_name!("noscript")
+            )
+    })
+}
+
+fn is_void(name: &QualName) -> bool {
+    name.ns == ns!(html)
+        && matches!(
+            name.local,
+            local_name!("area")
+                | local_name!("base")
+                | local_name!("basefont")
+                | local_name!("bgsound")
+                | local_name!("br")
+                | local_name!("col")
+                | local_name!("embed")
+                | local_name!("frame")
+                | local_name!("hr")
+                | local_name!("img")
+                | local_name!("input")
+                | local_name!("keygen")
+                | local_name!("link")
+                | local_name!("meta")
+                | local_name!("param")
+                | local_name!("source")
+                | local_name!("track")
+                | local_name!("wbr")
+        )
+}
+
+/// Returns true if the start tag of an element can be omitted, given its first child and its
+/// previous sibling, together with whether the end tag of that sibling was omitted.
+fn start_tag_optional(
+    element: &Element,
+    first: Option<&Child>,
+    previous: Option<(&Child, bool)>,
+) -> bool {
+    let preceded_by = |names: &[LocalName]| {
+        previous.is_some_and(|(previous, end_omitted)| end_omitted && previous.is(names))
+    };
+    match element.name.local {
+        local_name!("html") => !first.is_some_and(Child::is_comment),
+        local_name!("head") => first.is_none_or(|first| first.element().is_some()),
+        local_name!("body") => first.is_none_or(|first| {
+            !first.starts_with_whitespace()
+                && !first.is_comment()
+                && !first.is(&[
+                    local_name!("meta"),
+                    local_name!("noscript"),
+                    local_name!("link"),
+                    local_name!("script"),
+                    local_name!("style"),
+                    local_name!("template"),
+                ])
+        }),
+        local_name!("colgroup") => {
+            first.is_some_and(|first| first.is(&[local_name!("col")]))
+                && !preceded_by(&[local_name!("colgroup")])
+        }
+        local_name!("tbody") => {
+            first.is_some_and(|first| first.is(&[local_name!("tr")]))
+                && !preceded_by(&[
+                    local_name!("tbody"),
+                    local_name!("thead"),
+                    local_name!("tfoot"),
+                ])
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the end tag of an element can be omitted, given its next sibling and parent.
+fn end_tag_optional(
+    element: &Element,
+    next: Option<&Child>,
+    parent: Option<NodeRef<Node>>,
+) -> bool {
+    let followed_by = |names: &[LocalName]| next.is_some_and(|next| next.is(names));
+    let last = next.is_none();
+    match element.name.local {
+        local_name!("html") | local_name!("body") => !next.is_some_and(Child::is_comment),
+        local_name!("head") | local_name!("colgroup") | local_name!("caption") => {
+            !next.is_some_and(|next| next.is_comment() || next.starts_with_whitespace())
+        }
+        local_name!("li") => last || followed_by(&[local_name!("li")]),
+        local_name!("dt") => followed_by(&[local_name!("dt"), local_name!("dd")]),
+        local_name!("dd") => last || followed_by(&[local_name!("dt"), local_name!("dd")]),
+        local_name!("rt") | local_name!("rp") => {
+            last || followed_by(&[local_name!("rt"), local_name!("rp")])
+        }
+        local_name!("optgroup") => {
+            last || followed_by(&[local_name!("optgroup"), local_name!("hr")])
+        }
+        local_name!("option") => {
+            last || followed_by(&[
+                local_name!("option"),
+                local_name!("optgroup"),
+                local_name!("hr"),
+            ])
+        }
+        local_name!("thead") => followed_by(&[local_name!("tbody"), local_name!("tfoot")]),
+        local_name!("tbody") => last || followed_by(&[local_name!("tbody"), local_name!("tfoot")]),
+        local_name!("tfoot") => last,
+        local_name!("tr") => last || followed_by(&[local_name!("tr")]),
+        local_name!("td") | local_name!("th") => {
+            last || followed_by(&[local_name!("td"), local_name!("th")])
+        }
+        local_name!("p") => {
+            if last {
+                !parent
+                    .and_then(|parent| parent.value().as_element())
+                    .is_some_and(|parent| {
+                        parent.name.local.contains('-')
+                            || matches!(
+                                parent.name.local,
+                                local_name!("a")
+                                    | local_name!("audio")
+                                    | local_name!("del")
+                                    | local_name!("ins")
+                                    | local_name!("map")
+                                    | local_name!("noscript")
+                                    | local_name!("video")
+                            )
+                    })
+            } else {
+                followed_by(&[
+                    local_name!("address"),
+                    local_name!("article"),
+                    local_name!("aside"),
+                    local_name!("blockquote"),
+                    local_name!("details"),
+                    local_name!("dialog"),
+                    local_name!("div"),
+                    local_name!("dl"),
+                    local_name!("fieldset"),
+                    local_name!("figcaption"),
+                    local_name!("figure"),
+                    local_name!("footer"),
+                    local_name!("form"),
+                    local_name!("h1"),
+                    local_name!("h2"),
+                    local_name!("h3"),
+                    local_name!("h4"),
+                    local_name!("h5"),
+                    local_name!("h6"),
+                    local_name!("header"),
+                    local_name!("hgroup"),
+                    local_name!("hr"),
+                    local_name!("main"),
+                    local_name!("menu"),
+                    local_name!("nav"),
+                    local_name!("ol"),
+                    local_name!("p"),
+                    local_name!("pre"),
+                    local_name!("search"),
+                    local_name!("section"),
+                    local_name!("table"),
+                    local_name!("ul"),
+                ])
+            }
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::{CharacterReferences, SerializeOptions};
+    use crate::{Html, Selector};
+
+    const DOCUMENT: &str = r#"<!DOCTYPE html>
+<html lang="en"><head><title>A &amp; B</title>
+<style>p > b { color: red }</style></head>
+<body>
+  <!-- comment -->
+  <p id="x" class="a b" data-empty="">One&nbsp;two &lt;three&gt;
+     <b>bold</b>  <i>it</i></p>
+  <p title='say "hi"'>Café</p>
+  <ul><li>a</li> <li>b</li></ul>
+  <table><colgroup><col></colgroup><tbody><tr><td>1</td><td>2</td></tr></tbody></table>
+  <pre>  keep   this  </pre>
+  <noscript><b>no</b></noscript>
+  <template><p>inert   text</p></template>
+  <svg><text>  x  </text></svg>
+  <select><option>o1</option><option>o2</option></select>
+  <dl><dt>t</dt><dd>d</dd></dl>
+</body></html>"#;
+
+    fn minified() -> SerializeOptions {
+        SerializeOptions::default()
+            .omit_optional_tags(true)
+            .minimal_attribute_quotes(true)
+            .collapse_whitespace(true)
+            .remove_comments(true)
+    }
+
+    #[test]
+    fn default_matches_html() {
+        let html = Html::parse_document(DOCUMENT);
+        assert_eq!(html.html_with(SerializeOptions::default()), html.html());
+
+        let fragment = Html::parse_fragment("<p>a<br>b</p><?pi data?><math><mi>x</mi></math>");
+        assert_eq!(
+            fragment.html_with(SerializeOptions::default()),
+            fragment.html()
+        );
+
+        let selector = Selector::parse("p, template, ul").unwrap();
+        for element in html.select(&selector) {
+            assert_eq!(
+                element.html_with(SerializeOptions::default()),
+                element.html()
+            );
+            assert_eq!(
+                element.inner_html_with(SerializeOptions::default()),
+                element.inner_html()
+            );
+        }
+    }
+
+    #[test]
+    fn minified_reparses_identically() {
+        let html = Html::parse_document(DOCUMENT);
+        let minified = html.html_with(minified());
+        let expected = Html::parse_document(
+            &html.html_with(
+                SerializeOptions::default()
+                    .collapse_whitespace(true)
+                    .remove_comments(true),
+            ),
+        );
+        assert_eq!(Html::parse_document(&minified).html(), expected.html());
+    }
+
+    #[test]
+    fn omit_optional_tags() {
+        let options = SerializeOptions::default().omit_optional_tags(true);
+        let html = Html::parse_document("<html><head></head><body><p>a</p><p>b</p></body></html>");
+        assert_eq!(html.html_with(options), "<p>a<p>b");
+
+        let html = Html::parse_document(r#"<html lang="en"><body><!--c--><p>a</p></body></html>"#);
+        assert_eq!(
+            html.html_with(options),
+            r#"<html lang="en"><body><!--c--><p>a"#
+        );
+
+        let html = Html::parse_fragment("<a><p>a</p></a><div><p>b</p><span>c</span></div>");
+        assert_eq!(
+            html.root_element().inner_html_with(options),
+            "<a><p>a</p></a><div><p>b</p><span>c</span></div>"
+        );
+
+        let html = Html::parse_fragment(
+            "<table><thead><tr><th>h</th></tr></thead><tbody><tr><td>1</td></tr></tbody>\
+             <tbody><tr><td>2</td></tr></tbody></table>",
+        );
+        assert_eq!(
+            html.root_element().inner_html_with(options),
+            "<table><thead><tr><th>h<tbody><tr><td>1<tbody><tr><td>2</table>"
+        );
+    }
+
+    #[test]
+    fn minimal_attribute_quotes() {
+        let options = SerializeOptions::default().minimal_attribute_quotes(true);
+        let html = Html::parse_fragment(
+            r#"<input a="x" b="" c="two words" d='say "hi"' e="it's &quot;q&quot;" f="a&amp;b">"#,
+        );
+        assert_eq!(
+            html.root_element().inner_html_with(options),
+            r#"<input a=x b c="two words" d='say "hi"' e="it's &quot;q&quot;" f=a&amp;b>"#
+        );
+    }
+
+    #[test]
+    fn collapse_whitespace() {
+        let options = SerializeOptions::default().collapse_whitespace(true);
+        let html = Html::parse_fragment(
+            "<div>\n  <p>  a \n <b> b </b>  c  </p>\n  <pre> x  y </pre>\n</div> <span> s </span>",
+        );
+        assert_eq!(
+            html.root_element().inner_html_with(options),
+            "<div><p>a <b> b </b> c</p><pre> x  y </pre></div><span> s </span>"
+        );
+    }
+
+    #[test]
+    fn remove_comments() {
+        let options = SerializeOptions::default().remove_comments(true);
+        let html = Html::parse_fragment("a<!-- b -->c<p><!---->d</p>");
+        assert_eq!(html.root_element().inner_html_with(options), "ac<p>d</p>");
+    }
+
+    #[test]
+    fn character_references() {
+        let options =
+            SerializeOptions::default().character_references(CharacterReferences::Numeric);
+        let html = Html::parse_fragment(r#"<p title="&quot;&amp;">&lt;&amp;&gt;&nbsp;é</p>"#);
+        assert_eq!(
+            html.root_element().inner_html_with(options),
+            r#"<p title="&#34;&#38;">&#60;&#38;&#62;&#160;é</p>"#
+        );
+
+        let options = SerializeOptions::default().escape_non_ascii(true);
+        assert_eq!(
+            html.root_element().inner_html_with(options),
+            r#"<p title="&quot;&amp;">&lt;&amp;&gt;&nbsp;&#233;</p>"#
+        );
+    }
+
+    #[test]
+    fn scripting_enabled() {
+        let html = Html::parse_fragment("<noscript><b>&amp;</b></noscript><script>a < b</script>");
+        let root = html.root_element();
+        assert_eq!(
+            root.inner_html_with(SerializeOptions::default()),
+            "<noscript>&lt;b&gt;&amp;amp;&lt;/b&gt;</noscript><script>a < b</script>"
+        );
+        let options = SerializeOptions::default().scripting_enabled(true);
+        assert_eq!(
+            root.inner_html_with(options),
+            "<noscript><b>&amp;</b></noscript><script>a < b</script>"
+        );
+    }
+}
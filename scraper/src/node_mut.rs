@@ -0,0 +1,1001 @@
+//! Mutable node handles.
+
+use std::fmt;
+use std::iter;
+use std::mem;
+
+use ego_tree::iter::Edge;
+use ego_tree::{NodeId, NodeRef, Tree};
+
+use html5ever::QualName;
+
+use crate::element_ref::ElementRef;
+use crate::error::HtmlContextError;
+use crate::html::body;
+use crate::node::Element;
+use crate::tendril_util::{self, NodeStr};
+use crate::{Html, Node};
+
+/// A mutable handle to a node of an [`Html`](crate::Html) tree.
+///
+/// Edits made through this handle keep the tree in the shape the parser produces: adjacent text
+/// nodes are merged into one, and children added to a `<template>` element go into its template
+/// contents.
+///
+/// Nodes that are removed stay in the tree's arena, detached from the root. They are no longer
+/// selected or serialized, but can be inserted again by their ID.
+///
+/// # Example
+///
+/// ```
+/// use scraper::{Html, Node, Selector};
+///
+/// let mut html = Html::parse_fragment("<ul><li>one</li><li>two</li></ul>");
+/// let items: Vec<_> = html
+///     .select(&Selector::parse("li").unwrap())
+///     .map(|li| li.id())
+///     .collect();
+///
+/// let mut first = html.get_mut(items[0]).unwrap();
+/// first.insert_before(Node::new_comment(" list "));
+/// html.get_mut(items[1]).unwrap().remove();
+///
+/// assert_eq!(
+///     html.root_element().inner_html(),
+///     "<ul><!-- list --><li>one</li></ul>"
+/// );
+/// ```
+pub struct NodeMut<'a> {
+    node: ego_tree::NodeMut<'a, Node>,
+}
+
+/// Where [`NodeMut::insert_adjacent_html`] inserts nodes, named as in the DOM's
+/// `insertAdjacentHTML`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdjacentPosition {
+    /// Before the element.
+    BeforeBegin,
+    /// Before the first child of the element.
+    AfterBegin,
+    /// After the last child of the element.
+    BeforeEnd,
+    /// After the element.
+    AfterEnd,
+}
+
+/// Where [`NodeMut::place`] puts a node relative to the handle.
+#[derive(Debug, Clone, Copy)]
+enum Position {
+    Append,
+    Prepend,
+    Before,
+    After,
+}
+
+impl<'a> NodeMut<'a> {
+    pub(crate) fn new(node: ego_tree::NodeMut<'a, Node>) -> Self {
+        NodeMut { node }
+    }
+
+    fn at(&mut self, id: NodeId) -> NodeMut<'_> {
+        NodeMut::new(self.node.tree().get_mut(id).unwrap())
+    }
+
+    /// Returns the ID of this node.
+    pub fn id(&self) -> NodeId {
+        self.node.id()
+    }
+
+    /// Returns the value of this node.
+    pub fn value(&mut self) -> &Node {
+        self.node.value()
+    }
+
+    /// Returns a shared reference to this node.
+    pub fn as_ref(&mut self) -> NodeRef<'_, Node> {
+        self.node.as_ref()
+    }
+
+    /// Returns this node as an element reference, or `None` if it is not an element.
+    pub fn as_element(&mut self) -> Option<ElementRef<'_>> {
+        ElementRef::wrap(self.node.as_ref())
+    }
+
+    /// Returns the element of this node for editing, or `None` if it is not an element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment(r#"<p class="a">text</p>"#);
+    /// let p = html.select(&Selector::parse("p").unwrap()).next().unwrap().id();
+    ///
+    /// let mut p = html.get_mut(p).unwrap();
+    /// let element = p.as_element_mut().unwrap();
+    /// element.toggle_class("a").unwrap();
+    /// element.set_attr("title", "x");
+    /// assert_eq!(html.root_element().inner_html(), r#"<p class="" title="x">text</p>"#);
+    /// ```
+    pub fn as_element_mut(&mut self) -> Option<&mut Element> {
+        match self.node.value() {
+            Node::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Returns the parent of this node.
+    pub fn parent(&mut self) -> Option<NodeMut<'_>> {
+        self.node.parent().map(NodeMut::new)
+    }
+
+    /// Returns the parent of this node, or this node itself if it has no parent.
+    pub fn into_parent(self) -> Result<Self, Self> {
+        self.node
+            .into_parent()
+            .map(NodeMut::new)
+            .map_err(NodeMut::new)
+    }
+
+    /// Returns the previous sibling of this node.
+    pub fn prev_sibling(&mut self) -> Option<NodeMut<'_>> {
+        self.node.prev_sibling().map(NodeMut::new)
+    }
+
+    /// Returns the next sibling of this node.
+    pub fn next_sibling(&mut self) -> Option<NodeMut<'_>> {
+        self.node.next_sibling().map(NodeMut::new)
+    }
+
+    /// Returns the first child of this node.
+    pub fn first_child(&mut self) -> Option<NodeMut<'_>> {
+        self.node.first_child().map(NodeMut::new)
+    }
+
+    /// Returns the last child of this node.
+    pub fn last_child(&mut self) -> Option<NodeMut<'_>> {
+        self.node.last_child().map(NodeMut::new)
+    }
+
+    /// Appends a new child to this node and returns it.
+    ///
+    /// If the new node is text that ends up next to other text, the two are merged and the merged
+    /// node is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node cannot have children, or if `value` is a document or fragment root.
+    pub fn append(&mut self, value: Node) -> NodeMut<'_> {
+        let id = self.node.tree().orphan(value).id();
+        self.append_id(id)
+    }
+
+    /// Prepends a new child to this node and returns it.
+    ///
+    /// Text is merged as with [`NodeMut::append`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node cannot have children, or if `value` is a document or fragment root.
+    pub fn prepend(&mut self, value: Node) -> NodeMut<'_> {
+        let id = self.node.tree().orphan(value).id();
+        self.prepend_id(id)
+    }
+
+    /// Inserts a new sibling before this node and returns it.
+    ///
+    /// Text is merged as with [`NodeMut::append`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node has no parent, or if `value` is a document or fragment root.
+    pub fn insert_before(&mut self, value: Node) -> NodeMut<'_> {
+        let id = self.node.tree().orphan(value).id();
+        self.insert_id_before(id)
+    }
+
+    /// Inserts a new sibling after this node and returns it.
+    ///
+    /// Text is merged as with [`NodeMut::append`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node has no parent, or if `value` is a document or fragment root.
+    pub fn insert_after(&mut self, value: Node) -> NodeMut<'_> {
+        let id = self.node.tree().orphan(value).id();
+        self.insert_id_after(id)
+    }
+
+    /// Moves the node with the given ID, and its descendants, to the end of this node's children.
+    ///
+    /// Text is merged as with [`NodeMut::append`], both where the node is moved to and where it
+    /// was moved from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is invalid, if this node cannot have children, or if the node would become
+    /// its own descendant.
+    pub fn append_id(&mut self, id: NodeId) -> NodeMut<'_> {
+        let id = self.place(id, Position::Append);
+        self.at(id)
+    }
+
+    /// Moves the node with the given ID to the start of this node's children.
+    ///
+    /// # Panics
+    ///
+    /// Panics as [`NodeMut::append_id`] does.
+    pub fn prepend_id(&mut self, id: NodeId) -> NodeMut<'_> {
+        let id = self.place(id, Position::Prepend);
+        self.at(id)
+    }
+
+    /// Moves the node with the given ID to just before this node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is invalid or is this node, if this node has no parent, or if the node
+    /// would become its own descendant.
+    pub fn insert_id_before(&mut self, id: NodeId) -> NodeMut<'_> {
+        let id = self.place(id, Position::Before);
+        self.at(id)
+    }
+
+    /// Moves the node with the given ID to just after this node.
+    ///
+    /// # Panics
+    ///
+    /// Panics as [`NodeMut::insert_id_before`] does.
+    pub fn insert_id_after(&mut self, id: NodeId) -> NodeMut<'_> {
+        let id = self.place(id, Position::After);
+        self.at(id)
+    }
+
+    /// Detaches this node, and its descendants, from the tree.
+    ///
+    /// The text on either side of the node is merged if both are text.
+    pub fn remove(mut self) {
+        let prev = self.node.as_ref().prev_sibling().map(|node| node.id());
+        self.node.detach();
+        if let Some(prev) = prev {
+            merge_text(self.node.tree(), prev, &[]);
+        }
+    }
+
+    /// Replaces this node, and its descendants, with a new node and returns the new node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node has no parent, or if `value` is a document or fragment root.
+    pub fn replace(mut self, value: Node) -> NodeMut<'a> {
+        let parent = self
+            .node
+            .as_ref()
+            .parent()
+            .expect("Cannot replace a node without a parent")
+            .id();
+        let old = self.id();
+        let new = self.node.tree().orphan(value).id();
+        check_insertion(self.node.tree(), parent, new);
+        self.node.insert_id_before(new);
+
+        let Ok(mut node) = self.node.into_prev_sibling() else {
+            unreachable!()
+        };
+        node.tree().get_mut(old).unwrap().detach();
+        merge_text(node.tree(), new, &[new]);
+        NodeMut::new(node)
+    }
+
+    /// Wraps this node in a new element and returns the element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::{Html, Node};
+    ///
+    /// let mut html = Html::parse_fragment("<p>text</p>");
+    /// let p = html.root_element().first_child().unwrap().id();
+    ///
+    /// html.get_mut(p).unwrap().wrap(Node::new_element("div"));
+    /// assert_eq!(html.root_element().inner_html(), "<div><p>text</p></div>");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node has no parent, or if `value` is not an element.
+    pub fn wrap(mut self, value: Node) -> NodeMut<'a> {
+        assert!(value.is_element(), "Only elements can wrap other nodes");
+        assert!(
+            self.node.as_ref().parent().is_some(),
+            "Cannot wrap a node without a parent"
+        );
+
+        let id = self.id();
+        let wrapper = self.node.tree().orphan(value).id();
+        self.node.insert_id_before(wrapper);
+        self.node.tree().get_mut(wrapper).unwrap().append_id(id);
+
+        let Ok(wrapper) = self.node.into_parent() else {
+            unreachable!()
+        };
+        NodeMut::new(wrapper)
+    }
+
+    /// Replaces this node with its children.
+    ///
+    /// For a `<template>` element, the children of its template contents take its place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this node has no parent.
+    pub fn unwrap(mut self) {
+        let id = self.id();
+        let node = self.node.as_ref();
+        assert!(
+            node.parent().is_some(),
+            "Cannot unwrap a node without a parent"
+        );
+        let prev = node.prev_sibling().map(|node| node.id());
+        let tree = self.node.tree();
+        let children = tree
+            .get(container(tree, id))
+            .unwrap()
+            .children()
+            .map(|child| child.id())
+            .collect::<Vec<_>>();
+
+        for &child in &children {
+            self.node.insert_id_before(child);
+        }
+        self.node.detach();
+
+        for id in prev.into_iter().chain(children.last().copied()) {
+            merge_text(self.node.tree(), id, &[]);
+        }
+    }
+
+    /// Copies this node and its descendants, returning the detached copy.
+    ///
+    /// The copy can be inserted with [`NodeMut::append_id`] and friends.
+    pub fn clone_subtree(&mut self) -> NodeMut<'_> {
+        NodeMut::new(self.node.clone_subtree())
+    }
+
+    /// Replaces the children of this element with the nodes parsed from `html`, as the DOM's
+    /// `innerHTML` setter does.
+    ///
+    /// The HTML is parsed as a fragment in the context of this element, so e.g. `<tr>` is kept
+    /// when setting the content of a `<tbody>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment("<table><tbody></tbody></table>");
+    /// let tbody = html.select(&Selector::parse("tbody").unwrap()).next().unwrap().id();
+    ///
+    /// html.get_mut(tbody).unwrap().set_inner_html("<tr><td>1</td></tr>").unwrap();
+    /// assert_eq!(
+    ///     html.root_element().inner_html(),
+    ///     "<table><tbody><tr><td>1</td></tr></tbody></table>"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the tree unchanged, if this node is not an element.
+    pub fn set_inner_html(&mut self, html: &str) -> Result<(), HtmlContextError> {
+        let context = self.element_name()?;
+        let nodes = self.parse_in(context, html);
+
+        let id = self.id();
+        let container = container(self.node.tree(), id);
+        let children = self
+            .node
+            .tree()
+            .get(container)
+            .unwrap()
+            .children()
+            .map(|child| child.id())
+            .collect::<Vec<_>>();
+        for child in children {
+            self.node.tree().get_mut(child).unwrap().detach();
+        }
+
+        for node in nodes {
+            self.place(node, Position::Append);
+        }
+        Ok(())
+    }
+
+    /// Replaces this element with the nodes parsed from `html`, as the DOM's `outerHTML` setter
+    /// does.
+    ///
+    /// The HTML is parsed as a fragment in the context of the parent element, or of `<body>` if
+    /// the parent is a fragment root.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the tree unchanged, if this node is not an element or its parent
+    /// is not an element or a fragment root.
+    pub fn set_outer_html(mut self, html: &str) -> Result<(), HtmlContextError> {
+        let context = self.parent_context()?;
+        let nodes = self.parse_in(context, html);
+        for node in nodes {
+            self.place(node, Position::Before);
+        }
+        self.remove();
+        Ok(())
+    }
+
+    /// Inserts the nodes parsed from `html` relative to this element, as the DOM's
+    /// `insertAdjacentHTML` does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    /// use scraper::node_mut::AdjacentPosition;
+    ///
+    /// let mut html = Html::parse_fragment("<p>text</p>");
+    /// let p = html.select(&Selector::parse("p").unwrap()).next().unwrap().id();
+    ///
+    /// let mut p = html.get_mut(p).unwrap();
+    /// p.insert_adjacent_html(AdjacentPosition::BeforeBegin, "<hr>").unwrap();
+    /// p.insert_adjacent_html(AdjacentPosition::AfterBegin, "<b>bold</b> ").unwrap();
+    /// p.insert_adjacent_html(AdjacentPosition::BeforeEnd, " more").unwrap();
+    /// assert_eq!(
+    ///     html.root_element().inner_html(),
+    ///     "<hr><p><b>bold</b> text more</p>"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the tree unchanged, if this node is not an element, or if
+    /// inserting before or after it when its parent is not an element or a fragment root.
+    pub fn insert_adjacent_html(
+        &mut self,
+        position: AdjacentPosition,
+        html: &str,
+    ) -> Result<(), HtmlContextError> {
+        let context = match position {
+            AdjacentPosition::BeforeBegin | AdjacentPosition::AfterEnd => self.parent_context()?,
+            AdjacentPosition::AfterBegin | AdjacentPosition::BeforeEnd => self.element_name()?,
+        };
+        let nodes = self.parse_in(context, html);
+
+        match position {
+            AdjacentPosition::BeforeBegin => nodes.into_iter().for_each(|node| {
+                self.place(node, Position::Before);
+            }),
+            AdjacentPosition::AfterBegin => nodes.into_iter().rev().for_each(|node| {
+                self.place(node, Position::Prepend);
+            }),
+            AdjacentPosition::BeforeEnd => nodes.into_iter().for_each(|node| {
+                self.place(node, Position::Append);
+            }),
+            AdjacentPosition::AfterEnd => nodes.into_iter().rev().for_each(|node| {
+                self.place(node, Position::After);
+            }),
+        }
+        Ok(())
+    }
+
+    fn element_name(&mut self) -> Result<QualName, HtmlContextError> {
+        if !self.node.value().is_element() {
+            return Err(HtmlContextError::NotAnElement);
+        }
+        context(self.node.as_ref()).ok_or(HtmlContextError::NotAnElement)
+    }
+
+    fn parent_context(&mut self) -> Result<QualName, HtmlContextError> {
+        self.element_name()?;
+        self.node
+            .as_ref()
+            .parent()
+            .and_then(context)
+            .ok_or(HtmlContextError::NoParentElement)
+    }
+
+    /// Parses `html` in the context of an element named `context`, moves the resulting nodes into
+    /// this tree and returns the IDs of the top-level ones in order. The nodes are detached until
+    /// they are placed.
+    ///
+    /// Only the parsed nodes are allocated in this tree, not the root and `<html>` element that
+    /// wrap them in the parsed fragment.
+    fn parse_in(&mut self, context: QualName, html: &str) -> Vec<NodeId> {
+        let mut fragment = Html::parse_fragment_in(html, context);
+
+        // Locations in `html` would be confused with locations in the original input.
+        #[cfg(feature = "spans")]
+        for node in fragment.tree.values_mut() {
+            if let Node::Element(element) = node {
+                element.source_span = None;
+            }
+        }
+        let Some(wrapper) = fragment.tree.root().first_child().map(|html| html.id()) else {
+            return Vec::new();
+        };
+
+        // The parsed nodes in tree order, with the index of their parent in this list.
+        let mut order: Vec<(NodeId, Option<usize>)> = Vec::new();
+        let mut parents = Vec::new();
+        for edge in fragment.tree.get(wrapper).unwrap().traverse() {
+            match edge {
+                Edge::Open(node) if node.id() != wrapper => {
+                    order.push((node.id(), parents.last().copied()));
+                    parents.push(order.len() - 1);
+                }
+                Edge::Close(node) if node.id() != wrapper => {
+                    parents.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let tree = self.node.tree();
+        let mut ids = Vec::with_capacity(order.len());
+        let mut top = Vec::new();
+        for (id, parent) in order {
+            let value = mem::replace(fragment.tree.get_mut(id).unwrap().value(), Node::Document);
+
+            let node = match parent {
+                Some(parent) => tree.get_mut(ids[parent]).unwrap().append(value).id(),
+                None => {
+                    let node = tree.orphan(value).id();
+                    top.push(node);
+                    node
+                }
+            };
+            ids.push(node);
+        }
+        top
+    }
+
+    fn place(&mut self, child: NodeId, position: Position) -> NodeId {
+        let id = self.id();
+        let (target, parent) = match position {
+            Position::Append | Position::Prepend => {
+                let container = container(self.node.tree(), id);
+                (container, container)
+            }
+            Position::Before | Position::After => {
+                let parent = self
+                    .node
+                    .as_ref()
+                    .parent()
+                    .expect("Cannot insert a sibling of a node without a parent");
+                (id, parent.id())
+            }
+        };
+        let tree = self.node.tree();
+        check_insertion(tree, parent, child);
+
+        let old_prev = tree
+            .get(child)
+            .unwrap()
+            .prev_sibling()
+            .map(|node| node.id());
+
+        let mut target = tree.get_mut(target).unwrap();
+        match position {
+            Position::Append => target.append_id(child),
+            Position::Prepend => target.prepend_id(child),
+            Position::Before => target.insert_id_before(child),
+            Position::After => target.insert_id_after(child),
+        };
+
+        let child = merge_text(tree, child, &[id]);
+        if let Some(old_prev) = old_prev {
+            merge_text(tree, old_prev, &[child, id]);
+        }
+        child
+    }
+}
+
+impl fmt::Debug for NodeMut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeMut").field("id", &self.id()).finish()
+    }
+}
+
+/// Returns the node that holds the children of `id`, which is the template contents for a
+/// `<template>` element.
+fn container(tree: &Tree<Node>, id: NodeId) -> NodeId {
+    let node = tree.get(id).unwrap();
+    ElementRef::wrap(node)
+        .and_then(|element| element.template_contents())
+        .map_or(id, |contents| contents.id())
+}
+
+/// Returns the name of the context element for parsing HTML into `node`, or `None` if `node` is
+/// not an element or a fragment.
+///
+/// The `<html>` element wrapping a parsed fragment stands for the fragment itself, whose context
+/// is `<body>`.
+fn context(node: NodeRef<'_, Node>) -> Option<QualName> {
+    match node.value() {
+        Node::Element(element) => match node.parent() {
+            Some(parent) if parent.value().is_fragment() && parent.parent().is_none() => {
+                Some(body())
+            }
+            _ => Some(element.name.clone()),
+        },
+        Node::Fragment => match node.parent() {
+            Some(template) => context(template),
+            None => Some(body()),
+        },
+        _ => None,
+    }
+}
+
+fn check_insertion(tree: &Tree<Node>, parent: NodeId, child: NodeId) {
+    let parent = tree.get(parent).unwrap();
+    assert!(
+        matches!(
+            parent.value(),
+            Node::Document | Node::Fragment | Node::Element(_)
+        ),
+        "Only documents, fragments and elements can have children"
+    );
+
+    let child = tree.get(child).expect("Invalid node ID");
+    assert!(
+        !matches!(child.value(), Node::Document | Node::Fragment),
+        "Cannot insert a document or fragment root"
+    );
+    assert!(
+        iter::successors(Some(parent), |node| node.parent()).all(|node| node != child),
+        "Cannot insert a node into itself or its descendants"
+    );
+}
+
+/// Merges the run of adjacent text nodes containing `id` into one node, and returns that node.
+///
+/// The first node of the run that is listed in `keep` survives, or the first node of the run if
+/// none is.
+fn merge_text(tree: &mut Tree<Node>, id: NodeId, keep: &[NodeId]) -> NodeId {
+    let node = tree.get(id).unwrap();
+    if !node.value().is_text() || node.parent().is_none() {
+        return id;
+    }
+
+    let is_text = |node: &NodeRef<'_, Node>| node.value().is_text();
+    let first = iter::successors(Some(node), |node| node.prev_sibling().filter(is_text))
+        .last()
+        .unwrap();
+    let run = iter::successors(Some(first), |node| node.next_sibling().filter(is_text))
+        .map(|node| node.id())
+        .collect::<Vec<_>>();
+    if run.len() == 1 {
+        return id;
+    }
+
+    let survivor = keep
+        .iter()
+        .copied()
+        .find(|id| run.contains(id))
+        .unwrap_or(run[0]);
+
+    let mut text = NodeStr::new();
+    for &id in &run {
+        if let Node::Text(t) = tree.get(id).unwrap().value() {
+            tendril_util::push(&mut text, &t.text);
+        }
+    }
+    for &id in &run {
+        if id != survivor {
+            tree.get_mut(id).unwrap().detach();
+        }
+    }
+    if let Node::Text(t) = tree.get_mut(survivor).unwrap().value() {
+        t.text = text;
+    }
+
+    survivor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdjacentPosition;
+    use crate::error::HtmlContextError;
+    use crate::{Html, Node, Selector};
+
+    fn find(html: &Html, selector: &str) -> ego_tree::NodeId {
+        html.select(&Selector::parse(selector).unwrap())
+            .next()
+            .unwrap()
+            .id()
+    }
+
+    fn inner(html: &Html) -> String {
+        html.root_element().inner_html()
+    }
+
+    #[test]
+    fn insert_in_every_position() {
+        let mut html = Html::parse_fragment("<div><p>x</p></div>");
+        let p = find(&html, "p");
+
+        let mut node = html.get_mut(p).unwrap();
+        node.append(Node::new_element("b"));
+        node.prepend(Node::new_element("i"));
+        node.insert_before(Node::new_element("h1"));
+        node.insert_after(Node::new_comment("c"));
+        assert_eq!(
+            inner(&html),
+            "<div><h1></h1><p><i></i>x<b></b></p><!--c--></div>"
+        );
+
+        let names = html
+            .select(&Selector::parse("*").unwrap())
+            .map(|element| element.value().name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["html", "div", "h1", "p", "i", "b"]);
+    }
+
+    #[test]
+    fn text_is_merged() {
+        let mut html = Html::parse_fragment("<p>a<b>b</b>c</p>");
+        let p = find(&html, "p");
+        let b = find(&html, "b");
+
+        let merged = html.get_mut(p).unwrap().append(Node::new_text("d")).id();
+        assert_eq!(inner(&html), "<p>a<b>b</b>cd</p>");
+        assert_eq!(
+            &**html.tree.get(merged).unwrap().value().as_text().unwrap(),
+            "cd"
+        );
+
+        html.get_mut(b).unwrap().remove();
+        let p = html.tree.get(p).unwrap();
+        assert_eq!(p.children().count(), 1);
+        assert_eq!(
+            &**p.first_child().unwrap().value().as_text().unwrap(),
+            "acd"
+        );
+        assert!(html.select(&Selector::parse("b").unwrap()).next().is_none());
+    }
+
+    #[test]
+    fn moving_merges_text_at_both_ends() {
+        let mut html = Html::parse_fragment("<p>a<b>b</b>c</p><i>d</i>");
+        let b = find(&html, "b");
+        let i = find(&html, "i");
+
+        html.get_mut(i).unwrap().append_id(b);
+        assert_eq!(inner(&html), "<p>ac</p><i>d<b>b</b></i>");
+        assert_eq!(
+            html.tree.get(find(&html, "p")).unwrap().children().count(),
+            1
+        );
+
+        let p = find(&html, "p");
+        html.get_mut(p).unwrap().prepend_id(i);
+        assert_eq!(inner(&html), "<p><i>d<b>b</b></i>ac</p>");
+    }
+
+    #[test]
+    fn replace_wrap_and_unwrap() {
+        let mut html = Html::parse_fragment("<p>a<b>b</b>c</p>");
+        let b = find(&html, "b");
+
+        let b = html.get_mut(b).unwrap().wrap(Node::new_element("em")).id();
+        assert_eq!(inner(&html), "<p>a<em><b>b</b></em>c</p>");
+
+        html.get_mut(b).unwrap().unwrap();
+        assert_eq!(inner(&html), "<p>a<b>b</b>c</p>");
+
+        let b = find(&html, "b");
+        html.get_mut(b).unwrap().unwrap();
+        assert_eq!(inner(&html), "<p>abc</p>");
+        assert_eq!(
+            html.tree.get(find(&html, "p")).unwrap().children().count(),
+            1
+        );
+
+        let p = find(&html, "p");
+        let text = html.get_mut(p).unwrap().replace(Node::new_text("new")).id();
+        assert_eq!(inner(&html), "new");
+        assert!(html.tree.get(text).unwrap().value().is_text());
+    }
+
+    #[test]
+    fn clone_and_reinsert() {
+        let mut html = Html::parse_fragment(r#"<p class="a" id="x">text <b>bold</b></p>"#);
+        let p = find(&html, "p");
+
+        let mut node = html.get_mut(p).unwrap();
+        let copy = node.clone_subtree().id();
+        node.insert_id_after(copy);
+        html.get_mut(p).unwrap().remove();
+        html.get_mut(copy).unwrap().insert_id_before(p);
+
+        let ps = html
+            .select(&Selector::parse("p.a#x").unwrap())
+            .map(|p| p.id())
+            .collect::<Vec<_>>();
+        assert_eq!(ps, [p, copy]);
+        assert_eq!(
+            html.root_element().inner_html(),
+            r#"<p class="a" id="x">text <b>bold</b></p>"#.repeat(2)
+        );
+    }
+
+    #[test]
+    fn template_children_go_into_contents() {
+        let mut html = Html::parse_fragment("<template><p>1</p></template>");
+        let template = find(&html, "template");
+
+        html.get_mut(template)
+            .unwrap()
+            .append(Node::new_element("b"));
+        assert_eq!(inner(&html), "<template><p>1</p><b></b></template>");
+
+        html.get_mut(template).unwrap().unwrap();
+        assert_eq!(inner(&html), "<p>1</p><b></b>");
+    }
+
+    #[test]
+    fn set_inner_and_outer_html() {
+        let mut html = Html::parse_fragment("<div><p>old</p></div><p>a<b>b</b>c</p>");
+        let div = find(&html, "div");
+
+        html.get_mut(div)
+            .unwrap()
+            .set_inner_html(r#"<span class="new">1</span>2"#)
+            .unwrap();
+        assert_eq!(
+            inner(&html),
+            r#"<div><span class="new">1</span>2</div><p>a<b>b</b>c</p>"#
+        );
+        let ids = html
+            .select(&Selector::parse("div .new").unwrap())
+            .map(|span| span.id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(html.select(&Selector::parse("p").unwrap()).count(), 1);
+
+        let b = find(&html, "b");
+        html.get_mut(b).unwrap().set_outer_html("B").unwrap();
+        assert_eq!(
+            inner(&html),
+            r#"<div><span class="new">1</span>2</div><p>aBc</p>"#
+        );
+        assert_eq!(
+            html.tree.get(find(&html, "p")).unwrap().children().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn html_is_parsed_in_context() {
+        let mut html =
+            Html::parse_fragment("<table><tr><td>1</td></tr></table><template></template>");
+        let tr = find(&html, "tr");
+        let template = find(&html, "template");
+
+        html.get_mut(tr)
+            .unwrap()
+            .insert_adjacent_html(AdjacentPosition::AfterEnd, "<tr><td>2</td></tr>")
+            .unwrap();
+        html.get_mut(tr)
+            .unwrap()
+            .insert_adjacent_html(AdjacentPosition::AfterBegin, "<td>0</td>")
+            .unwrap();
+        html.get_mut(template)
+            .unwrap()
+            .set_inner_html("<td>t</td>")
+            .unwrap();
+        assert_eq!(
+            inner(&html),
+            "<table><tbody><tr><td>0</td><td>1</td></tr><tr><td>2</td></tr></tbody></table>\
+             <template><td>t</td></template>"
+        );
+    }
+
+    #[test]
+    fn insert_adjacent_html_merges_text() {
+        let mut html = Html::parse_fragment("a<p>b</p>c");
+        let p = find(&html, "p");
+
+        let mut node = html.get_mut(p).unwrap();
+        node.insert_adjacent_html(AdjacentPosition::BeforeBegin, "1<i></i>2")
+            .unwrap();
+        node.insert_adjacent_html(AdjacentPosition::AfterEnd, "3")
+            .unwrap();
+        node.insert_adjacent_html(AdjacentPosition::AfterBegin, "4")
+            .unwrap();
+        node.insert_adjacent_html(AdjacentPosition::BeforeEnd, "5")
+            .unwrap();
+        assert_eq!(inner(&html), "a1<i></i>2<p>4b5</p>3c");
+
+        let root = html.root_element();
+        assert_eq!(root.children().count(), 5);
+        let text = root.first_child().unwrap().value().as_text().unwrap();
+        assert_eq!(&**text, "a1");
+    }
+
+    #[test]
+    fn setting_html_does_not_grow_the_tree() {
+        let mut html = Html::parse_fragment("<div></div>");
+        let div = find(&html, "div");
+
+        html.get_mut(div)
+            .unwrap()
+            .set_inner_html("<p>a</p><template><b>b</b></template>")
+            .unwrap();
+        let count = html.tree.nodes().count();
+        for _ in 0..10 {
+            html.get_mut(div)
+                .unwrap()
+                .set_inner_html("<p>a</p><template><b>b</b></template>")
+                .unwrap();
+        }
+        // Each edit allocates the same six nodes and nothing else.
+        assert_eq!(html.tree.nodes().count(), count + 10 * 6);
+        assert_eq!(
+            inner(&html),
+            "<div><p>a</p><template><b>b</b></template></div>"
+        );
+    }
+
+    #[test]
+    fn html_needs_an_element_context() {
+        let mut html = Html::parse_fragment("a<p>b</p>");
+        let text = html.root_element().first_child().unwrap().id();
+        let root = html.root_element().id();
+        let count = html.tree.nodes().count();
+
+        assert_eq!(
+            html.get_mut(text).unwrap().set_inner_html("<i></i>"),
+            Err(HtmlContextError::NotAnElement)
+        );
+        assert_eq!(
+            html.get_mut(text)
+                .unwrap()
+                .insert_adjacent_html(AdjacentPosition::AfterEnd, "<i></i>"),
+            Err(HtmlContextError::NotAnElement)
+        );
+
+        let mut document = Html::parse_document("<p>b</p>");
+        let html_element = document.root_element().id();
+        assert_eq!(
+            document
+                .get_mut(html_element)
+                .unwrap()
+                .set_outer_html("<i></i>"),
+            Err(HtmlContextError::NoParentElement)
+        );
+
+        assert_eq!(html.tree.nodes().count(), count);
+        assert_eq!(html.get_mut(root).unwrap().set_inner_html("c"), Ok(()));
+        assert_eq!(inner(&html), "c");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot insert a node into itself or its descendants")]
+    fn cannot_create_cycles() {
+        let mut html = Html::parse_fragment("<div><p></p></div>");
+        let div = find(&html, "div");
+        let p = find(&html, "p");
+        html.get_mut(p).unwrap().append_id(div);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only documents, fragments and elements can have children")]
+    fn text_cannot_have_children() {
+        let mut html = Html::parse_fragment("<p>text</p>");
+        let text = html
+            .tree
+            .get(find(&html, "p"))
+            .unwrap()
+            .first_child()
+            .unwrap()
+            .id();
+        html.get_mut(text).unwrap().append(Node::new_text("more"));
+    }
+}
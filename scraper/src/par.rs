@@ -0,0 +1,257 @@
+//! Parallel parsing and selection on the [`rayon`] thread pool.
+//!
+//! Requires the `rayon` feature, which also enables the `atomic` feature so that parsed documents
+//! can be shared between threads.
+
+use ego_tree::NodeId;
+use ego_tree::iter::Edge;
+use rayon::prelude::*;
+use selectors::matching::SelectorCaches;
+
+use crate::element_ref::is_template_contents;
+use crate::{ElementRef, Html, Selector};
+
+impl Html {
+    /// Returns the elements matching a selector in document order, matching separate subtrees of
+    /// the document concurrently.
+    ///
+    /// Like [`Html::select`], elements inside the contents of `<template>` elements are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document("<ul><li>1</li><li>2</li></ul><ol><li>3</li></ol>");
+    /// let items = html.par_select(&Selector::parse("li").unwrap());
+    ///
+    /// let texts: Vec<String> = items.iter().map(|li| li.text().collect()).collect();
+    /// assert_eq!(texts, ["1", "2", "3"]);
+    /// ```
+    pub fn par_select(&self, selector: &Selector) -> Vec<ElementRef<'_>> {
+        let tasks = split(self, rayon::current_num_threads() * 4);
+        let matches: Vec<Vec<NodeId>> = tasks
+            .into_par_iter()
+            .map(|task| {
+                let mut caches = SelectorCaches::default();
+                task.select(self, selector, &mut caches)
+            })
+            .collect();
+
+        matches
+            .into_iter()
+            .flatten()
+            .map(|id| ElementRef::wrap(self.tree.get(id).unwrap()).unwrap())
+            .collect()
+    }
+}
+
+/// Parses many documents concurrently, returning them in the order of `inputs`.
+///
+/// # Examples
+///
+/// ```
+/// use scraper::par;
+///
+/// let documents = par::parse_documents(&["<title>one</title>", "<title>two</title>"]);
+/// assert_eq!(documents.len(), 2);
+/// ```
+pub fn parse_documents<S: AsRef<str> + Sync>(inputs: &[S]) -> Vec<Html> {
+    inputs
+        .par_iter()
+        .map(|input| Html::parse_document(input.as_ref()))
+        .collect()
+}
+
+/// Parses many fragments concurrently, returning them in the order of `inputs`.
+pub fn parse_fragments<S: AsRef<str> + Sync>(inputs: &[S]) -> Vec<Html> {
+    inputs
+        .par_iter()
+        .map(|input| Html::parse_fragment(input.as_ref()))
+        .collect()
+}
+
+/// Applies each of `selectors` to each of `documents`, one document per task.
+///
+/// The result is indexed by document, then by selector, and each list of matches is in the same
+/// order as [`Html::select`] would return it.
+///
+/// # Examples
+///
+/// ```
+/// use scraper::{Html, Selector, par};
+///
+/// let documents = par::parse_documents(&[
+///     r#"<h1>One</h1><a href="/a">a</a><a href="/b">b</a>"#,
+///     "<h1>Two</h1>",
+/// ]);
+/// let selectors = [Selector::parse("h1").unwrap(), Selector::parse("a").unwrap()];
+///
+/// let results = par::select_all(&documents, &selectors);
+/// assert_eq!(results[0][1].len(), 2);
+/// assert_eq!(results[1][0][0].inner_html(), "Two");
+/// assert!(results[1][1].is_empty());
+/// ```
+pub fn select_all<'a>(
+    documents: &'a [Html],
+    selectors: &[Selector],
+) -> Vec<Vec<Vec<ElementRef<'a>>>> {
+    documents
+        .par_iter()
+        .map(|document| {
+            selectors
+                .iter()
+                .map(|selector| document.select(selector).collect())
+                .collect()
+        })
+        .collect()
+}
+
+/// A unit of work for [`Html::par_select`].
+#[derive(Debug, Clone, Copy)]
+enum Task {
+    /// Match a single node.
+    Node(NodeId),
+    /// Match a node and all of its descendants.
+    Subtree(NodeId),
+}
+
+/// Splits the document into at least `target` tasks if possible, in document order, by expanding
+/// subtrees one level at a time.
+fn split(html: &Html, target: usize) -> Vec<Task> {
+    let mut tasks = vec![Task::Subtree(html.tree.root().id())];
+
+    while tasks.len() < target {
+        let mut expanded = Vec::with_capacity(tasks.len() * 2);
+        let mut changed = false;
+
+        for task in tasks {
+            match task {
+                Task::Subtree(id) => {
+                    let node = html.tree.get(id).unwrap();
+                    if !node.has_children() {
+                        expanded.push(task);
+                        continue;
+                    }
+                    changed = true;
+                    expanded.push(Task::Node(id));
+                    expanded.extend(
+                        node.children()
+                            .filter(|child| !is_template_contents(*child))
+                            .map(|child| Task::Subtree(child.id())),
+                    );
+                }
+                Task::Node(_) => expanded.push(task),
+            }
+        }
+
+        tasks = expanded;
+        if !changed {
+            break;
+        }
+    }
+
+    tasks
+}
+
+impl Task {
+    fn select(self, html: &Html, selector: &Selector, caches: &mut SelectorCaches) -> Vec<NodeId> {
+        let mut matches = Vec::new();
+        let mut matched = |node| {
+            if let Some(element) = ElementRef::wrap(node)
+                && selector.matches_with_scope_and_cache(&element, None, caches)
+            {
+                matches.push(element.id());
+            }
+        };
+
+        match self {
+            Task::Node(id) => matched(html.tree.get(id).unwrap()),
+            Task::Subtree(id) => {
+                let mut template_depth = 0_usize;
+                for edge in html.tree.get(id).unwrap().traverse() {
+                    match edge {
+                        Edge::Open(node) if is_template_contents(node) => template_depth += 1,
+                        Edge::Close(node) if is_template_contents(node) => template_depth -= 1,
+                        Edge::Open(node) if template_depth == 0 => matched(node),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_documents, parse_fragments, select_all};
+    use crate::{Html, Selector};
+
+    fn document() -> String {
+        let mut html = String::from("<!DOCTYPE html><title>t</title>");
+        for section in 0..50 {
+            html.push_str(&format!(r#"<section id="s{section}">"#));
+            for item in 0..20 {
+                html.push_str(&format!(
+                    r#"<p class="c{}">{section}.{item}<b>b</b></p>"#,
+                    item % 3
+                ));
+            }
+            html.push_str("<template><p class=\"c0\">hidden</p></template></section>");
+        }
+        html
+    }
+
+    #[test]
+    fn par_select_matches_select() {
+        let html = Html::parse_document(&document());
+        for selector in [
+            "p",
+            "p.c1",
+            "section > p.c0 b",
+            "#s7 p",
+            "template",
+            "title",
+        ] {
+            let selector = Selector::parse(selector).unwrap();
+            let expected: Vec<_> = html.select(&selector).collect();
+            assert_eq!(html.par_select(&selector), expected);
+        }
+    }
+
+    #[test]
+    fn batch_parsing_keeps_order() {
+        let inputs: Vec<String> = (0..64).map(|i| format!("<p>{i}</p>")).collect();
+
+        let documents = parse_documents(&inputs);
+        assert_eq!(documents.len(), inputs.len());
+        for (document, input) in documents.iter().zip(&inputs) {
+            assert_eq!(document, &Html::parse_document(input));
+        }
+
+        let fragments = parse_fragments(&inputs);
+        for (fragment, input) in fragments.iter().zip(&inputs) {
+            assert_eq!(fragment.root_element().inner_html(), *input);
+        }
+    }
+
+    #[test]
+    fn select_all_per_document() {
+        let documents = parse_documents(&[document(), String::new(), "<p>x</p>".to_owned()]);
+        let selectors = [
+            Selector::parse("p.c2").unwrap(),
+            Selector::parse("p").unwrap(),
+        ];
+
+        let results = select_all(&documents, &selectors);
+        assert_eq!(results.len(), 3);
+        for (result, document) in results.iter().zip(&documents) {
+            for (matches, selector) in result.iter().zip(&selectors) {
+                assert_eq!(*matches, document.select(selector).collect::<Vec<_>>());
+            }
+        }
+        assert_eq!(results[2][1][0].inner_html(), "x");
+    }
+}
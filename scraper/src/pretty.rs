@@ -0,0 +1,585 @@
+//! Pretty-printing serialization.
+//!
+//! Requires the `pretty` feature.
+//!
+//! [`PrettySerializer`] is an `html5ever` serializer that lays out what it is given with one block
+//! element per line, indented by depth, and fills inline content up to a line width. Whitespace
+//! is only added, removed or collapsed where the rendering of the document does not change:
+//!
+//! - Between block elements, such as `<div>` and `<li>`, and at their start and end.
+//! - Within inline content, where a run of whitespace is collapsed to a single space or a line
+//!   break, and lines are only broken where there was whitespace.
+//!
+//! The contents of `<pre>`, `<textarea>`, `<script>`, `<style>` and other elements whose
+//! whitespace matters, as well as foreign elements such as `<svg>`, are written exactly as
+//! [`Html::html`](crate::Html::html) would.
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//!
+//! let html = Html::parse_fragment(
+//!     "<ul><li>One <b>bold</b> item</li><li><pre>  two\n  lines</pre></li></ul>",
+//! );
+//! assert_eq!(
+//!     html.root_element().pretty(),
+//!     "<html>\n  <ul>\n    <li>One <b>bold</b> item</li>\n    <li>\n      <pre>  two\n  lines</pre>\n    </li>\n  </ul>\n</html>\n",
+//! );
+//! ```
+
+use std::io::{self, Write};
+
+use html5ever::serialize::{AttrRef, HtmlSerializer, SerializeOpts, Serializer, TraversalScope};
+use html5ever::{LocalName, QualName};
+
+use crate::node::serializable::{Kind, kind};
+
+/// Options of [`PrettySerializer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// The number of spaces each level of nesting is indented by.
+    pub indent: usize,
+
+    /// The width that inline content is filled to, including indentation. Lines are longer when
+    /// a tag or word does not fit.
+    pub width: usize,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            width: 80,
+        }
+    }
+}
+
+/// An `html5ever` serializer that pretty-prints HTML.
+///
+/// The output is buffered, since the layout of an element depends on its contents, and written
+/// by [`PrettySerializer::finish`].
+///
+/// # Example
+///
+/// ```
+/// use html5ever::serialize::{Serialize, TraversalScope};
+/// use scraper::Html;
+/// use scraper::pretty::{PrettyOptions, PrettySerializer};
+///
+/// let html = Html::parse_document("<title>Hi</title><p>Hello, world!");
+/// let mut serializer = PrettySerializer::new(Vec::new(), PrettyOptions::default());
+/// html.serialize(&mut serializer, TraversalScope::IncludeNode).unwrap();
+/// let output = String::from_utf8(serializer.finish().unwrap()).unwrap();
+/// assert!(output.contains("\n    <title>Hi</title>\n"));
+/// ```
+#[derive(Debug)]
+pub struct PrettySerializer<W: Write> {
+    writer: W,
+    options: PrettyOptions,
+    open: Vec<Element>,
+    items: Vec<Item>,
+}
+
+#[derive(Debug)]
+enum Item {
+    Element(Element),
+    Text(String),
+    Comment(String),
+    Doctype(String),
+    ProcessingInstruction(String, String),
+}
+
+#[derive(Debug)]
+struct Element {
+    name: QualName,
+    attrs: Vec<(QualName, String)>,
+    children: Vec<Item>,
+}
+
+impl<W: Write> PrettySerializer<W> {
+    /// Creates a serializer that writes to `writer` when it is finished.
+    pub fn new(writer: W, options: PrettyOptions) -> Self {
+        Self {
+            writer,
+            options,
+            open: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Lays out what was serialized, writes it and returns the writer.
+    ///
+    /// Each line ends with a newline, including the last one.
+    pub fn finish(mut self) -> io::Result<W> {
+        while let Some(element) = self.open.pop() {
+            self.push(Item::Element(element));
+        }
+
+        let mut layout = Layout {
+            options: self.options,
+            output: String::new(),
+        };
+        layout.block(&self.items, 0);
+        self.writer.write_all(layout.output.as_bytes())?;
+        Ok(self.writer)
+    }
+
+    fn push(&mut self, item: Item) {
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(item),
+            None => self.items.push(item),
+        }
+    }
+}
+
+impl<W: Write> Serializer for PrettySerializer<W> {
+    fn start_elem<'a, AttrIter>(&mut self, name: QualName, attrs: AttrIter) -> io::Result<()>
+    where
+        AttrIter: Iterator<Item = AttrRef<'a>>,
+    {
+        let attrs = attrs
+            .map(|(name, value)| (name.clone(), value.to_owned()))
+            .collect();
+        self.open.push(Element {
+            name,
+            attrs,
+            children: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn end_elem(&mut self, _name: QualName) -> io::Result<()> {
+        if let Some(element) = self.open.pop() {
+            self.push(Item::Element(element));
+        }
+        Ok(())
+    }
+
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        self.push(Item::Text(text.to_owned()));
+        Ok(())
+    }
+
+    fn write_comment(&mut self, text: &str) -> io::Result<()> {
+        self.push(Item::Comment(text.to_owned()));
+        Ok(())
+    }
+
+    fn write_doctype(&mut self, name: &str) -> io::Result<()> {
+        self.push(Item::Doctype(name.to_owned()));
+        Ok(())
+    }
+
+    fn write_processing_instruction(&mut self, target: &str, data: &str) -> io::Result<()> {
+        self.push(Item::ProcessingInstruction(
+            target.to_owned(),
+            data.to_owned(),
+        ));
+        Ok(())
+    }
+}
+
+/// A piece of inline content.
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Space,
+    Break,
+}
+
+struct Layout {
+    options: PrettyOptions,
+    output: String,
+}
+
+impl Layout {
+    fn line(&mut self, depth: usize, line: &str) {
+        let indent = depth * self.options.indent;
+        self.output.extend(std::iter::repeat_n(' ', indent));
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    fn block(&mut self, items: &[Item], depth: usize) {
+        let mut run = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            if is_block(items, index) {
+                self.run(&run, depth);
+                run.clear();
+                self.block_item(item, depth);
+            } else {
+                run.push(item);
+            }
+        }
+        self.run(&run, depth);
+    }
+
+    fn block_item(&mut self, item: &Item, depth: usize) {
+        let Item::Element(element) = item else {
+            self.line(depth, &render(|s| item.write(s)));
+            return;
+        };
+        if kind(&element.name) != Kind::Block || is_void(element) {
+            self.line(depth, &render(|s| item.write(s)));
+            return;
+        }
+
+        let (start, end) = tags(element);
+        if element
+            .children
+            .iter()
+            .enumerate()
+            .any(|(index, _)| is_block(&element.children, index))
+        {
+            self.line(depth, &start);
+            self.block(&element.children, depth + 1);
+            self.line(depth, &end);
+            return;
+        }
+
+        let mut tokens = Vec::new();
+        for child in &element.children {
+            inline(child, &mut tokens);
+        }
+        let lines = fill(&tokens, usize::MAX);
+        let fits = match lines.as_slice() {
+            [] => true,
+            [line] => {
+                let width = depth * self.options.indent + width(&start) + width(line) + width(&end);
+                width <= self.options.width
+            }
+            _ => false,
+        };
+
+        if fits {
+            let line = lines.first().map_or("", String::as_str);
+            self.line(depth, &format!("{start}{line}{end}"));
+        } else {
+            self.line(depth, &start);
+            self.fill(&tokens, depth + 1);
+            self.line(depth, &end);
+        }
+    }
+
+    fn run(&mut self, items: &[&Item], depth: usize) {
+        let mut tokens = Vec::new();
+        for item in items {
+            inline(item, &mut tokens);
+        }
+        self.fill(&tokens, depth);
+    }
+
+    fn fill(&mut self, tokens: &[Token], depth: usize) {
+        let available = self
+            .options
+            .width
+            .saturating_sub(depth * self.options.indent);
+        for line in fill(tokens, available) {
+            self.line(depth, &line);
+        }
+    }
+}
+
+/// Returns true if an item is laid out on lines of its own among its siblings.
+fn is_block(items: &[Item], index: usize) -> bool {
+    match &items[index] {
+        Item::Doctype(_) | Item::ProcessingInstruction(..) => true,
+        Item::Text(_) | Item::Comment(_) => false,
+        Item::Element(element) => match kind(&element.name) {
+            Kind::Block => true,
+            Kind::Inline => false,
+            Kind::Verbatim => {
+                let before = index
+                    .checked_sub(1)
+                    .is_some_and(|index| glues(&items[index], true));
+                let after = items.get(index + 1).is_some_and(|item| glues(item, false));
+                !before && !after
+            }
+        },
+    }
+}
+
+/// Returns true if an item is inline content that is not separated by whitespace from the item
+/// before it, if `before`, or else after it.
+fn glues(item: &Item, before: bool) -> bool {
+    match item {
+        Item::Text(text) => {
+            let edge = if before {
+                text.chars().next_back()
+            } else {
+                text.chars().next()
+            };
+            edge.is_some_and(|c| !c.is_ascii_whitespace())
+        }
+        Item::Comment(_) => true,
+        Item::Element(element) => kind(&element.name) == Kind::Inline,
+        Item::Doctype(_) | Item::ProcessingInstruction(..) => false,
+    }
+}
+
+/// Appends the tokens of an item of inline content.
+fn inline(item: &Item, tokens: &mut Vec<Token>) {
+    match item {
+        Item::Text(text) => {
+            for (index, word) in text.split(|c: char| c.is_ascii_whitespace()).enumerate() {
+                if index > 0 {
+                    space(tokens);
+                }
+                if !word.is_empty() {
+                    push(tokens, &render(|s| s.write_text(word)));
+                }
+            }
+        }
+        Item::Element(element) if element.name == html("br") => {
+            push(tokens, &render(|s| item.write(s)));
+            tokens.push(Token::Break);
+        }
+        Item::Element(element) if kind(&element.name) == Kind::Inline && !is_void(element) => {
+            let (start, end) = tags(element);
+            push(tokens, &start);
+            for child in &element.children {
+                inline(child, tokens);
+            }
+            push(tokens, &end);
+        }
+        _ => push(tokens, &render(|s| item.write(s))),
+    }
+}
+
+fn push(tokens: &mut Vec<Token>, text: &str) {
+    match tokens.last_mut() {
+        Some(Token::Word(word)) => word.push_str(text),
+        _ => tokens.push(Token::Word(text.to_owned())),
+    }
+}
+
+fn space(tokens: &mut Vec<Token>) {
+    if !matches!(tokens.last(), Some(Token::Space)) {
+        tokens.push(Token::Space);
+    }
+}
+
+/// Fills lines with words up to a width. Spaces at the start and end of lines are left out, as
+/// they are not rendered.
+fn fill(tokens: &[Token], available: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut space = false;
+    for token in tokens {
+        match token {
+            Token::Word(word) => {
+                if line.is_empty() || !space {
+                    line.push_str(word);
+                } else if width(&line) + 1 + width(word) <= available {
+                    line.push(' ');
+                    line.push_str(word);
+                } else {
+                    lines.push(std::mem::replace(&mut line, word.clone()));
+                }
+                space = false;
+            }
+            Token::Space => space = !line.is_empty(),
+            Token::Break => {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                space = false;
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn html(local: &str) -> QualName {
+    QualName::new(None, ns!(html), LocalName::from(local))
+}
+
+/// Returns true for elements without end tags, such as `<img>`.
+fn is_void(element: &Element) -> bool {
+    tags(element).1.is_empty()
+}
+
+/// Returns true for `<pre>`, `<textarea>` and `<listing>` elements whose text starts with a newline.
+/// The parser drops a newline right after their start tag, so another one has to be written.
+fn starts_with_newline(element: &Element) -> bool {
+    ["pre", "textarea", "listing"]
+        .iter()
+        .any(|name| element.name == html(name))
+        && matches!(element.children.first(), Some(Item::Text(text)) if text.starts_with('\n'))
+}
+
+/// Returns the start and end tags of an element.
+fn tags(element: &Element) -> (String, String) {
+    let start = render(|s| element.start(s));
+    let mut end = render(|s| {
+        element.start(s)?;
+        s.end_elem(element.name.clone())
+    });
+    end.replace_range(..start.len(), "");
+    (start, end)
+}
+
+/// Serializes with the options of [`Html::html`](crate::Html::html).
+fn render(write: impl FnOnce(&mut HtmlSerializer<&mut Vec<u8>>) -> io::Result<()>) -> String {
+    let opts = SerializeOpts {
+        scripting_enabled: false,
+        traversal_scope: TraversalScope::IncludeNode,
+        create_missing_parent: false,
+    };
+    let mut buf = Vec::new();
+    write(&mut HtmlSerializer::new(&mut buf, opts)).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+impl Element {
+    fn start<S: Serializer>(&self, serializer: &mut S) -> io::Result<()> {
+        let attrs = self
+            .attrs
+            .iter()
+            .map(|(name, value)| (name, value.as_str()));
+        serializer.start_elem(self.name.clone(), attrs)
+    }
+}
+
+impl Item {
+    fn write<S: Serializer>(&self, serializer: &mut S) -> io::Result<()> {
+        match self {
+            Item::Element(element) => {
+                element.start(serializer)?;
+                if starts_with_newline(element) {
+                    serializer.write_text("\n")?;
+                }
+                for child in &element.children {
+                    child.write(serializer)?;
+                }
+                serializer.end_elem(element.name.clone())
+            }
+            Item::Text(text) => serializer.write_text(text),
+            Item::Comment(comment) => serializer.write_comment(comment),
+            Item::Doctype(name) => serializer.write_doctype(name),
+            Item::ProcessingInstruction(target, data) => {
+                serializer.write_processing_instruction(target, data)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrettyOptions;
+    use crate::{Html, Selector};
+
+    #[test]
+    fn document() {
+        let html = Html::parse_document(
+            r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title> Title </title>
+            <style>  p { color: red; }
+            </style></head><body><div id="main"><h1>Heading</h1>
+            <p>Some <em>inline</em>  text.</p><img src="a.png"><!-- note --></div></body></html>"#,
+        );
+        assert_eq!(
+            html.pretty(),
+            r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>Title</title>
+    <style>  p { color: red; }
+            </style>
+  </head>
+  <body>
+    <div id="main">
+      <h1>Heading</h1>
+      <p>Some <em>inline</em> text.</p>
+      <img src="a.png"><!-- note -->
+    </div>
+  </body>
+</html>
+"#
+        );
+    }
+
+    #[test]
+    fn fills_inline_content() {
+        let html = Html::parse_fragment(
+            "<p>The quick brown <a href=\"/fox\">fox jumps over</a> the lazy dog.<br>Again.</p>",
+        );
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+        let options = PrettyOptions {
+            indent: 4,
+            width: 24,
+        };
+        assert_eq!(
+            p.pretty_with(options),
+            "<p>\n    The quick brown\n    <a href=\"/fox\">fox\n    jumps over</a> the\n    lazy dog.<br>\n    Again.\n</p>\n"
+        );
+    }
+
+    #[test]
+    fn keeps_whitespace_sensitive_content() {
+        let html = Html::parse_fragment(
+            "<div><pre>  a\n    b</pre><textarea>  x  </textarea><script>if (a  <  b) {}</script>\
+             <svg><text> t </text></svg></div>",
+        );
+        let div = html
+            .select(&Selector::parse("div").unwrap())
+            .next()
+            .unwrap();
+        assert_eq!(
+            div.pretty(),
+            "<div>\n  <pre>  a\n    b</pre>\n  <textarea>  x  </textarea>\n  <script>if (a  <  b) {}</script>\n  <svg><text> t </text></svg>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn keeps_glued_content_inline() {
+        let html = Html::parse_fragment("<p>a<script>x</script>b <b>c</b><span>d</span> </p>");
+        assert_eq!(
+            html.root_element().pretty(),
+            "<html>\n  <p>a<script>x</script>b <b>c</b><span>d</span></p>\n</html>\n"
+        );
+    }
+
+    #[test]
+    fn keeps_leading_newlines() {
+        let html = Html::parse_fragment(
+            "<div><pre>\n\nq</pre><textarea>\n\nx</textarea><p><listing>\n\ny</listing></p></div>",
+        );
+        let reparsed = Html::parse_fragment(&html.root_element().pretty());
+
+        let selector = Selector::parse("pre, textarea, listing").unwrap();
+        let texts = |html: &Html| {
+            html.select(&selector)
+                .map(|element| element.text().collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&html), ["\nq", "\nx", "\ny"]);
+        assert_eq!(texts(&reparsed), texts(&html));
+    }
+
+    #[test]
+    fn preserves_rendering() {
+        let html = Html::parse_fragment(
+            "<div> <p>One  two\nthree</p> <ul> <li>a</li> <li>b <i>c</i></li> </ul> </div>",
+        );
+        let reparsed = Html::parse_fragment(&html.root_element().pretty());
+
+        let text = |html: &Html| {
+            html.root_element()
+                .text()
+                .collect::<String>()
+                .split_ascii_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(text(&reparsed), text(&html));
+    }
+}
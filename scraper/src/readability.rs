@@ -0,0 +1,837 @@
+//! Main content extraction, after Mozilla's Readability.
+//!
+//! Requires the `readability` feature.
+//!
+//! Paragraphs are scored by their length and number of commas, and their scores are added to
+//! those of their ancestors. The ancestor with the highest score, after weighing it by its link
+//! density and the hints in its `class` and `id`, is taken as the main content, together with
+//! siblings that score nearly as well. Navigation, forms, comments, ads and other boilerplate are
+//! left out of both scoring and the extracted content.
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//! use scraper::readability::Readability;
+//!
+//! let document = Html::parse_document(r#"
+//!     <title>Rust 2.0 Released Today | Example News</title>
+//!     <nav><a href="/">Home</a> <a href="/world">World</a></nav>
+//!     <div class="article-body">
+//!         <p>The new release improves compile times, adds new lints, and stabilizes a long
+//!         list of library functions that many have been waiting for.</p>
+//!     </div>
+//!     <div class="comments"><p>First, and a very long comment about nothing at all.</p></div>
+//! "#);
+//!
+//! let article = Readability::default().parse(&document).unwrap();
+//! assert_eq!(article.title.as_deref(), Some("Rust 2.0 Released Today"));
+//! assert!(article.content.html().contains("compile times"));
+//! assert!(!article.content.html().contains("Home"));
+//! assert!(!article.content.html().contains("comment"));
+//! ```
+
+use std::collections::HashMap;
+
+use ego_tree::iter::Edge;
+use ego_tree::{NodeId, NodeRef};
+use html5ever::{LocalName, QualName};
+use url::Url;
+
+use crate::node::Element;
+use crate::rebuild::{Action, rebuild};
+use crate::{ElementRef, Html, Node};
+
+/// Options for main content extraction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Readability {
+    /// The number of characters a paragraph needs to be scored.
+    pub min_text_length: usize,
+
+    /// Keep `class` and `id` attributes in the extracted content.
+    pub keep_classes: bool,
+
+    /// The URL that relative URLs in the extracted content and the lead image are resolved
+    /// against, if any.
+    pub base_url: Option<Url>,
+}
+
+impl Default for Readability {
+    fn default() -> Self {
+        Readability {
+            min_text_length: 25,
+            keep_classes: false,
+            base_url: None,
+        }
+    }
+}
+
+/// An article extracted from a document.
+#[derive(Debug, Clone)]
+pub struct Article {
+    /// The title of the article, without the site name that often accompanies it in `<title>`.
+    pub title: Option<String>,
+
+    /// The author line, without a leading "By".
+    pub byline: Option<String>,
+
+    /// The publication date, as it appears in the document.
+    pub published: Option<String>,
+
+    /// The URL of the image representing the article.
+    pub lead_image: Option<String>,
+
+    /// A fragment with a cleaned copy of the main content.
+    pub content: Html,
+}
+
+/// Elements that are left out with their contents.
+const BOILERPLATE: &[&str] = &[
+    "aside", "button", "embed", "footer", "form", "header", "iframe", "input", "link", "meta",
+    "nav", "noscript", "object", "script", "select", "style", "svg", "template", "textarea",
+];
+
+/// Values of `role` that mark boilerplate.
+const BOILERPLATE_ROLES: &[&str] = &[
+    "alert",
+    "alertdialog",
+    "banner",
+    "complementary",
+    "contentinfo",
+    "dialog",
+    "menu",
+    "menubar",
+    "navigation",
+];
+
+/// Hints in a `class` or `id` that an element is boilerplate.
+const UNLIKELY: &[&str] = &[
+    "-ad-",
+    "ad-break",
+    "agegate",
+    "banner",
+    "breadcrumbs",
+    "combx",
+    "comment",
+    "community",
+    "cookie",
+    "cover-wrap",
+    "disqus",
+    "extra",
+    "footer",
+    "gdpr",
+    "header",
+    "legends",
+    "menu",
+    "newsletter",
+    "pager",
+    "pagination",
+    "popup",
+    "related",
+    "remark",
+    "replies",
+    "rss",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "social",
+    "sponsor",
+    "supplemental",
+];
+
+/// Hints that override [`UNLIKELY`].
+const MAYBE: &[&str] = &[
+    "and", "article", "body", "column", "content", "main", "shadow",
+];
+
+/// Hints in a `class` or `id` that an element is content.
+const POSITIVE: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "h-entry", "hentry", "main", "page", "post",
+    "story", "text",
+];
+
+/// Hints in a `class` or `id` that an element is not content.
+const NEGATIVE: &[&str] = &[
+    "-ad-",
+    "banner",
+    "combx",
+    "comment",
+    "com-",
+    "contact",
+    "footer",
+    "gdpr",
+    "hidden",
+    "masthead",
+    "media",
+    "meta",
+    "outbrain",
+    "promo",
+    "related",
+    "scroll",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "sponsor",
+    "shopping",
+    "tags",
+    "widget",
+];
+
+/// Elements that start blocks, so a `<div>` containing them is not scored as a paragraph.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "div",
+    "dl",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Containers that are removed from the content if they look like link lists or widgets.
+const CONDITIONAL: &[&str] = &["div", "fieldset", "ol", "section", "table", "ul"];
+
+/// Attributes whose values are URLs.
+const URL_ATTRIBUTES: &[&str] = &["href", "poster", "src"];
+
+/// Scores of candidate containers, in document order.
+#[derive(Debug, Default)]
+struct Scores {
+    order: Vec<NodeId>,
+    scores: HashMap<NodeId, f64>,
+}
+
+impl Readability {
+    /// Extracts the main content and metadata of a document.
+    ///
+    /// Returns `None` if no part of the document looks like an article.
+    pub fn parse(&self, html: &Html) -> Option<Article> {
+        let (top, scores) = self.top_candidate(html)?;
+
+        let mut content = Html::parse_fragment("");
+        for node in self.content_nodes(top, &scores) {
+            self.copy(node, &mut content);
+        }
+
+        let lead_image = meta(html, &["og:image", "og:image:url", "twitter:image"])
+            .or_else(|| {
+                html.root_element()
+                    .descendent_elements()
+                    .find(|element| {
+                        element.value().name() == "link"
+                            && has_token(element.value().attr("rel"), "image_src")
+                    })
+                    .and_then(|link| link.value().attr("href"))
+                    .map(str::to_owned)
+            })
+            .or_else(|| {
+                content
+                    .root_element()
+                    .descendent_elements()
+                    .find(|element| element.value().name() == "img")
+                    .and_then(|img| img.value().attr("src"))
+                    .map(str::to_owned)
+            })
+            .map(|url| self.resolve(&url));
+
+        Some(Article {
+            title: title(html),
+            byline: byline(html),
+            published: published(html),
+            lead_image,
+            content,
+        })
+    }
+
+    /// Returns the element that holds the main content of a document, as it is in the document.
+    ///
+    /// Returns `None` if no part of the document looks like an article.
+    pub fn main_content<'a>(&self, html: &'a Html) -> Option<ElementRef<'a>> {
+        self.top_candidate(html).map(|(top, _)| top)
+    }
+
+    fn top_candidate<'a>(&self, html: &'a Html) -> Option<(ElementRef<'a>, Scores)> {
+        let mut scores = Scores::default();
+        let mut skipped = None;
+
+        for edge in html.root_element().traverse() {
+            match edge {
+                Edge::Open(node) if skipped.is_none() => {
+                    let Some(element) = ElementRef::wrap(node) else {
+                        if !node.value().is_text() && node.has_children() {
+                            skipped = Some(node.id());
+                        }
+                        continue;
+                    };
+                    if is_boilerplate(element.value()) {
+                        skipped = Some(node.id());
+                    } else if is_paragraph(element) {
+                        self.score_paragraph(element, &mut scores);
+                    }
+                }
+                Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+                _ => {}
+            }
+        }
+
+        // Containers full of links are likely to be navigation.
+        for id in &scores.order {
+            let element = ElementRef::wrap(html.tree.get(*id).unwrap()).unwrap();
+            *scores.scores.get_mut(id).unwrap() *= 1.0 - link_density(element);
+        }
+
+        let mut top: Option<(NodeId, f64)> = None;
+        for id in &scores.order {
+            let score = scores.scores[id];
+            if top.is_none_or(|(_, top)| score > top) {
+                top = Some((*id, score));
+            }
+        }
+
+        let (id, _) = top?;
+        let top = ElementRef::wrap(html.tree.get(id).unwrap()).unwrap();
+        Some((top, scores))
+    }
+
+    fn score_paragraph(&self, paragraph: ElementRef<'_>, scores: &mut Scores) {
+        let text = visible_text(*paragraph);
+        let length = text.chars().count();
+        if length < self.min_text_length {
+            return;
+        }
+
+        let commas = text.chars().filter(|c| matches!(c, ',' | '，')).count();
+        let score = 1.0 + commas as f64 + (length as f64 / 100.0).min(3.0);
+
+        for (level, ancestor) in paragraph
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .take(5)
+            .enumerate()
+        {
+            let entry = scores.scores.entry(ancestor.id()).or_insert_with(|| {
+                scores.order.push(ancestor.id());
+                initial_score(ancestor.value())
+            });
+            let divider = match level {
+                0 => 1.0,
+                1 => 2.0,
+                level => level as f64 * 3.0,
+            };
+            *entry += score / divider;
+        }
+    }
+
+    /// Returns the top candidate and the siblings that belong to the content with it.
+    fn content_nodes<'a>(&self, top: ElementRef<'a>, scores: &Scores) -> Vec<ElementRef<'a>> {
+        let Some(parent) = top.parent().filter(|parent| parent.value().is_element()) else {
+            return vec![top];
+        };
+
+        let top_score = scores.scores[&top.id()];
+        let threshold = (top_score * 0.2).max(10.0);
+        let top_class = top.value().attr("class").filter(|class| !class.is_empty());
+
+        parent
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|sibling| {
+                if *sibling == top {
+                    return true;
+                }
+                if is_boilerplate(sibling.value()) {
+                    return false;
+                }
+
+                let mut bonus = 0.0;
+                if top_class.is_some() && sibling.value().attr("class") == top_class {
+                    bonus = top_score * 0.2;
+                }
+                if scores
+                    .scores
+                    .get(&sibling.id())
+                    .is_some_and(|score| score + bonus >= threshold)
+                {
+                    return true;
+                }
+
+                if sibling.value().name() != "p" {
+                    return false;
+                }
+                let text = visible_text(**sibling);
+                let length = text.chars().count();
+                let density = link_density(*sibling);
+                (length > 80 && density < 0.25)
+                    || (length > 0
+                        && density == 0.0
+                        && (text.contains(". ") || text.ends_with('.')))
+            })
+            .collect()
+    }
+
+    /// Appends a cleaned copy of `root` to the root element of `output`.
+    fn copy(&self, root: ElementRef<'_>, output: &mut Html) {
+        rebuild(*root, output, |element| {
+            if is_boilerplate(element.value()) || (element != root && is_clutter(element)) {
+                Action::Skip
+            } else {
+                Action::Copy(Box::new(self.clean(element.value())))
+            }
+        });
+    }
+
+    /// Returns a copy of an element without presentational and script attributes.
+    fn clean(&self, element: &Element) -> Element {
+        let mut copy = Element::new(element.name.clone(), Vec::new());
+        for (name, value) in &element.attrs {
+            let local = &*name.local;
+            if local == "style"
+                || local.starts_with("on")
+                || (!self.keep_classes && matches!(local, "class" | "id"))
+            {
+                continue;
+            }
+
+            if name.ns == ns!() && URL_ATTRIBUTES.contains(&local) {
+                copy.set_attr_ns(
+                    QualName::new(None, ns!(), LocalName::from(local)),
+                    &self.resolve(value),
+                );
+            } else {
+                copy.set_attr_ns(name.clone(), value);
+            }
+        }
+        copy
+    }
+
+    fn resolve(&self, url: &str) -> String {
+        let url = url.trim();
+        match &self.base_url {
+            Some(base) => base.join(url).map_or_else(|_| url.to_owned(), String::from),
+            None => url.to_owned(),
+        }
+    }
+}
+
+/// Returns true if an element is navigation, a form, hidden, or otherwise not content.
+fn is_boilerplate(element: &Element) -> bool {
+    let name = element.name();
+    if BOILERPLATE.contains(&name)
+        || element.attr("hidden").is_some()
+        || element.attr("aria-hidden") == Some("true")
+        || element
+            .attr("role")
+            .is_some_and(|role| BOILERPLATE_ROLES.contains(&role.trim()))
+    {
+        return true;
+    }
+
+    let hints = hints(element);
+    !matches!(name, "a" | "article" | "body" | "html" | "main")
+        && UNLIKELY.iter().any(|hint| hints.contains(hint))
+        && !MAYBE.iter().any(|hint| hints.contains(hint))
+}
+
+/// Returns true if a container looks like a list of links, a gallery or a widget rather than
+/// content.
+fn is_clutter(element: ElementRef<'_>) -> bool {
+    let name = element.value().name();
+    if !CONDITIONAL.contains(&name) {
+        return false;
+    }
+
+    let weight = class_weight(element.value());
+    if weight < 0.0 {
+        return true;
+    }
+
+    let text = visible_text(*element);
+    if text.chars().filter(|c| *c == ',').count() >= 10 {
+        return false;
+    }
+
+    let count = |names: &[&str]| {
+        element
+            .descendent_elements()
+            .filter(|descendant| names.contains(&descendant.value().name()))
+            .count()
+    };
+    let paragraphs = count(&["p"]) as f64;
+    let images = count(&["img"]);
+    let items = count(&["li"]) as f64 - 100.0;
+    let inputs = count(&["input"]) as f64;
+    let headings = count(&["h1", "h2", "h3", "h4", "h5", "h6"]);
+    let density = link_density(element);
+    let length = text.chars().count();
+    let is_list = matches!(name, "ol" | "ul");
+
+    (images > 1 && paragraphs / (images as f64) < 0.5)
+        || (!is_list && items > paragraphs)
+        || inputs > (paragraphs / 3.0).floor()
+        || (!is_list && length < 25 && headings == 0 && (images == 0 || images > 2))
+        || (weight < 25.0 && density > 0.2)
+        || (weight >= 25.0 && density > 0.5)
+}
+
+/// Returns true if an element is scored as a paragraph.
+fn is_paragraph(element: ElementRef<'_>) -> bool {
+    match element.value().name() {
+        "p" | "pre" | "td" | "h2" | "h3" | "h4" | "h5" | "h6" => true,
+        "div" => !element
+            .descendent_elements()
+            .any(|descendant| BLOCKS.contains(&descendant.value().name())),
+        _ => false,
+    }
+}
+
+fn initial_score(element: &Element) -> f64 {
+    let score = match element.name() {
+        "div" => 5.0,
+        "blockquote" | "pre" | "td" => 3.0,
+        "address" | "dd" | "dl" | "dt" | "form" | "li" | "ol" | "ul" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    score + class_weight(element)
+}
+
+/// Returns the weight of the hints in the `class` and `id` of an element.
+fn class_weight(element: &Element) -> f64 {
+    let mut weight = 0.0;
+    for value in [element.attr("class"), element.attr("id")]
+        .into_iter()
+        .flatten()
+    {
+        let value = value.to_ascii_lowercase();
+        if NEGATIVE.iter().any(|hint| value.contains(hint)) {
+            weight -= 25.0;
+        }
+        if POSITIVE.iter().any(|hint| value.contains(hint)) {
+            weight += 25.0;
+        }
+    }
+    weight
+}
+
+/// Returns the lowercase `class` and `id` of an element.
+fn hints(element: &Element) -> String {
+    let class = element.attr("class").unwrap_or_default();
+    let id = element.attr("id").unwrap_or_default();
+    format!("{class} {id}").to_ascii_lowercase()
+}
+
+/// Returns the text of a node outside boilerplate, with whitespace collapsed.
+fn visible_text(node: NodeRef<'_, Node>) -> String {
+    let mut text = String::new();
+    let mut skipped = None;
+    for edge in node.traverse() {
+        match edge {
+            Edge::Open(node) if skipped.is_none() => match node.value() {
+                Node::Text(t) => {
+                    for word in t.split_ascii_whitespace() {
+                        if !text.is_empty() {
+                            text.push(' ');
+                        }
+                        text.push_str(word);
+                    }
+                }
+                Node::Element(element) if is_boilerplate(element) => skipped = Some(node.id()),
+                Node::Element(_) => {}
+                _ => {
+                    if node.has_children() {
+                        skipped = Some(node.id());
+                    }
+                }
+            },
+            Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Returns the share of the text of an element that is in links.
+fn link_density(element: ElementRef<'_>) -> f64 {
+    let length = visible_text(*element).chars().count();
+    if length == 0 {
+        return 0.0;
+    }
+
+    let links = element
+        .descendent_elements()
+        .filter(|descendant| descendant.value().name() == "a")
+        .map(|link| {
+            let weight = match link.value().attr("href") {
+                Some(href) if href.trim_start().starts_with('#') => 0.3,
+                _ => 1.0,
+            };
+            visible_text(*link).chars().count() as f64 * weight
+        })
+        .sum::<f64>();
+    links / length as f64
+}
+
+fn has_token(value: Option<&str>, token: &str) -> bool {
+    value.is_some_and(|value| {
+        value
+            .split_ascii_whitespace()
+            .any(|t| t.eq_ignore_ascii_case(token))
+    })
+}
+
+/// Returns the content of the first `<meta>` whose `property`, `name` or `itemprop` is one of
+/// `keys`, trying the keys in order.
+fn meta(html: &Html, keys: &[&str]) -> Option<String> {
+    let metas = html
+        .root_element()
+        .descendent_elements()
+        .filter(|element| element.value().name() == "meta")
+        .collect::<Vec<_>>();
+
+    keys.iter().find_map(|key| {
+        metas.iter().find_map(|meta| {
+            let element = meta.value();
+            let matches = ["property", "name", "itemprop"].iter().any(|attr| {
+                element
+                    .attr(attr)
+                    .is_some_and(|value| value.trim().eq_ignore_ascii_case(key))
+            });
+            element
+                .attr("content")
+                .map(str::trim)
+                .filter(|content| matches && !content.is_empty())
+                .map(str::to_owned)
+        })
+    })
+}
+
+fn first_element<'a>(
+    html: &'a Html,
+    predicate: impl Fn(&ElementRef<'a>) -> bool,
+) -> Option<ElementRef<'a>> {
+    html.root_element().descendent_elements().find(predicate)
+}
+
+fn title(html: &Html) -> Option<String> {
+    if let Some(title) = meta(html, &["og:title", "twitter:title"]) {
+        return Some(title);
+    }
+
+    if let Some(title) = first_element(html, |element| element.value().name() == "title") {
+        let title = visible_text(*title);
+        if !title.is_empty() {
+            return Some(strip_site_name(&title).to_owned());
+        }
+    }
+
+    first_element(html, |element| element.value().name() == "h1")
+        .map(|h1| visible_text(*h1))
+        .filter(|title| !title.is_empty())
+}
+
+/// Removes the site name from a title such as "Headline | Site".
+fn strip_site_name(title: &str) -> &str {
+    const SEPARATORS: &[&str] = &[" | ", " - ", " – ", " — ", " :: ", " / ", " » "];
+    let words = |text: &str| text.split_whitespace().count();
+
+    let last = SEPARATORS
+        .iter()
+        .filter_map(|separator| title.rfind(separator))
+        .max();
+    if let Some(index) = last
+        && words(&title[..index]) >= 3
+    {
+        return title[..index].trim();
+    }
+
+    let first = SEPARATORS
+        .iter()
+        .filter_map(|separator| title.find(separator).map(|index| (index, separator.len())))
+        .min();
+    if let Some((index, length)) = first
+        && words(&title[index + length..]) >= 3
+    {
+        return title[index + length..].trim();
+    }
+
+    title
+}
+
+fn byline(html: &Html) -> Option<String> {
+    let byline = meta(html, &["author", "article:author", "dc.creator"])
+        .filter(|author| !author.starts_with("http"))
+        .or_else(|| {
+            first_element(html, |element| {
+                let element = element.value();
+                has_token(element.attr("rel"), "author")
+                    || has_token(element.attr("itemprop"), "author")
+                    || ["byline", "author"]
+                        .iter()
+                        .any(|hint| hints(element).contains(hint))
+            })
+            .map(|element| {
+                element
+                    .value()
+                    .attr("content")
+                    .map_or_else(|| visible_text(*element), str::to_owned)
+            })
+            .filter(|byline| !byline.is_empty() && byline.chars().count() < 100)
+        })?;
+
+    let trimmed = byline.trim();
+    let without_by = trimmed
+        .get(..3)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("by "))
+        .map_or(trimmed, |_| trimmed[3..].trim_start());
+    Some(without_by.to_owned())
+}
+
+fn published(html: &Html) -> Option<String> {
+    meta(
+        html,
+        &[
+            "article:published_time",
+            "datePublished",
+            "pubdate",
+            "publish-date",
+            "date",
+            "dc.date",
+            "dcterms.created",
+        ],
+    )
+    .or_else(|| {
+        first_element(html, |element| {
+            let element = element.value();
+            has_token(element.attr("itemprop"), "datePublished")
+                || (element.name() == "time" && element.attr("datetime").is_some())
+        })
+        .and_then(|element| {
+            let value = element.value();
+            value
+                .attr("datetime")
+                .or_else(|| value.attr("content"))
+                .map(str::to_owned)
+                .or_else(|| Some(visible_text(*element)))
+        })
+        .filter(|date| !date.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::Readability;
+    use crate::Html;
+
+    const PARAGRAPH: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
+                             eiusmod tempor incididunt ut labore et dolore magna aliqua.";
+
+    fn article_page() -> Html {
+        Html::parse_document(&format!(
+            r#"<html><head>
+            <title>A Long Headline Here - The Daily Site</title>
+            <meta name="author" content="By Jane Doe">
+            <meta property="article:published_time" content="2024-05-01T08:00:00Z">
+            </head><body>
+            <header><a href="/">The Daily Site</a></header>
+            <nav><ul><li><a href="/a">Section A</a></li><li><a href="/b">Section B</a></li></ul></nav>
+            <div id="main">
+                <div class="post-body">
+                    <p>{PARAGRAPH}</p>
+                    <img src="figure.png">
+                    <p>{PARAGRAPH} <a href="more.html">More</a></p>
+                    <div class="share-widget"><a href="https://x.com">Share</a></div>
+                    <ul class="links"><li><a href="/1">One link</a></li><li><a href="/2">Two</a></li></ul>
+                    <script>track()</script>
+                </div>
+                <div class="post-body"><p>{PARAGRAPH}</p></div>
+            </div>
+            <div class="sidebar"><p>{PARAGRAPH}</p></div>
+            <div id="comments"><p>{PARAGRAPH}</p></div>
+            <footer><p>Copyright, all rights reserved, and so on, and so forth.</p></footer>
+            </body></html>"#
+        ))
+    }
+
+    #[test]
+    fn main_content() {
+        let html = article_page();
+        let main = Readability::default().main_content(&html).unwrap();
+        assert_eq!(main.value().attr("class"), Some("post-body"));
+    }
+
+    #[test]
+    fn content_is_cleaned() {
+        let readability = Readability {
+            base_url: Some(Url::parse("https://news.example/2024/story").unwrap()),
+            ..Default::default()
+        };
+        let article = readability.parse(&article_page()).unwrap();
+        let content = article.content.html();
+
+        // Both parts of the post, but none of the boilerplate.
+        assert_eq!(content.matches("Lorem ipsum").count(), 3);
+        for boilerplate in [
+            "Section A",
+            "Share",
+            "One link",
+            "track",
+            "Copyright",
+            "class=",
+        ] {
+            assert!(!content.contains(boilerplate), "{boilerplate} in {content}");
+        }
+        assert!(content.contains(r#"<a href="https://news.example/2024/more.html">More</a>"#));
+
+        assert_eq!(
+            article.lead_image.as_deref(),
+            Some("https://news.example/2024/figure.png")
+        );
+    }
+
+    #[test]
+    fn metadata() {
+        let article = Readability::default().parse(&article_page()).unwrap();
+        assert_eq!(article.title.as_deref(), Some("A Long Headline Here"));
+        assert_eq!(article.byline.as_deref(), Some("Jane Doe"));
+        assert_eq!(article.published.as_deref(), Some("2024-05-01T08:00:00Z"));
+
+        let html = Html::parse_document(&format!(
+            r#"<title>Site | Another Long Headline</title>
+            <meta property="og:image" content="/og.png">
+            <article><h1>Heading</h1><span class="byline">by Sam Roe</span>
+            <time datetime="2023-01-02">Jan 2</time><p>{PARAGRAPH}</p></article>"#
+        ));
+        let article = Readability::default().parse(&html).unwrap();
+        assert_eq!(article.title.as_deref(), Some("Another Long Headline"));
+        assert_eq!(article.byline.as_deref(), Some("Sam Roe"));
+        assert_eq!(article.published.as_deref(), Some("2023-01-02"));
+        assert_eq!(article.lead_image.as_deref(), Some("/og.png"));
+    }
+
+    #[test]
+    fn no_content() {
+        let html = Html::parse_document("<nav><p>Only navigation, with no content.</p></nav>");
+        assert!(Readability::default().parse(&html).is_none());
+    }
+}
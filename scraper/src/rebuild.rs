@@ -0,0 +1,72 @@
+//! Copying filtered subtrees into a new document.
+
+use ego_tree::iter::Edge;
+use ego_tree::{NodeId, NodeRef};
+
+use crate::node::Element;
+use crate::{ElementRef, Html, Node};
+
+/// What to do with an element of the copied subtree.
+pub(crate) enum Action {
+    /// Append the given element in its place and copy its children into it.
+    Copy(Box<Element>),
+
+    /// Copy its children in its place.
+    #[cfg_attr(not(feature = "sanitize"), allow(dead_code))]
+    Unwrap,
+
+    /// Leave it out together with its descendants.
+    Skip,
+}
+
+/// Appends a copy of the subtree rooted at `root` to the root element of `output`, asking
+/// `action` what to do with each element.
+///
+/// Text is copied as it is. Comments, doctypes, processing instructions and template contents
+/// are left out.
+pub(crate) fn rebuild<'a>(
+    root: NodeRef<'a, Node>,
+    output: &mut Html,
+    mut action: impl FnMut(ElementRef<'a>) -> Action,
+) {
+    // The output parent of the children of each open source node.
+    let mut parents: Vec<NodeId> = vec![output.root_element().id()];
+    let mut skipped = None;
+
+    for edge in root.traverse() {
+        let node = match edge {
+            Edge::Open(node) => node,
+            Edge::Close(node) => {
+                if skipped == Some(node.id()) {
+                    skipped = None;
+                } else if skipped.is_none() && node.value().is_element() {
+                    parents.pop();
+                }
+                continue;
+            }
+        };
+        if skipped.is_some() {
+            continue;
+        }
+
+        let parent = *parents.last().unwrap();
+        match node.value() {
+            Node::Text(text) => {
+                output.get_mut(parent).unwrap().append(Node::new_text(text));
+            }
+            Node::Element(_) => match action(ElementRef::wrap(node).unwrap()) {
+                Action::Copy(element) => {
+                    let mut parent = output.get_mut(parent).unwrap();
+                    parents.push(parent.append(Node::Element(*element)).id());
+                }
+                Action::Unwrap => parents.push(parent),
+                Action::Skip => skipped = Some(node.id()),
+            },
+            _ => {
+                if node.has_children() {
+                    skipped = Some(node.id());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Allowlist-based HTML sanitization.
+//!
+//! Requires the `sanitize` feature.
+//!
+//! A [`Sanitizer`] copies the parts of a parsed document that it allows into a new fragment and
+//! serializes that, so the result is interpreted the same way by scraper and by browsers.
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::sanitize::Sanitizer;
+//!
+//! let sanitizer = Sanitizer::default();
+//! let clean = sanitizer.clean(
+//!     r#"<p onclick="steal()">Hi <a href="javascript:steal()">there</a><script>steal()</script></p>"#,
+//! );
+//! assert_eq!(clean, r#"<p>Hi <a rel="noopener">there</a></p>"#);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use html5ever::{LocalName, QualName};
+
+use crate::node::Element;
+use crate::rebuild::{Action, rebuild};
+use crate::srcset::srcset_candidates;
+use crate::{ElementRef, Html, Node};
+
+/// What to do with elements whose tags are not allowed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Disallowed {
+    /// Remove the element and its contents.
+    Drop,
+
+    /// Remove the element but keep its sanitized contents.
+    #[default]
+    Unwrap,
+}
+
+/// Allowlists for sanitizing HTML.
+///
+/// The defaults allow common formatting, list, table, link and image markup, with `http`,
+/// `https` and `mailto` URLs and no inline styles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sanitizer {
+    /// Allowed tag names.
+    pub tags: HashSet<String>,
+
+    /// Allowed attribute names per tag name. Attributes listed under `"*"` are allowed on every
+    /// allowed tag.
+    ///
+    /// Event handler attributes, whose names start with `on`, and attributes with a namespace are
+    /// always removed.
+    pub attributes: HashMap<String, HashSet<String>>,
+
+    /// Attributes whose values are URLs, which are removed unless they are relative or use one of
+    /// [`Sanitizer::url_schemes`].
+    ///
+    /// `srcset` and `imagesrcset` values are lists of image candidates: candidates with other URLs
+    /// are removed, and the attribute is removed if none are left.
+    pub url_attributes: HashSet<String>,
+
+    /// Allowed URL schemes, in lowercase and without the trailing colon.
+    pub url_schemes: HashSet<String>,
+
+    /// Allowed CSS properties in `style` attributes, which must also be allowed as an attribute.
+    /// Other declarations are removed, as are declarations whose values could load resources or
+    /// run code.
+    pub css_properties: HashSet<String>,
+
+    /// Tags whose elements are always removed together with their contents, whether or not they
+    /// are allowed.
+    pub drop_contents: HashSet<String>,
+
+    /// What to do with elements whose tags are not allowed.
+    pub disallowed: Disallowed,
+
+    /// A `rel` value added to links, such as `noopener`. Its tokens are added to those of an
+    /// allowed `rel` attribute.
+    pub link_rel: Option<String>,
+}
+
+impl Default for Sanitizer {
+    fn default() -> Self {
+        let tags = [
+            "a",
+            "abbr",
+            "b",
+            "blockquote",
+            "br",
+            "caption",
+            "cite",
+            "code",
+            "dd",
+            "del",
+            "div",
+            "dl",
+            "dt",
+            "em",
+            "figcaption",
+            "figure",
+            "h1",
+            "h2",
+            "h3",
+            "h4",
+            "h5",
+            "h6",
+            "hr",
+            "i",
+            "img",
+            "ins",
+            "kbd",
+            "li",
+            "mark",
+            "ol",
+            "p",
+            "pre",
+            "q",
+            "s",
+            "small",
+            "span",
+            "strong",
+            "sub",
+            "sup",
+            "table",
+            "tbody",
+            "td",
+            "tfoot",
+            "th",
+            "thead",
+            "tr",
+            "u",
+            "ul",
+        ];
+        let attributes = [
+            ("*", &["dir", "lang", "title"][..]),
+            ("a", &["href", "hreflang"]),
+            ("blockquote", &["cite"]),
+            ("del", &["cite", "datetime"]),
+            ("img", &["alt", "height", "src", "width"]),
+            ("ins", &["cite", "datetime"]),
+            ("ol", &["reversed", "start"]),
+            ("q", &["cite"]),
+            ("td", &["colspan", "rowspan"]),
+            ("th", &["colspan", "rowspan", "scope"]),
+        ];
+        let url_attributes = [
+            "action",
+            "background",
+            "cite",
+            "formaction",
+            "href",
+            "imagesrcset",
+            "longdesc",
+            "poster",
+            "src",
+            "srcset",
+        ];
+        let drop_contents = [
+            "embed", "iframe", "noscript", "object", "script", "style", "template", "textarea",
+            "title",
+        ];
+
+        Sanitizer {
+            tags: set(&tags),
+            attributes: attributes
+                .iter()
+                .map(|(tag, attributes)| (tag.to_string(), set(attributes)))
+                .collect(),
+            url_attributes: set(&url_attributes),
+            url_schemes: set(&["http", "https", "mailto"]),
+            css_properties: HashSet::new(),
+            drop_contents: set(&drop_contents),
+            disallowed: Disallowed::default(),
+            link_rel: Some("noopener".to_owned()),
+        }
+    }
+}
+
+fn set(values: &[&str]) -> HashSet<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+impl Sanitizer {
+    /// Parses `fragment` as an HTML fragment and returns its sanitized serialization.
+    pub fn clean(&self, fragment: &str) -> String {
+        self.sanitize(&Html::parse_fragment(fragment))
+    }
+
+    /// Returns the sanitized serialization of a document or fragment.
+    ///
+    /// Of a document, only the contents of `<body>` are kept.
+    pub fn sanitize(&self, html: &Html) -> String {
+        let root = html.root_element();
+        let body = root
+            .child_elements()
+            .find(|child| child.value().name.expanded() == expanded_name!(html "body"))
+            .unwrap_or(root);
+
+        let mut output = Html::parse_fragment("");
+        for child in body.children() {
+            self.copy(child, &mut output);
+        }
+        output.root_element().inner_html()
+    }
+
+    /// Returns the sanitized serialization of an element, including the element itself.
+    pub fn sanitize_element(&self, element: ElementRef<'_>) -> String {
+        let mut output = Html::parse_fragment("");
+        self.copy(*element, &mut output);
+        output.root_element().inner_html()
+    }
+
+    /// Appends the sanitized copy of the subtree rooted at `node` to the root element of
+    /// `output`.
+    fn copy(&self, node: ego_tree::NodeRef<'_, Node>, output: &mut Html) {
+        rebuild(node, output, |element| {
+            if self.drops_contents(element.value()) {
+                return Action::Skip;
+            }
+            match self.element(element.value()) {
+                Some(element) => Action::Copy(Box::new(element)),
+                None if self.disallowed == Disallowed::Unwrap => Action::Unwrap,
+                None => Action::Skip,
+            }
+        });
+    }
+
+    fn drops_contents(&self, element: &Element) -> bool {
+        self.drop_contents.contains(element.name())
+    }
+
+    /// Returns the sanitized copy of an element, or `None` if its tag is not allowed.
+    fn element(&self, element: &Element) -> Option<Element> {
+        if element.name.ns != ns!(html) || !self.tags.contains(element.name()) {
+            return None;
+        }
+
+        let tag = element.name();
+        let allowed = |name: &str| {
+            [tag, "*"].iter().any(|tag| {
+                self.attributes
+                    .get(*tag)
+                    .is_some_and(|attributes| attributes.contains(name))
+            })
+        };
+
+        let mut copy = Element::new(element.name.clone(), Vec::new());
+        for (name, value) in &element.attrs {
+            let local = &*name.local;
+            if name.ns != ns!() || local.starts_with("on") || !allowed(local) {
+                continue;
+            }
+
+            if self.url_attributes.contains(local) {
+                if matches!(local, "srcset" | "imagesrcset") {
+                    let srcset = self.srcset(value);
+                    if !srcset.is_empty() {
+                        copy.set_attr_ns(name.clone(), &srcset);
+                    }
+                    continue;
+                }
+                if !self.allows_url(value) {
+                    continue;
+                }
+            }
+
+            if local == "style" {
+                let style = self.style(value);
+                if !style.is_empty() {
+                    copy.set_attr(local, &style);
+                }
+                continue;
+            }
+
+            copy.set_attr_ns(name.clone(), value);
+        }
+
+        if let Some(link_rel) = &self.link_rel
+            && matches!(tag, "a" | "area")
+        {
+            let mut rel = copy
+                .attr("rel")
+                .unwrap_or_default()
+                .split_ascii_whitespace()
+                .map(str::to_owned)
+                .collect::<Vec<_>>();
+            for token in link_rel.split_ascii_whitespace() {
+                if !rel.iter().any(|t| t.eq_ignore_ascii_case(token)) {
+                    rel.push(token.to_owned());
+                }
+            }
+            copy.set_attr_ns(
+                QualName::new(None, ns!(), LocalName::from("rel")),
+                &rel.join(" "),
+            );
+        }
+
+        Some(copy)
+    }
+
+    /// Returns true if `url` is relative or uses an allowed scheme.
+    fn allows_url(&self, url: &str) -> bool {
+        // Browsers ignore leading and trailing control characters and spaces, and tabs and
+        // newlines anywhere, so `java\tscript:` is a `javascript:` URL.
+        let url = url
+            .trim_matches(|c: char| c <= ' ')
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .collect::<String>();
+
+        match url.find([':', '/', '?', '#']) {
+            Some(end) if url[end..].starts_with(':') => {
+                let scheme = url[..end].to_ascii_lowercase();
+                self.url_schemes.contains(&scheme)
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns the image candidates of a `srcset` attribute whose URLs are allowed.
+    fn srcset(&self, srcset: &str) -> String {
+        let mut candidates = Vec::new();
+        for (url, descriptors) in srcset_candidates(srcset) {
+            if self.allows_url(url) {
+                candidates.push(if descriptors.is_empty() {
+                    url.to_owned()
+                } else {
+                    format!("{url} {descriptors}")
+                });
+            }
+        }
+        candidates.join(", ")
+    }
+
+    /// Returns the allowed declarations of a `style` attribute.
+    fn style(&self, style: &str) -> String {
+        let mut declarations = Vec::new();
+        for declaration in style.split(';') {
+            let Some((property, value)) = declaration.split_once(':') else {
+                continue;
+            };
+            let property = property.trim().to_ascii_lowercase();
+            let value = value.trim();
+            let lowercase = value.to_ascii_lowercase();
+            let unsafe_value = ["url(", "expression(", "image-set(", "javascript:", "\\"]
+                .iter()
+                .any(|pattern| lowercase.contains(pattern));
+
+            if self.css_properties.contains(&property) && !value.is_empty() && !unsafe_value {
+                declarations.push(format!("{property}: {value}"));
+            }
+        }
+        declarations.join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Disallowed, Sanitizer};
+    use crate::{Html, Selector};
+
+    #[test]
+    fn strips_scripts_handlers_and_urls() {
+        let sanitizer = Sanitizer::default();
+        assert_eq!(
+            sanitizer.clean(
+                r#"<div onmouseover="x()"><script>x()</script><style>p{}</style>
+                <img src=" JaVa&#x09;Script:x()" alt="a"><img src="/i.png" onerror="x()">
+                <a href="https://example.com/" target="_blank" rel="author">ok</a></div>"#
+            ),
+            "<div>\n                <img alt=\"a\"><img src=\"/i.png\">\n                \
+             <a href=\"https://example.com/\" rel=\"noopener\">ok</a></div>"
+        );
+    }
+
+    #[test]
+    fn srcset_candidates_are_filtered() {
+        let mut sanitizer = Sanitizer::default();
+        sanitizer
+            .attributes
+            .get_mut("img")
+            .unwrap()
+            .insert("srcset".to_owned());
+
+        assert_eq!(
+            sanitizer.clean(
+                r#"<img srcset="javascript:x() 1x"><img srcset=" a.png, b.png 2x , JaVaScript:x() 3x,
+                https://example.com/c,d.png 100w,data:image/png;base64,AA== 4x">"#
+            ),
+            r#"<img><img srcset="a.png, b.png 2x, https://example.com/c,d.png 100w">"#
+        );
+    }
+
+    #[test]
+    fn unknown_tags_are_unwrapped_or_dropped() {
+        let input = "<custom><b>bold</b> text<svg><script>x()</script><text>svg</text></svg></custom><!-- c -->";
+
+        let mut sanitizer = Sanitizer::default();
+        assert_eq!(sanitizer.clean(input), "<b>bold</b> textsvg");
+
+        sanitizer.disallowed = Disallowed::Drop;
+        assert_eq!(sanitizer.clean(input), "");
+    }
+
+    #[test]
+    fn attributes_per_tag_and_rel() {
+        let mut sanitizer = Sanitizer::default();
+        sanitizer
+            .attributes
+            .get_mut("a")
+            .unwrap()
+            .insert("rel".to_owned());
+        sanitizer.link_rel = Some("noopener noreferrer".to_owned());
+
+        assert_eq!(
+            sanitizer.clean(
+                r#"<a href="mailto:a@b.c" lang="en" rel="author NoOpener">m</a><p href="/">p</p>"#
+            ),
+            r#"<a href="mailto:a@b.c" lang="en" rel="author NoOpener noreferrer">m</a><p>p</p>"#
+        );
+    }
+
+    #[test]
+    fn styles_are_filtered() {
+        let mut sanitizer = Sanitizer::default();
+        sanitizer
+            .attributes
+            .get_mut("*")
+            .unwrap()
+            .insert("style".to_owned());
+        sanitizer.css_properties.insert("color".to_owned());
+
+        assert_eq!(
+            sanitizer.clean(
+                r#"<p style="COLOR: red; position: fixed; color: x;background: url(a)">a</p><p style="color: u\72l(x)">b</p>"#
+            ),
+            r#"<p style="color: red; color: x">a</p><p>b</p>"#
+        );
+    }
+
+    #[test]
+    fn documents_and_elements() {
+        let sanitizer = Sanitizer::default();
+        let html = Html::parse_document(
+            "<title>t</title><body><p>a<template><p>t</p></template><i>b</i></p></body>",
+        );
+        assert_eq!(sanitizer.sanitize(&html), "<p>a<i>b</i></p>");
+
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+        assert_eq!(sanitizer.sanitize_element(p), "<p>a<i>b</i></p>");
+
+        let i = html.select(&Selector::parse("i").unwrap()).next().unwrap();
+        let mut sanitizer = sanitizer;
+        sanitizer.tags.remove("i");
+        assert_eq!(sanitizer.sanitize_element(i), "b");
+    }
+}
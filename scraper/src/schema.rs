@@ -0,0 +1,603 @@
+//! Extraction schemas, loaded at runtime.
+//!
+//! A [`Schema`] maps field names to a selector, an extraction mode, a [`Multiplicity`] and a
+//! pipeline of [`Process`] steps. Schemas deserialize from any serde format, so extraction rules
+//! can be stored as data, and extract to a [`serde_json::Value`] or, through a [`Deserializer`],
+//! to any type that implements [`Deserialize`]. [`Schema::validate`] reports the fields whose
+//! selectors match nothing, which usually means the markup of a site changed.
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//! use scraper::schema::Schema;
+//! use serde_json::json;
+//!
+//! let schema: Schema = serde_json::from_value(json!({
+//!     "name": { "selector": "h1", "process": ["trim"] },
+//!     "price": { "selector": ".price", "process": [{ "regex": "\\$(\\d+)" }, "number"] },
+//!     "image": { "selector": "img", "extract": { "attr": "src" }, "multiplicity": "optional" },
+//!     "reviews": {
+//!         "selector": ".review",
+//!         "multiplicity": "many",
+//!         "extract": { "schema": { "stars": { "selector": ".stars", "process": ["number"] } } },
+//!     },
+//! })).unwrap();
+//!
+//! let document = Html::parse_document(r#"
+//!     <h1> Teapot </h1>
+//!     <span class="price">Now $20</span>
+//!     <div class="review"><span class="stars">5</span></div>
+//!     <div class="review"><span class="stars">4</span></div>
+//! "#);
+//!
+//! assert_eq!(
+//!     schema.extract(&document).unwrap(),
+//!     json!({
+//!         "name": "Teapot",
+//!         "price": 20,
+//!         "image": null,
+//!         "reviews": [{ "stars": 5 }, { "stars": 4 }],
+//!     }),
+//! );
+//! assert_eq!(schema.validate(&document).unmatched[0].path, "image");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use cssparser::ToCss;
+use regex::Regex;
+use serde::Deserialize;
+use serde::de::{self, Visitor};
+use serde_json::{Map, Number, Value};
+
+use crate::error::{SchemaError, SchemaErrorKind};
+use crate::{ElementRef, Html, Selector};
+
+/// An extraction schema: the fields of the extracted object.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Schema {
+    /// The fields, by name.
+    pub fields: BTreeMap<String, Field>,
+}
+
+/// A field of a [`Schema`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    /// The elements the field is extracted from, among the descendants of the element being
+    /// extracted. Without a selector, the field is extracted from that element itself.
+    #[serde(default)]
+    pub selector: Option<Selector>,
+
+    /// How the value is taken from each element.
+    #[serde(default)]
+    pub extract: Extract,
+
+    /// How many values the field has.
+    #[serde(default)]
+    pub multiplicity: Multiplicity,
+
+    /// The steps applied to each value, in order.
+    #[serde(default)]
+    pub process: Vec<Process>,
+}
+
+/// How the value of a [`Field`] is taken from an element.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Extract {
+    /// The text of the element and its descendants.
+    #[default]
+    Text,
+
+    /// The value of an attribute.
+    Attr(String),
+
+    /// The HTML of the element.
+    Html,
+
+    /// The HTML of the children of the element.
+    InnerHtml,
+
+    /// An object extracted by a nested schema.
+    Schema(Schema),
+}
+
+/// How many values a [`Field`] has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Multiplicity {
+    /// The value of the first matching element, which is required.
+    #[default]
+    One,
+
+    /// The value of the first matching element, or `null`.
+    Optional,
+
+    /// An array of the values of all matching elements.
+    Many,
+}
+
+/// A step applied to an extracted value.
+///
+/// Values that lack an attribute, or that do not match a regular expression, are `null` if the
+/// field is optional and left out if it has many values.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Process {
+    /// Removes leading and trailing whitespace.
+    Trim,
+
+    /// Replaces the text with the first capture group of a regular expression, or the whole
+    /// match without capture groups.
+    Regex(#[serde(deserialize_with = "regex")] Regex),
+
+    /// Parses the text as a JSON number.
+    Number,
+}
+
+/// A report of the fields of a schema whose selectors matched nothing, see [`Schema::validate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The unmatched fields.
+    pub unmatched: Vec<Unmatched>,
+}
+
+/// A field whose selector matched nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unmatched {
+    /// The path of the field, such as `reviews.stars`.
+    pub path: String,
+
+    /// The selector of the field.
+    pub selector: String,
+}
+
+impl Report {
+    /// Returns true if the selectors of all fields matched.
+    pub fn is_empty(&self) -> bool {
+        self.unmatched.is_empty()
+    }
+}
+
+impl Schema {
+    /// Extracts an object from the root element of a document or fragment.
+    pub fn extract(&self, html: &Html) -> Result<Value, SchemaError> {
+        self.extract_element(html.root_element())
+    }
+
+    /// Extracts an object from an element.
+    pub fn extract_element(&self, element: ElementRef<'_>) -> Result<Value, SchemaError> {
+        self.object(element, "").map(Value::Object)
+    }
+
+    /// Extracts a value of any type that implements [`Deserialize`] from a document or fragment.
+    pub fn extract_as<T: de::DeserializeOwned>(&self, html: &Html) -> Result<T, SchemaError> {
+        T::deserialize(self.deserializer(html.root_element()))
+    }
+
+    /// Returns a deserializer of the object extracted from an element.
+    pub fn deserializer<'a>(&'a self, element: ElementRef<'a>) -> Deserializer<'a> {
+        Deserializer {
+            schema: self,
+            element,
+        }
+    }
+
+    /// Lists the fields whose selectors match nothing in a document or fragment.
+    ///
+    /// The fields of nested schemas are checked against all the elements their parent field
+    /// matches, and are not listed when their parent field is.
+    pub fn validate(&self, html: &Html) -> Report {
+        let mut report = Report::default();
+        self.unmatched(&[html.root_element()], "", &mut report);
+        report
+    }
+
+    fn object(
+        &self,
+        element: ElementRef<'_>,
+        path: &str,
+    ) -> Result<Map<String, Value>, SchemaError> {
+        let mut object = Map::new();
+        for (name, field) in &self.fields {
+            let path = join(path, name);
+            let value = field.values(element, &path)?;
+            object.insert(name.clone(), value);
+        }
+        Ok(object)
+    }
+
+    fn unmatched(&self, elements: &[ElementRef<'_>], path: &str, report: &mut Report) {
+        for (name, field) in &self.fields {
+            let path = join(path, name);
+            let matches = match &field.selector {
+                Some(selector) => elements
+                    .iter()
+                    .flat_map(|element| element.select(selector))
+                    .collect(),
+                None => elements.to_vec(),
+            };
+
+            if matches.is_empty() {
+                report.unmatched.push(Unmatched {
+                    path,
+                    selector: field
+                        .selector
+                        .as_ref()
+                        .map_or_else(String::new, |selector| selector.to_css_string()),
+                });
+            } else if let Extract::Schema(schema) = &field.extract {
+                schema.unmatched(&matches, &path, report);
+            }
+        }
+    }
+}
+
+impl Field {
+    fn values(&self, element: ElementRef<'_>, path: &str) -> Result<Value, SchemaError> {
+        let mut elements: Box<dyn Iterator<Item = ElementRef<'_>>> = match &self.selector {
+            Some(selector) => Box::new(element.select(selector)),
+            None => Box::new(std::iter::once(element)),
+        };
+
+        match self.multiplicity {
+            Multiplicity::One => match elements.next() {
+                Some(element) => self.value(element, path),
+                None => Err(self.error(path, SchemaErrorKind::NotFound)),
+            },
+            Multiplicity::Optional => {
+                match elements.next().map(|element| self.value(element, path)) {
+                    Some(Err(error)) if !is_missing(&error, path) => Err(error),
+                    Some(Ok(value)) => Ok(value),
+                    _ => Ok(Value::Null),
+                }
+            }
+            Multiplicity::Many => {
+                let mut values = Vec::new();
+                for (index, element) in elements.enumerate() {
+                    let path = format!("{path}[{index}]");
+                    match self.value(element, &path) {
+                        Ok(value) => values.push(value),
+                        Err(error) if is_missing(&error, &path) => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+        }
+    }
+
+    fn value(&self, element: ElementRef<'_>, path: &str) -> Result<Value, SchemaError> {
+        let mut value = match &self.extract {
+            Extract::Text => Value::String(element.text().collect()),
+            Extract::Attr(name) => match element.attr(name) {
+                Some(value) => Value::String(value.to_owned()),
+                None => {
+                    return Err(self.error(path, SchemaErrorKind::MissingAttribute(name.clone())));
+                }
+            },
+            Extract::Html => Value::String(element.html()),
+            Extract::InnerHtml => Value::String(element.inner_html()),
+            Extract::Schema(schema) => return schema.object(element, path).map(Value::Object),
+        };
+
+        for process in &self.process {
+            value = process
+                .apply(value)
+                .map_err(|kind| self.error(path, kind))?;
+        }
+        Ok(value)
+    }
+
+    fn error(&self, path: &str, kind: SchemaErrorKind) -> SchemaError {
+        SchemaError {
+            path: path.to_owned(),
+            selector: self.selector.as_ref().map(ToCss::to_css_string),
+            kind,
+        }
+    }
+}
+
+impl Process {
+    fn apply(&self, value: Value) -> Result<Value, SchemaErrorKind> {
+        let Value::String(text) = value else {
+            return Err(SchemaErrorKind::NotText);
+        };
+
+        match self {
+            Self::Trim => Ok(Value::String(text.trim().to_owned())),
+            Self::Regex(regex) => {
+                let captures = regex
+                    .captures(&text)
+                    .ok_or_else(|| SchemaErrorKind::NoMatch(regex.as_str().to_owned()))?;
+                let capture = captures.get(1).or_else(|| captures.get(0)).unwrap();
+                Ok(Value::String(capture.as_str().to_owned()))
+            }
+            Self::Number => {
+                let text = text.trim();
+                let number = match text.parse::<i64>() {
+                    Ok(integer) => Some(Number::from(integer)),
+                    Err(_) => text.parse().ok().and_then(Number::from_f64),
+                };
+                number
+                    .map(Value::Number)
+                    .ok_or_else(|| SchemaErrorKind::InvalidNumber(text.to_owned()))
+            }
+        }
+    }
+}
+
+/// A [`serde::Deserializer`] of the object extracted by a schema, see [`Schema::deserializer`].
+#[derive(Debug, Clone, Copy)]
+pub struct Deserializer<'a> {
+    schema: &'a Schema,
+    element: ElementRef<'a>,
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_> {
+    type Error = SchemaError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+        self.schema
+            .extract_element(self.element)?
+            .deserialize_any(visitor)
+            .map_err(de::Error::custom)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Returns true for the errors of values that are left out of optional and repeated fields. Only
+/// the errors of the field at `path` itself count, not those of the fields of a nested schema.
+fn is_missing(error: &SchemaError, path: &str) -> bool {
+    error.path == path
+        && matches!(
+            error.kind,
+            SchemaErrorKind::MissingAttribute(_) | SchemaErrorKind::NoMatch(_)
+        )
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+fn regex<'de, D: de::Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    deserializer.deserialize_str(RegexVisitor)
+}
+
+struct RegexVisitor;
+
+impl Visitor<'_> for RegexVisitor {
+    type Value = Regex;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a regular expression string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Regex::new(v).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::{Schema, Unmatched};
+    use crate::Html;
+    use crate::error::{SchemaError, SchemaErrorKind};
+
+    fn schema(value: serde_json::Value) -> Schema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    const DOCUMENT: &str = r#"
+        <h1> Kettle </h1>
+        <a href="/a">A</a><a>B</a><a href="/c">C</a>
+        <ul><li>1.5</li><li>2</li></ul>
+        <article id="one"><h2>First</h2><time>2024-01-02</time></article>
+        <article id="two"><h2>Second</h2></article>
+    "#;
+
+    #[test]
+    fn extract_modes() {
+        let schema = schema(json!({
+            "title": { "selector": "h1", "process": ["trim"] },
+            "links": { "selector": "a", "extract": { "attr": "href" }, "multiplicity": "many" },
+            "numbers": { "selector": "li", "process": ["number"], "multiplicity": "many" },
+            "list": { "selector": "ul", "extract": "inner_html" },
+            "first": { "selector": "li", "extract": "html" },
+            "articles": {
+                "selector": "article",
+                "multiplicity": "many",
+                "extract": { "schema": {
+                    "id": { "extract": { "attr": "id" } },
+                    "heading": { "selector": "h2" },
+                    "year": {
+                        "selector": "time",
+                        "multiplicity": "optional",
+                        "process": [{ "regex": "^(\\d{4})-" }, "number"],
+                    },
+                } },
+            },
+        }));
+
+        let value = schema.extract(&Html::parse_document(DOCUMENT)).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "title": "Kettle",
+                "links": ["/a", "/c"],
+                "numbers": [1.5, 2],
+                "list": "<li>1.5</li><li>2</li>",
+                "first": "<li>1.5</li>",
+                "articles": [
+                    { "id": "one", "heading": "First", "year": 2024 },
+                    { "id": "two", "heading": "Second", "year": null },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let html = Html::parse_document(DOCUMENT);
+
+        let error = schema(json!({ "name": { "selector": "h3" } }))
+            .extract(&html)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            SchemaError {
+                path: "name".to_owned(),
+                selector: Some("h3".to_owned()),
+                kind: SchemaErrorKind::NotFound,
+            }
+        );
+
+        let error = schema(json!({
+            "articles": {
+                "selector": "article",
+                "multiplicity": "many",
+                "extract": { "schema": { "heading": { "selector": "h2", "process": ["number"] } } },
+            },
+        }))
+        .extract(&html)
+        .unwrap_err();
+        assert_eq!(error.path, "articles[0].heading");
+        assert_eq!(
+            error.kind,
+            SchemaErrorKind::InvalidNumber("First".to_owned())
+        );
+        assert_eq!(
+            error.to_string(),
+            "Cannot extract `articles[0].heading` from `h2`: \"First\" is not a number"
+        );
+
+        let html = Html::parse_fragment("<div class=author><a>Ann</a></div>");
+        for (multiplicity, path) in [("optional", "author.name"), ("many", "author[0].name")] {
+            let error = schema(json!({
+                "author": {
+                    "selector": ".author",
+                    "multiplicity": multiplicity,
+                    "extract": { "schema": { "name": { "selector": "a", "extract": { "attr": "title" } } } },
+                },
+            }))
+            .extract(&html)
+            .unwrap_err();
+            assert_eq!(error.path, path);
+            assert_eq!(
+                error.kind,
+                SchemaErrorKind::MissingAttribute("title".to_owned())
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_schema() {
+        let error = serde_json::from_value::<Schema>(json!({ "a": { "selector": "div >" } }));
+        assert!(error.is_err());
+        let error =
+            serde_json::from_value::<Schema>(json!({ "a": { "process": [{ "regex": "(" }] } }));
+        assert!(error.is_err());
+        let error = serde_json::from_value::<Schema>(json!({ "a": { "selectr": "div" } }));
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn deserialize() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Page {
+            title: String,
+            articles: Vec<Article>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Article {
+            heading: String,
+            date: Option<String>,
+        }
+
+        let schema = schema(json!({
+            "title": { "selector": "h1", "process": ["trim"] },
+            "articles": {
+                "selector": "article",
+                "multiplicity": "many",
+                "extract": { "schema": {
+                    "heading": { "selector": "h2" },
+                    "date": { "selector": "time", "multiplicity": "optional" },
+                } },
+            },
+        }));
+
+        let html = Html::parse_document(DOCUMENT);
+        let page: Page = schema.extract_as(&html).unwrap();
+        assert_eq!(
+            page,
+            Page {
+                title: "Kettle".to_owned(),
+                articles: vec![
+                    Article {
+                        heading: "First".to_owned(),
+                        date: Some("2024-01-02".to_owned()),
+                    },
+                    Article {
+                        heading: "Second".to_owned(),
+                        date: None,
+                    },
+                ],
+            }
+        );
+
+        let error = schema.extract_as::<Vec<String>>(&html).unwrap_err();
+        assert!(matches!(error.kind, SchemaErrorKind::Deserialize(_)));
+    }
+
+    #[test]
+    fn validate() {
+        let schema = schema(json!({
+            "title": { "selector": "h1" },
+            "subtitle": { "selector": "h1 + p" },
+            "articles": {
+                "selector": "article",
+                "multiplicity": "many",
+                "extract": { "schema": {
+                    "date": { "selector": "time" },
+                    "author": { "selector": ".author" },
+                } },
+            },
+            "comments": {
+                "selector": ".comment",
+                "extract": { "schema": { "text": { "selector": "p" } } },
+            },
+        }));
+
+        let report = schema.validate(&Html::parse_document(DOCUMENT));
+        let unmatched = |path: &str, selector: &str| Unmatched {
+            path: path.to_owned(),
+            selector: selector.to_owned(),
+        };
+        assert_eq!(
+            report.unmatched,
+            [
+                unmatched("articles.author", ".author"),
+                unmatched("comments", ".comment"),
+                unmatched("subtitle", "h1 + p"),
+            ]
+        );
+        assert!(!report.is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+//! Declarative extraction into Rust types.
+//!
+//! [`Scrape`] is implemented by types that can be extracted from an element. With the `derive`
+//! feature, it can be derived for structs whose fields are annotated with a selector and an
+//! extraction mode, see [the derive macro](derive@Scrape) for the attributes it takes. Selectors
+//! are checked when the struct is compiled, and values are converted with [`FromStr`] or a custom
+//! function.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "derive")] {
+//! use scraper::Html;
+//! use scraper::scrape::Scrape;
+//!
+//! #[derive(Scrape)]
+//! struct Product {
+//!     #[scrape(select = "h1")]
+//!     name: String,
+//!     #[scrape(select = ".price", with = parse_price)]
+//!     price: u32,
+//!     #[scrape(select = "img", attr = "src")]
+//!     image: Option<String>,
+//!     #[scrape(select = ".review", nested)]
+//!     reviews: Vec<Review>,
+//! }
+//!
+//! #[derive(Scrape)]
+//! struct Review {
+//!     #[scrape(select = ".stars")]
+//!     stars: u8,
+//!     #[scrape(select = "p", inner_html)]
+//!     body: String,
+//! }
+//!
+//! fn parse_price(text: &str) -> Result<u32, std::num::ParseIntError> {
+//!     text.trim_start_matches('$').parse()
+//! }
+//!
+//! let document = Html::parse_document(r#"
+//!     <h1>Teapot</h1>
+//!     <span class="price">$20</span>
+//!     <div class="review"><span class="stars">5</span><p>Short and <em>stout</em></p></div>
+//!     <div class="review"><span class="stars">4</span><p>Leaks a little</p></div>
+//! "#);
+//!
+//! let product = Product::from_html(&document).unwrap();
+//! assert_eq!(product.name, "Teapot");
+//! assert_eq!(product.price, 20);
+//! assert_eq!(product.image, None);
+//! assert_eq!(product.reviews.len(), 2);
+//! assert_eq!(product.reviews[0].stars, 5);
+//! assert_eq!(product.reviews[0].body, "Short and <em>stout</em>");
+//! # }
+//! ```
+//!
+//! [`FromStr`]: std::str::FromStr
+
+use crate::error::ScrapeError;
+use crate::{ElementRef, Html};
+
+#[cfg(feature = "derive")]
+pub use scraper_derive::Scrape;
+
+/// A type that can be extracted from an element.
+pub trait Scrape: Sized {
+    /// Extracts a value from an element.
+    fn from_element(element: ElementRef<'_>) -> Result<Self, ScrapeError>;
+
+    /// Extracts a value from the root element of a document or fragment.
+    fn from_html(html: &Html) -> Result<Self, ScrapeError> {
+        Self::from_element(html.root_element())
+    }
+}
+
+/// Support for the code generated by the derive macro, not public API.
+#[doc(hidden)]
+pub mod __private {
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub use std::sync::OnceLock;
+
+    use super::Scrape;
+    use crate::error::ScrapeErrorKind;
+    use crate::{ElementRef, Selector};
+
+    pub fn selector(cell: &'static OnceLock<Selector>, css: &str) -> &'static Selector {
+        cell.get_or_init(|| Selector::parse(css).expect("selector is checked by the derive macro"))
+    }
+
+    pub fn text(element: ElementRef<'_>) -> String {
+        element.text().collect::<String>().trim().to_owned()
+    }
+
+    pub fn attr<'a>(
+        element: ElementRef<'a>,
+        name: &'static str,
+    ) -> Result<&'a str, ScrapeErrorKind> {
+        element
+            .attr(name)
+            .ok_or(ScrapeErrorKind::MissingAttribute(name))
+    }
+
+    pub fn parse<T>(value: &str) -> Result<T, ScrapeErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        convert(value.parse())
+    }
+
+    pub fn convert<T, E: Display>(result: Result<T, E>) -> Result<T, ScrapeErrorKind> {
+        result.map_err(|error| ScrapeErrorKind::Parse(error.to_string()))
+    }
+
+    pub fn nested<T: Scrape>(element: ElementRef<'_>) -> Result<T, ScrapeErrorKind> {
+        T::from_element(element).map_err(|error| ScrapeErrorKind::Nested(Box::new(error)))
+    }
+
+    /// Extracts a value from the first element, which is required.
+    pub fn one<'a, T>(
+        mut elements: impl Iterator<Item = ElementRef<'a>>,
+        extract: impl FnOnce(ElementRef<'a>) -> Result<T, ScrapeErrorKind>,
+    ) -> Result<T, ScrapeErrorKind> {
+        extract(elements.next().ok_or(ScrapeErrorKind::NotFound)?)
+    }
+
+    /// Extracts a value from the first element, if there is one and it has the attribute.
+    pub fn optional<'a, T>(
+        mut elements: impl Iterator<Item = ElementRef<'a>>,
+        extract: impl FnOnce(ElementRef<'a>) -> Result<T, ScrapeErrorKind>,
+    ) -> Result<Option<T>, ScrapeErrorKind> {
+        match elements.next().map(extract) {
+            None | Some(Err(ScrapeErrorKind::MissingAttribute(_))) => Ok(None),
+            Some(result) => result.map(Some),
+        }
+    }
+
+    /// Extracts values from all elements that have the attribute.
+    pub fn all<'a, T>(
+        elements: impl Iterator<Item = ElementRef<'a>>,
+        extract: impl FnMut(ElementRef<'a>) -> Result<T, ScrapeErrorKind>,
+    ) -> Result<Vec<T>, ScrapeErrorKind> {
+        elements
+            .map(extract)
+            .filter(|result| !matches!(result, Err(ScrapeErrorKind::MissingAttribute(_))))
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use std::error::Error;
+
+    use super::Scrape;
+    use crate::Html;
+    use crate::error::{ScrapeError, ScrapeErrorKind};
+
+    #[derive(Debug, Scrape)]
+    struct Page {
+        #[scrape(select = "title")]
+        title: String,
+        #[scrape(select = "link[rel=canonical]", attr = "href")]
+        canonical: Option<String>,
+        #[scrape(select = "meta[name=rating]", attr = "content")]
+        rating: Option<f32>,
+        #[scrape(select = "ul.tags > li")]
+        tags: Vec<String>,
+        #[scrape(select = "a", attr = "href")]
+        links: Vec<String>,
+        #[scrape(select = "article", nested)]
+        articles: Vec<Article>,
+    }
+
+    #[derive(Debug, Scrape)]
+    struct Article {
+        #[scrape(attr = "id")]
+        id: String,
+        #[scrape(select = "h2")]
+        heading: String,
+        #[scrape(select = ".words", with = parse_count)]
+        words: usize,
+        #[scrape(select = ".body", html)]
+        body: String,
+        #[scrape(select = ".body", inner_html)]
+        inner: String,
+    }
+
+    fn parse_count(text: &str) -> Result<usize, std::num::ParseIntError> {
+        text.trim_end_matches(" words").parse()
+    }
+
+    const DOCUMENT: &str = r#"
+        <title> Example </title>
+        <meta name="rating" content="4.5">
+        <ul class="tags"><li>one</li><li>two</li></ul>
+        <a href="/a">A</a><a name="anchor">B</a><a href="/c">C</a>
+        <article id="first">
+            <h2>First</h2><span class="words">120 words</span>
+            <div class="body"><b>Hi</b></div>
+        </article>
+    "#;
+
+    #[test]
+    fn extracts_fields() {
+        let page = Page::from_html(&Html::parse_document(DOCUMENT)).unwrap();
+        assert_eq!(page.title, "Example");
+        assert_eq!(page.canonical, None);
+        assert_eq!(page.rating, Some(4.5));
+        assert_eq!(page.tags, ["one", "two"]);
+        assert_eq!(page.links, ["/a", "/c"]);
+
+        let article = &page.articles[0];
+        assert_eq!(article.id, "first");
+        assert_eq!(article.heading, "First");
+        assert_eq!(article.words, 120);
+        assert_eq!(article.body, r#"<div class="body"><b>Hi</b></div>"#);
+        assert_eq!(article.inner, "<b>Hi</b>");
+    }
+
+    #[test]
+    fn missing_element() {
+        let error = Page::from_html(&Html::parse_fragment("<p>No title</p>")).unwrap_err();
+        assert_eq!(
+            error,
+            ScrapeError {
+                ty: "Page",
+                field: "title",
+                selector: Some("title"),
+                kind: ScrapeErrorKind::NotFound,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Cannot extract `Page::title` from `title`: no element matches"
+        );
+    }
+
+    #[test]
+    fn invalid_value() {
+        let document = DOCUMENT.replace("4.5", "great");
+        let error = Page::from_html(&Html::parse_document(&document)).unwrap_err();
+        assert_eq!(error.field, "rating");
+        assert_eq!(
+            error.kind,
+            ScrapeErrorKind::Parse("invalid float literal".to_owned())
+        );
+    }
+
+    #[test]
+    fn nested_error() {
+        let document = DOCUMENT.replace("120 words", "many words");
+        let error = Page::from_html(&Html::parse_document(&document)).unwrap_err();
+        assert_eq!(error.field, "articles");
+        assert!(matches!(error.kind, ScrapeErrorKind::Nested(_)));
+
+        let source = error
+            .source()
+            .unwrap()
+            .downcast_ref::<ScrapeError>()
+            .unwrap();
+        assert_eq!((source.ty, source.field), ("Article", "words"));
+        assert_eq!(source.selector, Some(".words"));
+    }
+}
@@ -52,10 +52,10 @@ impl Selector {
     // The `nth_index_cache` must not be used after `self` is dropped
     // to avoid incorrect results (even though no undefined behaviour is possible)
     // due to the usage of selector memory addresses as cache keys.
-    pub(crate) fn matches_with_scope_and_cache(
+    pub(crate) fn matches_with_scope_and_cache<E: selectors::Element<Impl = Simple>>(
         &self,
-        element: &ElementRef,
-        scope: Option<ElementRef>,
+        element: &E,
+        scope: Option<E>,
         caches: &mut matching::SelectorCaches,
     ) -> bool {
         let mut context = matching::MatchingContext::new(
@@ -72,6 +72,33 @@ impl Selector {
             .iter()
             .any(|s| matching::matches_selector(s, 0, None, element, &mut context))
     }
+
+    /// Returns true if matching an element only ever looks at the element and its ancestors,
+    /// i.e. there are no sibling combinators, no `:has()`, `:empty` or `:nth-*()` pseudo-classes.
+    #[cfg(feature = "stream")]
+    pub(crate) fn is_ancestral(&self) -> bool {
+        use selectors::parser::Component;
+
+        fn list(selectors: &SelectorList<Simple>) -> bool {
+            selectors.slice().iter().all(|selector| {
+                selector
+                    .iter_raw_match_order()
+                    .all(|component| match component {
+                        Component::Combinator(combinator) => !combinator.is_sibling(),
+                        Component::Empty
+                        | Component::Nth(_)
+                        | Component::NthOf(_)
+                        | Component::Has(_) => false,
+                        Component::Negation(inner)
+                        | Component::Is(inner)
+                        | Component::Where(inner) => list(inner),
+                        _ => true,
+                    })
+            })
+        }
+
+        list(&self.selectors)
+    }
 }
 
 impl ToCss for Selector {
@@ -0,0 +1,535 @@
+//! Source locations of parsed elements.
+//!
+//! With the `spans` feature enabled, [`Html::parse_document`] and [`Html::parse_fragment`] record
+//! where each element's markup was found in the input, which is then available through
+//! [`ElementRef::source_span`].
+//!
+//! Only elements that were produced by a tag in the input have a span. Elements implied by the
+//! parser, such as a missing `<html>` or `<body>`, and elements recreated by the adoption agency
+//! algorithm do not. Text, comments, doctypes and processing instructions are not recorded either:
+//! text nodes are merged and moved by the parser, so their markup is located through the span of
+//! the enclosing element instead.
+//!
+//! [`Html::parse_document`]: crate::Html::parse_document
+//! [`Html::parse_fragment`]: crate::Html::parse_fragment
+//! [`ElementRef::source_span`]: crate::ElementRef::source_span
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ego_tree::NodeId;
+use html5ever::driver::ParseOpts;
+use html5ever::tokenizer::{
+    Tag, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts,
+};
+use html5ever::tree_builder::{TreeBuilder, create_element};
+use html5ever::{LocalName, QualName, TokenizerResult};
+use tendril::StrTendril;
+
+use crate::error::LimitExceeded;
+use crate::html::LimitedTreeSink;
+use crate::{Html, Node};
+
+/// A position in the parsed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourcePosition {
+    /// The byte offset from the start of the input.
+    pub offset: usize,
+
+    /// The line number, starting at 1.
+    pub line: usize,
+
+    /// The column number in characters, starting at 1.
+    pub column: usize,
+}
+
+/// The location of an element's markup in the parsed input.
+///
+/// The span starts at the `<` of the start tag and ends after the `>` of the matching end tag.
+/// Void elements end with their start tag. Elements closed implicitly end where the markup that
+/// closed them starts, and elements left open at the end of the input extend to its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceSpan {
+    /// The start of the start tag.
+    pub start: SourcePosition,
+
+    /// The end of the start tag.
+    pub start_tag_end: SourcePosition,
+
+    /// The end of the element, including its end tag if it has one.
+    pub end: SourcePosition,
+}
+
+impl SourceSpan {
+    /// Returns the byte range of the element's markup, suitable for slicing the input.
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// Returns the byte range of the element's start tag.
+    pub fn start_tag_range(&self) -> Range<usize> {
+        self.start.offset..self.start_tag_end.offset
+    }
+}
+
+/// Parses `input` as a document, or as a fragment in the context of `context`, recording element
+/// source spans.
+pub(crate) fn parse(
+    input: &str,
+    sink: LimitedTreeSink,
+    opts: ParseOpts,
+    context: Option<QualName>,
+) -> (Html, Option<LimitExceeded>) {
+    let (tree_builder, opts) = match context {
+        Some(context) => {
+            let context = create_element(&sink, context, Vec::new());
+            let tree_builder =
+                TreeBuilder::new_for_fragment(sink, context, None, opts.tree_builder);
+            let tokenizer_opts = TokenizerOpts {
+                initial_state: Some(tree_builder.tokenizer_state_for_context_elem(false)),
+                ..opts.tokenizer
+            };
+            (tree_builder, tokenizer_opts)
+        }
+        None => (TreeBuilder::new(sink, opts.tree_builder), opts.tokenizer),
+    };
+
+    let tokenizer = Tokenizer::new(SpanTracker::new(input, tree_builder), opts);
+    let queue = tokenizer.sink.inner.sink.input();
+
+    // Tags are emitted when their closing `>` is consumed. Feeding the input in chunks that end
+    // right after each `>` means the tracker knows exactly where every tag token ends. Chunks
+    // also end before each `<`, so that errors in text are not attributed to the following tag.
+    let mut start = 0;
+    // The end of the next `>`, which is only searched for again once it has been fed.
+    let mut tag_end = 0;
+    while start < input.len() && !tokenizer.sink.inner.sink.is_stopped() {
+        if tag_end <= start {
+            tag_end = input[start..]
+                .find('>')
+                .map_or(usize::MAX, |idx| start + idx + 1);
+        }
+        let end = input.as_bytes()[start + 1..]
+            .iter()
+            .position(|&byte| byte == b'<')
+            .map_or(input.len(), |idx| start + 1 + idx)
+            .min(tag_end);
+        tokenizer.sink.offset.set(end);
+        queue.push_back(StrTendril::from_slice(&input[start..end]));
+        while !matches!(tokenizer.feed(queue), TokenizerResult::Done) {}
+        #[cfg(feature = "errors")]
+        tokenizer.sink.skip_errors();
+        start = end;
+    }
+    tokenizer.end();
+
+    let aborted = tokenizer.sink.inner.sink.aborted();
+    (tokenizer.sink.finish(), aborted)
+}
+
+/// Byte ranges recorded for an element while parsing.
+#[derive(Debug, Clone, Copy)]
+struct Recorded {
+    start: usize,
+    start_tag_end: usize,
+    end: Option<usize>,
+}
+
+/// Wraps the tree builder to attribute tag tokens to the elements they create.
+struct SpanTracker<'a> {
+    input: &'a str,
+    index: LineIndex<'a>,
+    inner: TreeBuilder<NodeId, LimitedTreeSink>,
+    /// The end of the chunk currently being tokenized.
+    offset: Cell<usize>,
+    /// The end of the last tag, comment or doctype token.
+    prev_end: Cell<usize>,
+    /// Elements whose end has not been seen yet, outermost first.
+    open: RefCell<Vec<(NodeId, LocalName)>>,
+    recorded: RefCell<HashMap<NodeId, Recorded>>,
+    /// The number of parse errors that have been attributed to a position, or given up on.
+    #[cfg(feature = "errors")]
+    located_errors: Cell<usize>,
+}
+
+impl<'a> SpanTracker<'a> {
+    fn new(input: &'a str, inner: TreeBuilder<NodeId, LimitedTreeSink>) -> Self {
+        Self {
+            input,
+            index: LineIndex::new(input),
+            inner,
+            offset: Cell::new(0),
+            prev_end: Cell::new(0),
+            open: RefCell::new(Vec::new()),
+            recorded: RefCell::new(HashMap::new()),
+            #[cfg(feature = "errors")]
+            located_errors: Cell::new(0),
+        }
+    }
+
+    /// Attributes the parse errors reported since the last call to `offset`.
+    #[cfg(feature = "errors")]
+    fn locate_errors(&self, offset: usize) {
+        let position = self.index.position(offset);
+        let mut html = self.inner.sink.sink.0.borrow_mut();
+        for error in &mut html.parse_errors[self.located_errors.get()..] {
+            error.line = position.line as u64;
+            error.column = Some(position.column as u64);
+        }
+        self.located_errors.set(html.parse_errors().len());
+    }
+
+    /// Leaves the parse errors reported so far without a column.
+    #[cfg(feature = "errors")]
+    fn skip_errors(&self) {
+        self.located_errors
+            .set(self.inner.sink.sink.0.borrow().parse_errors().len());
+    }
+
+    /// Finds the `<` that started the token which ends at the current offset.
+    fn token_start(&self, token: &Token) -> Option<usize> {
+        if !matches!(
+            token,
+            Token::TagToken(_) | Token::CommentToken(_) | Token::DoctypeToken(_)
+        ) {
+            return None;
+        }
+
+        let end = self.offset.get();
+        let prev_end = self.prev_end.get().min(end);
+        let bytes = &self.input.as_bytes()[..end];
+
+        let is_start = |idx: usize| match token {
+            Token::TagToken(tag) => {
+                let name_at = match tag.kind {
+                    TagKind::StartTag => idx + 1,
+                    TagKind::EndTag if bytes.get(idx + 1) == Some(&b'/') => idx + 2,
+                    TagKind::EndTag => return false,
+                };
+                let name = tag.name.as_bytes();
+                bytes
+                    .get(name_at..name_at + name.len())
+                    .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+                    && bytes.get(name_at + name.len()).is_none_or(|&byte| {
+                        byte.is_ascii_whitespace() || byte == b'/' || byte == b'>'
+                    })
+            }
+            _ => matches!(bytes.get(idx + 1), Some(b'!' | b'?' | b'/')),
+        };
+
+        (prev_end..end)
+            .filter(|&idx| bytes[idx] == b'<')
+            .find(|&idx| is_start(idx))
+            .or_else(|| {
+                bytes[prev_end..end]
+                    .iter()
+                    .rposition(|&byte| byte == b'<')
+                    .map(|idx| prev_end + idx)
+            })
+    }
+
+    fn last_node(&self) -> NodeId {
+        let html = self.inner.sink.sink.0.borrow();
+        html.tree.nodes().next_back().unwrap().id()
+    }
+
+    /// Returns the last element named `name` created after `last`.
+    fn created_element(&self, last: NodeId, name: &LocalName) -> Option<NodeId> {
+        let html = self.inner.sink.sink.0.borrow();
+        html.tree
+            .nodes()
+            .rev()
+            .take_while(|node| node.id() > last)
+            .find(|node| {
+                node.value()
+                    .as_element()
+                    .is_some_and(|element| element.name.local.eq_ignore_ascii_case(name))
+            })
+            .map(|node| node.id())
+    }
+
+    fn start_tag(&self, tag: &Tag, last: NodeId, start: usize, end: usize) {
+        let Some(id) = self.created_element(last, &tag.name) else {
+            return;
+        };
+
+        let mut recorded = self.recorded.borrow_mut();
+        let mut open = self.open.borrow_mut();
+
+        // Elements that are not ancestors of the new element were closed implicitly by its tag.
+        let foreign = {
+            let html = self.inner.sink.sink.0.borrow();
+            let node = html.tree.get(id).unwrap();
+            while let Some((open_id, _)) = open.last() {
+                if node.ancestors().any(|ancestor| ancestor.id() == *open_id) {
+                    break;
+                }
+                if let Some(entry) = recorded.get_mut(open_id) {
+                    entry.end = Some(start);
+                }
+                open.pop();
+            }
+            node.value().as_element().unwrap().name.ns != ns!(html)
+        };
+
+        let void = is_void(&tag.name) || tag.self_closing && foreign;
+        recorded.insert(
+            id,
+            Recorded {
+                start,
+                start_tag_end: end,
+                end: void.then_some(end),
+            },
+        );
+        if !void {
+            open.push((id, tag.name.clone()));
+        }
+    }
+
+    fn end_tag(&self, tag: &Tag, last: NodeId, start: usize, end: usize) {
+        let mut recorded = self.recorded.borrow_mut();
+
+        // Some end tags such as `</p>` and `</br>` create an element when nothing matches.
+        if let Some(id) = self.created_element(last, &tag.name) {
+            recorded.insert(
+                id,
+                Recorded {
+                    start,
+                    start_tag_end: end,
+                    end: Some(end),
+                },
+            );
+            return;
+        }
+
+        let mut open = self.open.borrow_mut();
+        let Some(idx) = open
+            .iter()
+            .rposition(|(_, name)| name.eq_ignore_ascii_case(&tag.name))
+        else {
+            return;
+        };
+
+        for (id, _) in open.drain(idx + 1..) {
+            if let Some(entry) = recorded.get_mut(&id) {
+                entry.end = Some(start);
+            }
+        }
+        let (id, _) = open.pop().unwrap();
+        if let Some(entry) = recorded.get_mut(&id) {
+            entry.end = Some(end);
+        }
+    }
+
+    fn finish(self) -> Html {
+        let mut html = self.inner.sink.sink.0.into_inner();
+        let index = self.index;
+        let input_end = self.input.len();
+
+        for (id, recorded) in self.recorded.into_inner() {
+            if let Some(Node::Element(element)) = html.tree.get_mut(id).as_mut().map(|n| n.value())
+            {
+                element.source_span = Some(SourceSpan {
+                    start: index.position(recorded.start),
+                    start_tag_end: index.position(recorded.start_tag_end),
+                    end: index.position(recorded.end.unwrap_or(input_end)),
+                });
+            }
+        }
+
+        html
+    }
+}
+
+fn is_void(name: &LocalName) -> bool {
+    matches!(
+        *name,
+        local_name!("area")
+            | local_name!("base")
+            | local_name!("basefont")
+            | local_name!("bgsound")
+            | local_name!("br")
+            | local_name!("col")
+            | local_name!("embed")
+            | local_name!("frame")
+            | local_name!("hr")
+            | local_name!("img")
+            | local_name!("input")
+            | local_name!("keygen")
+            | local_name!("link")
+            | local_name!("meta")
+            | local_name!("param")
+            | local_name!("source")
+            | local_name!("track")
+            | local_name!("wbr")
+    )
+}
+
+impl TokenSink for SpanTracker<'_> {
+    type Handle = NodeId;
+
+    fn process_token(&self, token: Token, line_number: u64) -> TokenSinkResult<NodeId> {
+        let end = self.offset.get();
+        let start = self.token_start(&token);
+        let tag = match token {
+            Token::TagToken(ref tag) => Some(tag.clone()),
+            _ => None,
+        };
+        let last = self.last_node();
+
+        let result = self.inner.process_token(token, line_number);
+
+        if let Some(start) = start {
+            self.prev_end.set(end);
+            #[cfg(feature = "errors")]
+            self.locate_errors(start);
+
+            match tag {
+                Some(ref tag) if tag.kind == TagKind::StartTag => {
+                    self.start_tag(tag, last, start, end)
+                }
+                Some(ref tag) => self.end_tag(tag, last, start, end),
+                None => (),
+            }
+        }
+
+        result
+    }
+
+    fn end(&self) {
+        self.inner.end();
+        #[cfg(feature = "errors")]
+        self.locate_errors(self.input.len());
+    }
+
+    fn adjusted_current_node_present_but_not_in_html_namespace(&self) -> bool {
+        self.inner
+            .adjusted_current_node_present_but_not_in_html_namespace()
+    }
+}
+
+/// Maps byte offsets to line and column numbers.
+struct LineIndex<'a> {
+    input: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(input: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self { input, line_starts }
+    }
+
+    fn position(&self, offset: usize) -> SourcePosition {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        SourcePosition {
+            offset,
+            line,
+            column: self.input[line_start..offset].chars().count() + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use html5ever::driver;
+    use tendril::TendrilSink;
+
+    use crate::{Html, HtmlTreeSink, Selector};
+
+    fn spans<'a>(html: &Html, source: &'a str, selector: &str) -> Vec<&'a str> {
+        html.select(&Selector::parse(selector).unwrap())
+            .map(|element| &source[element.source_span().unwrap().range()])
+            .collect()
+    }
+
+    #[test]
+    fn explicit_end_tags() {
+        let source = "<div id=a><p>one</p><p>two</p></div>";
+        let html = Html::parse_fragment(source);
+        assert_eq!(spans(&html, source, "div"), [source]);
+        assert_eq!(spans(&html, source, "p"), ["<p>one</p>", "<p>two</p>"]);
+    }
+
+    #[test]
+    fn implicit_end_tags() {
+        let source = "<ul><li>one<li>two</ul><p>three<p>four";
+        let html = Html::parse_fragment(source);
+        assert_eq!(spans(&html, source, "li"), ["<li>one", "<li>two"]);
+        assert_eq!(spans(&html, source, "p"), ["<p>three", "<p>four"]);
+    }
+
+    #[test]
+    fn void_elements() {
+        let source = "<p>a<br>b<img src=x.png/>c</p>";
+        let html = Html::parse_fragment(source);
+        assert_eq!(spans(&html, source, "br"), ["<br>"]);
+        assert_eq!(spans(&html, source, "img"), ["<img src=x.png/>"]);
+    }
+
+    #[test]
+    fn markup_inside_attributes_and_text() {
+        let source = r#"<a title="<b>x</b>">1 < 2 > 0</a><!-- <a> --><b>y</b>"#;
+        let html = Html::parse_fragment(source);
+        assert_eq!(
+            spans(&html, source, "a"),
+            [r#"<a title="<b>x</b>">1 < 2 > 0</a>"#]
+        );
+        assert_eq!(spans(&html, source, "b"), ["<b>y</b>"]);
+    }
+
+    #[test]
+    fn implied_elements_have_no_span() {
+        let source = "<!DOCTYPE html>\n<title>x</title>\n<P Class=a>\n  text";
+        let html = Html::parse_document(source);
+        assert!(html.root_element().source_span().is_none());
+
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+        let span = p.source_span().unwrap();
+        assert_eq!((span.start.line, span.start.column), (3, 1));
+        assert_eq!((span.end.line, span.end.column), (4, 7));
+        assert_eq!(&source[span.start_tag_range()], "<P Class=a>");
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        let source = "<p>ééé <em>wörld</em></p>";
+        let html = Html::parse_fragment(source);
+        let em = html.select(&Selector::parse("em").unwrap()).next().unwrap();
+        let span = em.source_span().unwrap();
+        assert_eq!(span.start.column, 8);
+        assert_eq!(span.start.offset, 10);
+        assert_eq!(&source[span.range()], "<em>wörld</em>");
+    }
+
+    #[test]
+    fn same_tree_as_unchunked_parse() {
+        let source = "<!doctype html><table><b>x<tr><td>a&amp<td>b</table>\r\n\
+            <script>if (a<b && c>d) {}</script><svg><path/><![CDATA[<p>]]></svg>\
+            <textarea>\n<p>\n</textarea><p>1<b>2<i>3</b>4</i>5<!-- c > d -->";
+        let unchunked =
+            driver::parse_document(HtmlTreeSink::new(Html::new_document()), Default::default())
+                .one(source);
+        assert_eq!(Html::parse_document(source).html(), unchunked.html());
+    }
+
+    #[cfg(feature = "errors")]
+    #[test]
+    fn errors_have_columns() {
+        let source = "<div>\n  text </b>\n  <p a=1 a=2>&bogus;";
+        let html = Html::parse_fragment(source);
+        let errors = html
+            .parse_errors()
+            .iter()
+            .map(|error| (error.line, error.column))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [(2, Some(8)), (3, Some(3)), (3, None), (3, Some(21))]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Parsing of `srcset` attributes.
+
+/// Returns the URLs and descriptors of the image candidates in a `srcset`, following the HTML
+/// parsing rules: a URL ends at whitespace, and its descriptors end at a comma outside
+/// parentheses. URLs may contain commas too, but a URL ending in commas has no descriptors.
+pub(crate) fn srcset_candidates(srcset: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = srcset;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return None;
+        }
+
+        let end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let (url, after) = rest.split_at(end);
+        rest = after;
+
+        let trimmed = url.trim_end_matches(',');
+        if trimmed.len() < url.len() {
+            return Some((trimmed, ""));
+        }
+
+        let mut depth = 0_usize;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| match c {
+                '(' => {
+                    depth += 1;
+                    false
+                }
+                ')' => {
+                    depth = depth.saturating_sub(1);
+                    false
+                }
+                ',' => depth == 0,
+                _ => false,
+            })
+            .map_or(rest.len(), |(index, _)| index);
+        let descriptors = rest[..end].trim_matches(|c: char| c.is_ascii_whitespace());
+        rest = &rest[end..];
+        Some((url, descriptors))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::srcset_candidates;
+
+    fn candidates(srcset: &str) -> Vec<(&str, &str)> {
+        srcset_candidates(srcset).collect()
+    }
+
+    #[test]
+    fn urls_and_descriptors() {
+        assert_eq!(candidates("a.png"), [("a.png", "")]);
+        assert_eq!(
+            candidates(" a.png 1x, b.png 2x ,c.png"),
+            [("a.png", "1x"), ("b.png", "2x"), ("c.png", "")]
+        );
+        assert_eq!(candidates("a.png,b.png 100w"), [("a.png,b.png", "100w")]);
+        assert_eq!(
+            candidates("a,1.png 1x, b.png (x, y) 2x, c.png,, d.png"),
+            [
+                ("a,1.png", "1x"),
+                ("b.png", "(x, y) 2x"),
+                ("c.png", ""),
+                ("d.png", "")
+            ]
+        );
+        assert_eq!(
+            candidates("data:image/png;base64,iVBOR 1x"),
+            [("data:image/png;base64,iVBOR", "1x")]
+        );
+        assert!(candidates(" , ").is_empty());
+    }
+}
@@ -0,0 +1,954 @@
+//! Streaming selector matching.
+//!
+//! Requires the `stream` feature.
+//!
+//! [`StreamSelector`] matches a set of selectors against a document while it is being parsed and
+//! hands each matched element to a callback as soon as its end tag has been seen. Everything that
+//! is not part of a match is dropped as soon as it is closed, so memory use is bounded by the depth
+//! of the document and the size of the matched elements rather than by the size of the document.
+//!
+//! Since an element is matched when it is inserted, before its siblings and descendants have been
+//! parsed, only selectors that can be decided from the element and its ancestors are supported.
+
+use std::borrow::Cow;
+use std::cell::{Cell, Ref, RefCell};
+use std::fmt;
+use std::io::{self, Read};
+use std::mem;
+use std::rc::{Rc, Weak};
+
+use html5ever::driver::{self, ParseOpts};
+use html5ever::tendril::{ByteTendril, StrTendril, TendrilSink};
+use html5ever::tree_builder::{ElementFlags, NodeOrText, QuirksMode, TreeSink};
+use html5ever::{Attribute, Namespace, QualName};
+use selectors::OpaqueElement;
+use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
+use selectors::bloom::BloomFilter;
+use selectors::matching::{self, SelectorCaches};
+
+use crate::error::UnsupportedSelector;
+use crate::node::{Comment, Element, Node, ProcessingInstruction, Text};
+use crate::selector::{CssLocalName, CssString, NonTSPseudoClass, PseudoElement, Simple};
+use crate::tendril_util::{make as make_tendril, push as push_str};
+use crate::{Html, Selector};
+
+/// A set of selectors that can be matched against a document while it is being parsed.
+///
+/// Selectors may only use descendant and child combinators and must not use `:has()`, `:empty` or
+/// any of the `:nth-*()`, `:first-*`, `:last-*` and `:only-*` pseudo-classes.
+///
+/// # Examples
+///
+/// ```
+/// use scraper::Selector;
+/// use scraper::stream::StreamSelector;
+///
+/// let stream = StreamSelector::new([
+///     Selector::parse("article h2").unwrap(),
+///     Selector::parse("a[href]").unwrap(),
+/// ])
+/// .unwrap();
+///
+/// let input = r#"<article><h2>Title</h2><p><a href="/next">Next</a></p></article>"#;
+/// let mut matches = Vec::new();
+/// stream
+///     .parse_document(input.as_bytes(), |index, html| {
+///         matches.push((index, html.root_element().text().collect::<String>()));
+///     })
+///     .unwrap();
+///
+/// assert_eq!(matches, [(0, "Title".to_owned()), (1, "Next".to_owned())]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamSelector {
+    selectors: Vec<Selector>,
+}
+
+impl StreamSelector {
+    /// Creates a set of selectors for streaming, failing if any of them needs to look at the
+    /// siblings or descendants of an element.
+    pub fn new<I>(selectors: I) -> Result<Self, UnsupportedSelector>
+    where
+        I: IntoIterator<Item = Selector>,
+    {
+        let selectors: Vec<Selector> = selectors.into_iter().collect();
+        match selectors.iter().find(|selector| !selector.is_ancestral()) {
+            Some(selector) => Err(UnsupportedSelector {
+                selector: cssparser::ToCss::to_css_string(selector),
+            }),
+            None => Ok(Self { selectors }),
+        }
+    }
+
+    /// Returns the selectors, in the order their indices are passed to callbacks.
+    pub fn selectors(&self) -> &[Selector] {
+        &self.selectors
+    }
+
+    /// Parses a document from `input`, calling `callback` with the index of the first matching
+    /// selector and a fragment containing the matched element each time a matched element is
+    /// closed.
+    ///
+    /// Nested matches are reported innermost first. Input that is not valid UTF-8 is decoded
+    /// lossily.
+    pub fn parse_document<R, F>(&self, mut input: R, callback: F) -> io::Result<()>
+    where
+        R: Read,
+        F: FnMut(usize, Html),
+    {
+        let sink = StreamSink::new(&self.selectors, callback);
+        let mut parser = driver::parse_document(sink, ParseOpts::default()).from_utf8();
+
+        // Elements can only be closed between chunks, when the tree builder holds no temporary
+        // references to them.
+        let mut buffer = vec![0; 16 * 1024];
+        loop {
+            let len = match input.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            parser.process(ByteTendril::from_slice(&buffer[..len]));
+            parser.inner_sink.tokenizer.sink.sink.sweep(false);
+        }
+        parser.finish();
+        Ok(())
+    }
+}
+
+type Handle = Rc<StreamNode>;
+
+/// Elements whose last handle held by the tree builder has been dropped since the last sweep.
+type Released = RefCell<Vec<Handle>>;
+
+struct StreamNode {
+    value: RefCell<Node>,
+    parent: RefCell<Weak<StreamNode>>,
+    children: RefCell<Vec<Handle>>,
+    /// The index of the first selector matching this element.
+    matched: Cell<Option<usize>>,
+    /// Whether this node is part of a matched element and must be kept until that is closed.
+    captured: Cell<bool>,
+    /// Whether `matched` and `captured` have been computed.
+    evaluated: Cell<bool>,
+    /// Whether this node is attached to the document.
+    connected: Cell<bool>,
+    /// Whether this node is inside the contents of a `<template>` element, if it is connected.
+    in_template: Cell<bool>,
+    /// Whether this element has been closed, i.e. the tree builder no longer refers to it.
+    closed: Cell<bool>,
+    /// The number of handles to this node held by the tree builder.
+    held: Cell<usize>,
+    /// Where this element is queued once `held` drops to zero.
+    released: Weak<Released>,
+}
+
+impl StreamNode {
+    fn new(value: Node) -> Handle {
+        Self::with_released(value, Weak::new())
+    }
+
+    fn with_released(value: Node, released: Weak<Released>) -> Handle {
+        Rc::new(Self {
+            value: RefCell::new(value),
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(Vec::new()),
+            matched: Cell::new(None),
+            captured: Cell::new(false),
+            evaluated: Cell::new(false),
+            connected: Cell::new(false),
+            in_template: Cell::new(false),
+            closed: Cell::new(false),
+            held: Cell::new(0),
+            released,
+        })
+    }
+
+    fn parent(&self) -> Option<Handle> {
+        self.parent.borrow().upgrade()
+    }
+
+    fn is_element(&self) -> bool {
+        self.value.borrow().is_element()
+    }
+
+    /// Returns true if this node or any of its descendants is an element that is still open.
+    fn holds_open(&self) -> bool {
+        if self.is_element() && !self.closed.get() {
+            return true;
+        }
+
+        let mut stack: Vec<Handle> = self.children.borrow().clone();
+        while let Some(node) = stack.pop() {
+            if node.is_element() && !node.closed.get() {
+                return true;
+            }
+            stack.extend(node.children.borrow().iter().cloned());
+        }
+        false
+    }
+}
+
+/// A handle held by the tree builder. The handles to each node are counted, so that an element
+/// can be closed as soon as the tree builder lets go of it.
+struct Held(Handle);
+
+impl Held {
+    fn new(node: &Handle) -> Self {
+        node.held.set(node.held.get() + 1);
+        Self(Rc::clone(node))
+    }
+}
+
+impl Clone for Held {
+    fn clone(&self) -> Self {
+        Self::new(&self.0)
+    }
+}
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        let held = self.0.held.get() - 1;
+        self.0.held.set(held);
+        if held == 0
+            && !self.0.closed.get()
+            && let Some(released) = self.0.released.upgrade()
+        {
+            released.borrow_mut().push(Rc::clone(&self.0));
+        }
+    }
+}
+
+// Avoid deep recursion when dropping deeply nested subtrees.
+impl Drop for StreamNode {
+    fn drop(&mut self) {
+        let mut stack = mem::take(self.children.get_mut());
+        while let Some(node) = stack.pop() {
+            stack.append(&mut node.children.borrow_mut());
+        }
+    }
+}
+
+fn detach(node: &Handle) {
+    let Some(parent) = node.parent() else {
+        return;
+    };
+    disconnect(node);
+    let mut children = parent.children.borrow_mut();
+    if let Some(index) = children.iter().rposition(|child| Rc::ptr_eq(child, node)) {
+        children.remove(index);
+    }
+    *node.parent.borrow_mut() = Weak::new();
+}
+
+/// Returns true if the tree builder no longer refers to `node`.
+fn is_released(node: &Handle) -> bool {
+    node.held.get() == 0
+}
+
+/// Marks a subtree that is being detached from the document as disconnected.
+fn disconnect(node: &Handle) {
+    if !node.connected.get() {
+        return;
+    }
+    let mut stack = vec![Rc::clone(node)];
+    while let Some(node) = stack.pop() {
+        node.connected.set(false);
+        stack.extend(node.children.borrow().iter().cloned());
+    }
+}
+
+/// Takes a node passed by the tree builder out of its handle.
+fn unwrap_held(child: NodeOrText<Held>) -> NodeOrText<Handle> {
+    match child {
+        NodeOrText::AppendNode(node) => NodeOrText::AppendNode(Rc::clone(&node.0)),
+        NodeOrText::AppendText(text) => NodeOrText::AppendText(text),
+    }
+}
+
+fn append(parent: &Handle, child: &Handle) {
+    detach(child);
+    *child.parent.borrow_mut() = Rc::downgrade(parent);
+    parent.children.borrow_mut().push(Rc::clone(child));
+}
+
+struct StreamSink<'a, F> {
+    selectors: &'a [Selector],
+    callback: RefCell<F>,
+    caches: RefCell<SelectorCaches>,
+    document: Handle,
+    /// Elements that have been created, including some that have been closed since the list was
+    /// last compacted.
+    pending: RefCell<Vec<Handle>>,
+    /// The length of `pending` after it was last compacted.
+    compacted: Cell<usize>,
+    released: Rc<Released>,
+    quirks_mode: Cell<QuirksMode>,
+}
+
+impl<'a, F: FnMut(usize, Html)> StreamSink<'a, F> {
+    fn new(selectors: &'a [Selector], callback: F) -> Self {
+        let document = StreamNode::new(Node::Document);
+        document.evaluated.set(true);
+        document.connected.set(true);
+        Self {
+            selectors,
+            callback: RefCell::new(callback),
+            caches: RefCell::new(SelectorCaches::default()),
+            document,
+            pending: RefCell::new(Vec::new()),
+            compacted: Cell::new(0),
+            released: Rc::new(RefCell::new(Vec::new())),
+            quirks_mode: Cell::new(QuirksMode::NoQuirks),
+        }
+    }
+
+    /// Matches the elements of a subtree that has just been inserted into the document.
+    ///
+    /// Like [`Html::select`], elements inside the contents of `<template>` elements are never
+    /// matched, but they are kept if the template itself is matched.
+    fn evaluate(&self, node: &Handle) {
+        let mut stack = vec![Rc::clone(node)];
+        while let Some(node) = stack.pop() {
+            let parent = node.parent().unwrap();
+            let in_template = parent.in_template.get() || parent.value.borrow().is_fragment();
+            node.connected.set(true);
+            node.in_template.set(in_template);
+
+            if !node.evaluated.replace(true) {
+                let matched = if node.is_element() && !in_template {
+                    let element = StreamElement(Rc::clone(&node));
+                    let mut caches = self.caches.borrow_mut();
+                    self.selectors.iter().position(|selector| {
+                        selector.matches_with_scope_and_cache(&element, None, &mut caches)
+                    })
+                } else {
+                    None
+                };
+                node.matched.set(matched);
+                node.captured
+                    .set(matched.is_some() || parent.captured.get());
+            }
+            stack.extend(node.children.borrow().iter().cloned());
+        }
+    }
+
+    /// Closes the elements that the tree builder has let go of since the last sweep, or all
+    /// pending elements if `force` is set.
+    fn sweep(&self, force: bool) {
+        let released = mem::take(&mut *self.released.borrow_mut());
+        for node in &released {
+            if !node.closed.get() && is_released(node) {
+                self.close(node, force);
+            }
+        }
+
+        if force {
+            let pending = mem::take(&mut *self.pending.borrow_mut());
+            for node in &pending {
+                if !node.closed.get() {
+                    self.close(node, force);
+                }
+            }
+            return;
+        }
+
+        // Closed elements are only removed from the list once it has doubled in size, so that
+        // this takes constant time per element.
+        let mut pending = self.pending.borrow_mut();
+        if pending.len() >= 2 * self.compacted.get().max(64) {
+            pending.retain(|node| !node.closed.get());
+            self.compacted.set(pending.len());
+        }
+    }
+
+    /// Closes an element, closing its released descendants first so that nested matches are
+    /// reported innermost first.
+    fn close(&self, node: &Handle, force: bool) {
+        // Each element is visited twice, to close its descendants and then itself.
+        let mut stack = vec![(Rc::clone(node), false)];
+        while let Some((node, descendants_closed)) = stack.pop() {
+            if descendants_closed {
+                if let Some(index) = node.matched.get() {
+                    self.emit(index, &node);
+                }
+                self.prune(&node);
+                continue;
+            }
+            if node.closed.replace(true) {
+                continue;
+            }
+            stack.push((Rc::clone(&node), true));
+
+            let mut children = Vec::new();
+            let mut parents = vec![node];
+            while let Some(parent) = parents.pop() {
+                for child in parent.children.borrow().iter() {
+                    match *child.value.borrow() {
+                        Node::Element(_)
+                            if !child.closed.get() && (force || is_released(child)) =>
+                        {
+                            children.push(Rc::clone(child));
+                        }
+                        Node::Fragment => parents.push(Rc::clone(child)),
+                        _ => {}
+                    }
+                }
+            }
+            stack.extend(children.into_iter().rev().map(|child| (child, false)));
+        }
+    }
+
+    /// Drops a closed node unless it belongs to a captured ancestor or still holds open elements,
+    /// then does the same for its closed ancestors.
+    fn prune(&self, node: &Handle) {
+        let mut node = Rc::clone(node);
+        while let Some(parent) = node.parent() {
+            if parent.captured.get() || node.holds_open() {
+                return;
+            }
+            detach(&node);
+            if !parent.closed.get() {
+                return;
+            }
+            node = parent;
+        }
+    }
+
+    fn emit(&self, index: usize, node: &Handle) {
+        let mut html = Html::new_fragment();
+        html.quirks_mode = self.quirks_mode.get();
+
+        let root = html.tree.root().id();
+        let mut stack = vec![(Rc::clone(node), root)];
+        while let Some((node, parent)) = stack.pop() {
+            let value = node.value.borrow().clone();
+            let id = html.tree.get_mut(parent).unwrap().append(value).id();
+            stack.extend(
+                node.children
+                    .borrow()
+                    .iter()
+                    .rev()
+                    .map(|child| (Rc::clone(child), id)),
+            );
+        }
+
+        (self.callback.borrow_mut())(index, html);
+    }
+
+    fn insert(&self, parent: &Handle, child: NodeOrText<Handle>, before: Option<&Handle>) {
+        match child {
+            NodeOrText::AppendNode(node) => {
+                if !node.is_element() && !parent.captured.get() {
+                    detach(&node);
+                    return;
+                }
+                match before {
+                    Some(sibling) => {
+                        detach(&node);
+                        let mut children = parent.children.borrow_mut();
+                        let index = children
+                            .iter()
+                            .position(|child| Rc::ptr_eq(child, sibling))
+                            .unwrap_or(children.len());
+                        *node.parent.borrow_mut() = Rc::downgrade(parent);
+                        children.insert(index, Rc::clone(&node));
+                    }
+                    None => append(parent, &node),
+                }
+                if parent.connected.get() {
+                    self.evaluate(&node);
+                }
+            }
+
+            NodeOrText::AppendText(text) => {
+                if !parent.captured.get() {
+                    return;
+                }
+                let mut children = parent.children.borrow_mut();
+                let index = match before {
+                    Some(sibling) => children
+                        .iter()
+                        .position(|child| Rc::ptr_eq(child, sibling))
+                        .unwrap_or(children.len()),
+                    None => children.len(),
+                };
+
+                if let Some(previous) = index.checked_sub(1).map(|index| &children[index])
+                    && let Node::Text(ref mut previous) = *previous.value.borrow_mut()
+                {
+                    push_str(&mut previous.text, &text);
+                    return;
+                }
+
+                let node = StreamNode::new(Node::Text(Text {
+                    text: make_tendril(text),
+                }));
+                node.evaluated.set(true);
+                node.captured.set(true);
+                *node.parent.borrow_mut() = Rc::downgrade(parent);
+                children.insert(index, node);
+            }
+        }
+    }
+}
+
+impl<F: FnMut(usize, Html)> TreeSink for StreamSink<'_, F> {
+    type Output = ();
+    type Handle = Held;
+    type ElemName<'a>
+        = Ref<'a, QualName>
+    where
+        Self: 'a;
+
+    fn finish(self) {
+        self.sweep(true);
+    }
+
+    fn parse_error(&self, _msg: Cow<'static, str>) {}
+
+    fn set_quirks_mode(&self, mode: QuirksMode) {
+        self.quirks_mode.set(mode);
+    }
+
+    fn get_document(&self) -> Held {
+        Held::new(&self.document)
+    }
+
+    fn same_node(&self, x: &Held, y: &Held) -> bool {
+        Rc::ptr_eq(&x.0, &y.0)
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a Held) -> Ref<'a, QualName> {
+        Ref::map(target.0.value.borrow(), |node| {
+            &node.as_element().unwrap().name
+        })
+    }
+
+    fn create_element(&self, name: QualName, attrs: Vec<Attribute>, _flags: ElementFlags) -> Held {
+        let template = name.expanded() == expanded_name!(html "template");
+        let node = StreamNode::with_released(
+            Node::Element(Element::new(name, attrs)),
+            Rc::downgrade(&self.released),
+        );
+        if template {
+            append(&node, &StreamNode::new(Node::Fragment));
+        }
+        self.pending.borrow_mut().push(Rc::clone(&node));
+        Held::new(&node)
+    }
+
+    fn create_comment(&self, text: StrTendril) -> Held {
+        Held::new(&StreamNode::new(Node::Comment(Comment {
+            comment: make_tendril(text),
+        })))
+    }
+
+    fn create_pi(&self, target: StrTendril, data: StrTendril) -> Held {
+        Held::new(&StreamNode::new(Node::ProcessingInstruction(
+            ProcessingInstruction {
+                target: make_tendril(target),
+                data: make_tendril(data),
+            },
+        )))
+    }
+
+    fn append_doctype_to_document(&self, _: StrTendril, _: StrTendril, _: StrTendril) {}
+
+    fn append(&self, parent: &Held, child: NodeOrText<Held>) {
+        self.insert(&parent.0, unwrap_held(child), None);
+    }
+
+    fn append_before_sibling(&self, sibling: &Held, new_node: NodeOrText<Held>) {
+        match sibling.0.parent() {
+            Some(parent) => self.insert(&parent, unwrap_held(new_node), Some(&sibling.0)),
+            None => {
+                if let NodeOrText::AppendNode(node) = new_node {
+                    detach(&node.0);
+                }
+            }
+        }
+    }
+
+    fn append_based_on_parent_node(
+        &self,
+        element: &Held,
+        prev_element: &Held,
+        child: NodeOrText<Held>,
+    ) {
+        if element.0.parent().is_some() {
+            self.append_before_sibling(element, child);
+        } else {
+            self.append(prev_element, child);
+        }
+    }
+
+    fn remove_from_parent(&self, target: &Held) {
+        detach(&target.0);
+    }
+
+    fn reparent_children(&self, node: &Held, new_parent: &Held) {
+        let (node, new_parent) = (&node.0, &new_parent.0);
+        let children = mem::take(&mut *node.children.borrow_mut());
+        for child in children {
+            *child.parent.borrow_mut() = Rc::downgrade(new_parent);
+            new_parent.children.borrow_mut().push(Rc::clone(&child));
+            if new_parent.connected.get() {
+                self.evaluate(&child);
+            } else {
+                disconnect(&child);
+            }
+        }
+    }
+
+    fn add_attrs_if_missing(&self, target: &Held, attrs: Vec<Attribute>) {
+        let mut value = target.0.value.borrow_mut();
+        let Node::Element(ref mut element) = *value else {
+            unreachable!();
+        };
+
+        for attr in attrs {
+            #[cfg(not(feature = "deterministic"))]
+            if let Err(idx) = element
+                .attrs
+                .binary_search_by(|(name, _)| name.cmp(&attr.name))
+            {
+                element
+                    .attrs
+                    .insert(idx, (attr.name, make_tendril(attr.value)));
+            }
+
+            #[cfg(feature = "deterministic")]
+            element
+                .attrs
+                .entry(attr.name)
+                .or_insert_with(|| make_tendril(attr.value));
+        }
+    }
+
+    fn get_template_contents(&self, target: &Held) -> Held {
+        Held::new(&target.0.children.borrow()[0])
+    }
+}
+
+/// An element of the partially built tree, which only knows its ancestors.
+#[derive(Clone)]
+struct StreamElement(Handle);
+
+impl StreamElement {
+    fn element(&self) -> Ref<'_, Element> {
+        Ref::map(self.0.value.borrow(), |node| node.as_element().unwrap())
+    }
+}
+
+impl fmt::Debug for StreamElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StreamElement")
+            .field(&*self.element())
+            .finish()
+    }
+}
+
+/// Note: sibling and child queries always come up empty as they are not known yet.
+impl selectors::Element for StreamElement {
+    type Impl = Simple;
+
+    fn opaque(&self) -> OpaqueElement {
+        OpaqueElement::new(&*self.0)
+    }
+
+    fn parent_element(&self) -> Option<Self> {
+        self.0
+            .parent()
+            .filter(|parent| parent.is_element())
+            .map(StreamElement)
+    }
+
+    fn parent_node_is_shadow_root(&self) -> bool {
+        false
+    }
+
+    fn containing_shadow_host(&self) -> Option<Self> {
+        None
+    }
+
+    fn is_pseudo_element(&self) -> bool {
+        false
+    }
+
+    fn is_part(&self, _name: &CssLocalName) -> bool {
+        false
+    }
+
+    fn is_same_type(&self, other: &Self) -> bool {
+        self.element().name == other.element().name
+    }
+
+    fn imported_part(&self, _: &CssLocalName) -> Option<CssLocalName> {
+        None
+    }
+
+    fn prev_sibling_element(&self) -> Option<Self> {
+        None
+    }
+
+    fn next_sibling_element(&self) -> Option<Self> {
+        None
+    }
+
+    fn first_element_child(&self) -> Option<Self> {
+        None
+    }
+
+    fn is_html_element_in_html_document(&self) -> bool {
+        self.element().name.ns == ns!(html)
+    }
+
+    fn has_local_name(&self, name: &CssLocalName) -> bool {
+        self.element().name.local == name.0
+    }
+
+    fn has_namespace(&self, namespace: &Namespace) -> bool {
+        &self.element().name.ns == namespace
+    }
+
+    fn attr_matches(
+        &self,
+        ns: &NamespaceConstraint<&Namespace>,
+        local_name: &CssLocalName,
+        operation: &AttrSelectorOperation<&CssString>,
+    ) -> bool {
+        self.element().attrs.iter().any(|(key, value)| {
+            !matches!(*ns, NamespaceConstraint::Specific(url) if *url != key.ns)
+                && local_name.0 == key.local
+                && operation.eval_str(value)
+        })
+    }
+
+    fn match_non_ts_pseudo_class(
+        &self,
+        _pc: &NonTSPseudoClass,
+        _context: &mut matching::MatchingContext<'_, Self::Impl>,
+    ) -> bool {
+        false
+    }
+
+    fn match_pseudo_element(
+        &self,
+        _pe: &PseudoElement,
+        _context: &mut matching::MatchingContext<Self::Impl>,
+    ) -> bool {
+        false
+    }
+
+    fn is_link(&self) -> bool {
+        self.element().name() == "link"
+    }
+
+    fn is_html_slot_element(&self) -> bool {
+        true
+    }
+
+    fn has_id(&self, id: &CssLocalName, case_sensitivity: CaseSensitivity) -> bool {
+        match self.element().id() {
+            Some(val) => case_sensitivity.eq(id.0.as_bytes(), val.as_bytes()),
+            None => false,
+        }
+    }
+
+    fn has_class(&self, name: &CssLocalName, case_sensitivity: CaseSensitivity) -> bool {
+        self.element().has_class(&name.0, case_sensitivity)
+    }
+
+    fn has_custom_state(&self, _name: &CssLocalName) -> bool {
+        false
+    }
+
+    fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn is_root(&self) -> bool {
+        self.0
+            .parent()
+            .is_some_and(|parent| parent.value.borrow().is_document())
+    }
+
+    fn apply_selector_flags(&self, _flags: matching::ElementSelectorFlags) {}
+
+    fn add_element_unique_hashes(&self, _filter: &mut BloomFilter) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamNode, StreamSelector, StreamSink};
+    use crate::Selector;
+    use html5ever::driver::{self, ParseOpts};
+    use html5ever::tendril::TendrilSink;
+    use std::io::{self, Read};
+
+    /// Hands out its input a few bytes at a time.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    fn stream(selectors: &[&str], input: &str) -> Vec<(usize, String)> {
+        let selectors = selectors.iter().map(|s| Selector::parse(s).unwrap());
+        let stream = StreamSelector::new(selectors).unwrap();
+
+        let mut matches = Vec::new();
+        stream
+            .parse_document(input.as_bytes(), |index, html| {
+                matches.push((index, html.html()));
+            })
+            .unwrap();
+
+        let mut trickled = Vec::new();
+        stream
+            .parse_document(Trickle(input.as_bytes()), |index, html| {
+                trickled.push((index, html.html()));
+            })
+            .unwrap();
+        assert_eq!(matches, trickled);
+
+        matches
+    }
+
+    #[test]
+    fn matches_on_end_tag() {
+        let matches = stream(
+            &["li.a", "ul b"],
+            r#"<ul><li class="a">1</li><li>2 <b>x</b></li></ul><b>y</b>"#,
+        );
+        assert_eq!(
+            matches,
+            [
+                (0, r#"<li class="a">1</li>"#.to_owned()),
+                (1, "<b>x</b>".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_and_void_matches() {
+        let matches = stream(
+            &["img", "div"],
+            r#"<div><img src="a"><p>t<img src="b"></p></div><img src="c">"#,
+        );
+        assert_eq!(
+            matches,
+            [
+                (0, r#"<img src="a">"#.to_owned()),
+                (0, r#"<img src="b">"#.to_owned()),
+                (
+                    1,
+                    r#"<div><img src="a"><p>t<img src="b"></p></div>"#.to_owned()
+                ),
+                (0, r#"<img src="c">"#.to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn implicitly_closed_and_unclosed() {
+        let matches = stream(&["p", "body > span"], "<p>one<p>two<div><span>x");
+        assert_eq!(
+            matches,
+            [(0, "<p>one</p>".to_owned()), (0, "<p>two</p>".to_owned()),]
+        );
+
+        let matches = stream(&["body > span", "div > span"], "<div><span>x");
+        assert_eq!(matches, [(1, "<span>x</span>".to_owned())]);
+    }
+
+    #[test]
+    fn same_as_full_parse() {
+        for input in [
+            "<div><b><p>a</b>b</p><p>c</div>",
+            "<table><tr><td>1<p>x</table><p>after",
+            "<p><a href=1>one<p>two</a><p>three",
+            "<ul><li>1<li><template><p>t</p></template>2</ul>",
+        ] {
+            let full = crate::Html::parse_document(input);
+            let expected: Vec<_> = full
+                .select(&Selector::parse("p").unwrap())
+                .map(|p| (0, p.html()))
+                .collect();
+            assert_eq!(stream(&["p"], input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn deeply_nested() {
+        let input = format!("{}<p>x</p>", "<div>".repeat(10_000));
+        let stream = StreamSelector::new([Selector::parse("p").unwrap()]).unwrap();
+
+        let mut matches = Vec::new();
+        stream
+            .parse_document(input.as_bytes(), |_, html| matches.push(html.html()))
+            .unwrap();
+        assert_eq!(matches, ["<p>x</p>"]);
+    }
+
+    #[test]
+    fn unsupported_selectors() {
+        for selector in [
+            "li + li",
+            "li ~ li",
+            "p:first-child",
+            "p:nth-of-type(2)",
+            "div:has(p)",
+            "p:empty",
+            "a:not(:last-child)",
+        ] {
+            let error = StreamSelector::new([Selector::parse(selector).unwrap()]).unwrap_err();
+            assert_eq!(error.selector, selector);
+        }
+
+        for selector in ["div > p:not(.x)", ":root a[href]", ":is(ul, ol) li"] {
+            assert!(StreamSelector::new([Selector::parse(selector).unwrap()]).is_ok());
+        }
+    }
+
+    #[test]
+    fn closed_nodes_are_dropped() {
+        let selectors = [Selector::parse("p.keep").unwrap()];
+        let mut matches = 0;
+        let sink = StreamSink::new(&selectors, |_, _| matches += 1);
+        let document = sink.document.clone();
+
+        let mut parser = driver::parse_document(sink, ParseOpts::default());
+        parser.process("<!DOCTYPE html><title>t</title><div>".into());
+        for _ in 0..1000 {
+            parser.process("<p>text<b>bold</b><br></p><!-- c -->".into());
+            parser.tokenizer.sink.sink.sweep(false);
+        }
+        parser.process(r#"<p class="keep">kept"#.into());
+        parser.tokenizer.sink.sink.sweep(false);
+
+        fn count(node: &StreamNode) -> usize {
+            1 + node
+                .children
+                .borrow()
+                .iter()
+                .map(|c| count(c))
+                .sum::<usize>()
+        }
+        // document, html, head, body, div, p.keep and its text
+        assert_eq!(count(&document), 7);
+
+        parser.finish();
+        assert_eq!(matches, 1);
+        assert_eq!(count(&document), 1);
+    }
+}
@@ -0,0 +1,377 @@
+//! JSON-LD extraction.
+
+use super::{Item, Syntax, Value, elements};
+use crate::Html;
+
+/// Extracts the items of the `<script type="application/ld+json">` elements of a document.
+///
+/// Top-level objects, arrays of objects and `@graph` arrays are supported. Types are expanded
+/// with the vocabulary of a string `@context` or of `@vocab`, and `@value` objects are replaced by
+/// their values. Scripts that are not valid JSON are skipped.
+pub fn json_ld(html: &Html) -> Vec<Item> {
+    let mut items = Vec::new();
+    for script in elements(html) {
+        let is_json_ld = script.value().name() == "script"
+            && script.value().attr("type").is_some_and(|ty| {
+                ty.split(';')
+                    .next()
+                    .unwrap()
+                    .trim()
+                    .eq_ignore_ascii_case("application/ld+json")
+            });
+        if !is_json_ld {
+            continue;
+        }
+
+        let text = script.text().collect::<String>();
+        if let Some(json) = Parser::new(&text).parse() {
+            top_level(&json, None, &mut items);
+        }
+    }
+    items
+}
+
+fn top_level(json: &Json, vocab: Option<&str>, items: &mut Vec<Item>) {
+    match json {
+        Json::Array(values) => {
+            for value in values {
+                top_level(value, vocab, items);
+            }
+        }
+        Json::Object(members) => {
+            let vocab = context_vocab(members).or(vocab);
+            match get(members, "@graph") {
+                Some(graph) => top_level(graph, vocab, items),
+                None => items.push(item(members, vocab)),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn item(members: &[(String, Json)], vocab: Option<&str>) -> Item {
+    let vocab = context_vocab(members).or(vocab);
+    let mut item = Item::new(Syntax::JsonLd);
+    for (key, value) in members {
+        match key.as_str() {
+            "@type" => {
+                for ty in values(value) {
+                    if let Json::String(ty) = ty {
+                        item.types.push(expand(ty, vocab));
+                    }
+                }
+            }
+            "@id" => {
+                if let Json::String(id) = value {
+                    item.id = Some(id.clone());
+                }
+            }
+            key if key.starts_with('@') => {}
+            key => {
+                for value in values(value) {
+                    if let Some(value) = property_value(value, vocab) {
+                        item.push(key, value);
+                    }
+                }
+            }
+        }
+    }
+    item
+}
+
+fn property_value(json: &Json, vocab: Option<&str>) -> Option<Value> {
+    match json {
+        Json::Null | Json::Array(_) => None,
+        Json::Bool(bool) => Some(Value::Bool(*bool)),
+        Json::Number(number) => Some(Value::Number(*number)),
+        Json::String(string) => Some(Value::Text(string.clone())),
+        Json::Object(members) => match get(members, "@value") {
+            Some(value) => property_value(value, vocab),
+            None => Some(Value::Item(item(members, vocab))),
+        },
+    }
+}
+
+/// Returns the elements of an array, or the value itself.
+fn values(json: &Json) -> &[Json] {
+    match json {
+        Json::Array(values) => values,
+        json => std::slice::from_ref(json),
+    }
+}
+
+fn get<'a>(members: &'a [(String, Json)], key: &str) -> Option<&'a Json> {
+    members
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value)
+}
+
+/// Returns the vocabulary set by the `@context` of an object.
+fn context_vocab(members: &[(String, Json)]) -> Option<&str> {
+    match get(members, "@context")? {
+        Json::String(context) => Some(context),
+        Json::Object(context) => match get(context, "@vocab")? {
+            Json::String(vocab) => Some(vocab),
+            _ => None,
+        },
+        Json::Array(contexts) => contexts.iter().find_map(|context| match context {
+            Json::String(context) => Some(context.as_str()),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Expands a type name that is not an absolute URL or compact IRI with the vocabulary.
+pub(super) fn expand(name: &str, vocab: Option<&str>) -> String {
+    match vocab {
+        Some(vocab) if !name.contains(':') => {
+            if vocab.ends_with(['/', '#']) {
+                format!("{vocab}{name}")
+            } else {
+                format!("{vocab}/{name}")
+            }
+        }
+        _ => name.to_owned(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// A JSON parser, which is all JSON-LD extraction needs and avoids a dependency.
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser { input, position: 0 }
+    }
+
+    fn parse(mut self) -> Option<Json> {
+        let value = self.value(0)?;
+        self.whitespace();
+        (self.position == self.input.len()).then_some(value)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.whitespace();
+        let matches = self.peek() == Some(byte);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Option<Json> {
+        let rest = &self.input[self.position..];
+        rest.starts_with(keyword).then(|| {
+            self.position += keyword.len();
+            value
+        })
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Json> {
+        // Deeply nested input would overflow the stack.
+        if depth > 128 {
+            return None;
+        }
+
+        self.whitespace();
+        match self.peek()? {
+            b'n' => self.keyword("null", Json::Null),
+            b't' => self.keyword("true", Json::Bool(true)),
+            b'f' => self.keyword("false", Json::Bool(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' => {
+                self.position += 1;
+                let mut values = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        values.push(self.value(depth + 1)?);
+                        if self.eat(b']') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return None;
+                        }
+                    }
+                }
+                Some(Json::Array(values))
+            }
+            b'{' => {
+                self.position += 1;
+                let mut members = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.whitespace();
+                        let key = self.string()?;
+                        if !self.eat(b':') {
+                            return None;
+                        }
+                        members.push((key, self.value(depth + 1)?));
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return None;
+                        }
+                    }
+                }
+                Some(Json::Object(members))
+            }
+            _ => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Option<Json> {
+        let start = self.position;
+        while matches!(
+            self.peek(),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        ) {
+            self.position += 1;
+        }
+        self.input[start..self.position]
+            .parse()
+            .ok()
+            .map(Json::Number)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.peek() != Some(b'"') {
+            return None;
+        }
+        self.position += 1;
+
+        let mut string = String::new();
+        loop {
+            let rest = &self.input[self.position..];
+            let end = rest.find(['"', '\\'])?;
+            string.push_str(&rest[..end]);
+            self.position += end + 1;
+            if rest.as_bytes()[end] == b'"' {
+                return Some(string);
+            }
+
+            let escape = self.peek()?;
+            self.position += 1;
+            match escape {
+                b'"' => string.push('"'),
+                b'\\' => string.push('\\'),
+                b'/' => string.push('/'),
+                b'b' => string.push('\u{8}'),
+                b'f' => string.push('\u{c}'),
+                b'n' => string.push('\n'),
+                b'r' => string.push('\r'),
+                b't' => string.push('\t'),
+                b'u' => {
+                    let high = self.hex()?;
+                    let c = if (0xD800..0xDC00).contains(&high) {
+                        let rest = &self.input[self.position..];
+                        if !rest.starts_with("\\u") {
+                            return None;
+                        }
+                        self.position += 2;
+                        let low = self.hex()?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return None;
+                        }
+                        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                    } else {
+                        char::from_u32(high)
+                    };
+                    string.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Option<u32> {
+        let digits = self.input.get(self.position..self.position + 4)?;
+        let value = u32::from_str_radix(digits, 16).ok()?;
+        self.position += 4;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Json, Parser, json_ld};
+    use crate::Html;
+    use crate::structured::Value;
+
+    #[test]
+    fn parser() {
+        assert_eq!(
+            Parser::new(r#" {"a": [1, -2.5e1, true, null], "bé😀": "x\"y"} "#).parse(),
+            Some(Json::Object(vec![
+                (
+                    "a".to_owned(),
+                    Json::Array(vec![
+                        Json::Number(1.0),
+                        Json::Number(-25.0),
+                        Json::Bool(true),
+                        Json::Null
+                    ])
+                ),
+                ("bé😀".to_owned(), Json::String("x\"y".to_owned())),
+            ]))
+        );
+        assert_eq!(Parser::new("[1,]").parse(), None);
+        assert_eq!(Parser::new("{} x").parse(), None);
+        assert_eq!(Parser::new(&"[".repeat(1000)).parse(), None);
+    }
+
+    #[test]
+    fn items() {
+        let html = Html::parse_document(
+            r##"<script type="application/ld+json">
+            {"@context": {"@vocab": "https://schema.org/"}, "@graph": [
+                {"@type": ["Product", "ex:Thing"], "@id": "#p", "name": "Kettle",
+                 "offers": {"@type": "Offer", "price": {"@value": 20}, "available": true},
+                 "color": ["red", "blue"], "missing": null}
+            ]}
+            </script>
+            <script type="application/ld+json">[{"@context": "http://schema.org", "@type": "Person"}]</script>
+            <script type="application/ld+json">{ not json</script>
+            <script type="text/javascript">{"@type": "Ignored"}</script>"##,
+        );
+        let items = json_ld(&html);
+        assert_eq!(items.len(), 2);
+
+        let product = &items[0];
+        assert_eq!(product.types, ["https://schema.org/Product", "ex:Thing"]);
+        assert_eq!(product.id.as_deref(), Some("#p"));
+        assert_eq!(product.properties["color"].len(), 2);
+        assert!(!product.properties.contains_key("missing"));
+
+        let offer = product.property("offers").unwrap().as_item().unwrap();
+        assert_eq!(offer.types, ["https://schema.org/Offer"]);
+        assert_eq!(offer.property("price"), Some(&Value::Number(20.0)));
+        assert_eq!(offer.property("available"), Some(&Value::Bool(true)));
+
+        assert_eq!(items[1].types, ["http://schema.org/Person"]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Microdata extraction.
+
+use std::collections::{HashMap, HashSet};
+
+use ego_tree::NodeId;
+use url::Url;
+
+use super::{Item, Syntax, Value, elements, resolve, tokens};
+use crate::{ElementRef, Html};
+
+/// Extracts the top-level Microdata items of a document, which are the elements with
+/// `itemscope` and without `itemprop`.
+///
+/// Properties are found by crawling the item's descendants and the elements named by `itemref`,
+/// stopping at nested items, and their values follow the Microdata specification: `content` for
+/// `<meta>`, a URL for `<a href>`, `<img src>` and other URL elements, `value` for `<data>` and
+/// `<meter>`, `datetime` for `<time>`, and the trimmed text otherwise. URLs are resolved against
+/// the document's `<base>` and `document_url`.
+pub fn microdata(html: &Html, document_url: Option<&Url>) -> Vec<Item> {
+    let elements = elements(html);
+
+    let mut ids = HashMap::new();
+    for element in &elements {
+        if let Some(id) = element.value().id() {
+            ids.entry(id).or_insert(*element);
+        }
+    }
+
+    let extractor = Extractor {
+        base: html.base_url(document_url),
+        order: elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (element.id(), index))
+            .collect(),
+        ids,
+    };
+
+    elements
+        .iter()
+        .filter(|element| {
+            let element = element.value();
+            element.attr("itemscope").is_some() && element.attr("itemprop").is_none()
+        })
+        .map(|element| extractor.item(*element, &mut Vec::new()))
+        .collect()
+}
+
+struct Extractor<'a> {
+    base: Option<Url>,
+    /// The position of each element in document order.
+    order: HashMap<NodeId, usize>,
+    /// The first element with each `id`.
+    ids: HashMap<&'a str, ElementRef<'a>>,
+}
+
+impl<'a> Extractor<'a> {
+    /// Returns the item of an `itemscope` element, with `ancestors` holding the items it is
+    /// nested in.
+    fn item(&self, root: ElementRef<'a>, ancestors: &mut Vec<NodeId>) -> Item {
+        let element = root.value();
+        let mut item = Item::new(Syntax::Microdata);
+        item.types = tokens(element.attr("itemtype"))
+            .map(str::to_owned)
+            .collect();
+        if !item.types.is_empty() {
+            item.id = element
+                .attr("itemid")
+                .map(|id| resolve(self.base.as_ref(), id));
+        }
+
+        ancestors.push(root.id());
+        for property in self.properties(root) {
+            let value = if property.value().attr("itemscope").is_some() {
+                // An item cannot be a property of itself.
+                if ancestors.contains(&property.id()) {
+                    continue;
+                }
+                Value::Item(self.item(property, ancestors))
+            } else {
+                self.value(property)
+            };
+
+            for name in tokens(property.value().attr("itemprop")) {
+                item.push(name, value.clone());
+            }
+        }
+        ancestors.pop();
+
+        item
+    }
+
+    /// Returns the property elements of an item in document order.
+    fn properties(&self, root: ElementRef<'a>) -> Vec<ElementRef<'a>> {
+        let mut pending = root.child_elements().collect::<Vec<_>>();
+        pending.extend(
+            tokens(root.value().attr("itemref")).filter_map(|id| self.ids.get(id).copied()),
+        );
+
+        let mut memory = HashSet::from([root.id()]);
+        let mut properties = Vec::new();
+        while let Some(current) = pending.pop() {
+            if !memory.insert(current.id()) {
+                continue;
+            }
+
+            if current.value().attr("itemscope").is_none() {
+                pending.extend(current.child_elements());
+            }
+            if tokens(current.value().attr("itemprop")).next().is_some() {
+                properties.push(current);
+            }
+        }
+
+        properties.sort_by_key(|property| self.order.get(&property.id()));
+        properties
+    }
+
+    fn value(&self, element: ElementRef<'_>) -> Value {
+        let value = element.value();
+        let url = |attr: &str| {
+            Value::Url(
+                value
+                    .attr(attr)
+                    .map(|url| resolve(self.base.as_ref(), url))
+                    .unwrap_or_default(),
+            )
+        };
+        let text = || Value::Text(element.text().collect::<String>().trim().to_owned());
+
+        match value.name() {
+            "meta" => Value::Text(value.attr("content").unwrap_or_default().to_owned()),
+            "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => url("src"),
+            "a" | "area" | "link" => url("href"),
+            "object" => url("data"),
+            "data" | "meter" => Value::Text(value.attr("value").unwrap_or_default().to_owned()),
+            "time" => match value.attr("datetime") {
+                Some(datetime) => Value::Text(datetime.to_owned()),
+                None => text(),
+            },
+            _ => text(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::microdata;
+    use crate::Html;
+    use crate::structured::Value;
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_owned())
+    }
+
+    #[test]
+    fn values_and_nesting() {
+        let html = Html::parse_document(
+            r#"<div itemscope itemtype="https://schema.org/Product" itemid="/p/1">
+                <h1 itemprop="name brand">  Kettle </h1>
+                <img itemprop="image" src="k.png">
+                <meta itemprop="sku" content="K-1">
+                <data itemprop="gtin" value="0123">GTIN</data>
+                <time itemprop="releaseDate" datetime="2024-01-01">New Year</time>
+                <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+                    <span itemprop="price">20</span>
+                </div>
+                <template><span itemprop="hidden">x</span></template>
+            </div>
+            <div itemscope><span itemprop="name">Second</span></div>"#,
+        );
+        let url = Url::parse("https://shop.example/list").unwrap();
+        let items = microdata(&html, Some(&url));
+        assert_eq!(items.len(), 2);
+
+        let product = &items[0];
+        assert_eq!(product.types, ["https://schema.org/Product"]);
+        assert_eq!(product.id.as_deref(), Some("https://shop.example/p/1"));
+        assert_eq!(product.property("name"), Some(&text("Kettle")));
+        assert_eq!(product.property("brand"), Some(&text("Kettle")));
+        assert_eq!(
+            product.property("image"),
+            Some(&Value::Url("https://shop.example/k.png".to_owned()))
+        );
+        assert_eq!(product.property("sku"), Some(&text("K-1")));
+        assert_eq!(product.property("gtin"), Some(&text("0123")));
+        assert_eq!(product.property("releaseDate"), Some(&text("2024-01-01")));
+        assert!(!product.properties.contains_key("price"));
+        assert!(!product.properties.contains_key("hidden"));
+
+        let offer = product.property("offers").unwrap().as_item().unwrap();
+        assert_eq!(offer.property("price"), Some(&text("20")));
+
+        assert!(items[1].types.is_empty());
+        assert_eq!(items[1].property("name"), Some(&text("Second")));
+    }
+
+    #[test]
+    fn itemref_order_and_cycles() {
+        let html = Html::parse_fragment(
+            r#"<p id="a"><span itemprop="name">First</span></p>
+            <div itemscope itemref="b a"><span itemprop="name">Second</span></div>
+            <p id="b" itemprop="name">Third</p>
+            <div itemscope><div itemprop="a" itemscope id="x" itemref="y"></div></div>
+            <div itemprop="b" itemscope id="y" itemref="x"></div>"#,
+        );
+        let items = microdata(&html, None);
+        assert_eq!(
+            items[0].properties["name"],
+            [text("First"), text("Second"), text("Third")]
+        );
+
+        assert_eq!(items.len(), 2);
+        let x = items[1].property("a").unwrap().as_item().unwrap();
+        let y = x.property("b").unwrap().as_item().unwrap();
+        assert!(y.properties.is_empty());
+    }
+}
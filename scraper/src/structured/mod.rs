@@ -0,0 +1,267 @@
+//! Structured data extraction from JSON-LD, Microdata and RDFa Lite.
+//!
+//! Requires the `structured` feature.
+//!
+//! All three syntaxes are extracted into the same [`Item`] model. Types are absolute URLs, such as
+//! `https://schema.org/Product`, while property names are kept as they are written, such as
+//! `name`.
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::Html;
+//! use scraper::structured::{self, Syntax, Value};
+//!
+//! let html = Html::parse_document(r#"
+//!     <script type="application/ld+json">
+//!         {"@context": "https://schema.org", "@type": "Product", "name": "Kettle"}
+//!     </script>
+//!     <div itemscope itemtype="https://schema.org/Product">
+//!         <span itemprop="name">Teapot</span>
+//!     </div>
+//! "#);
+//!
+//! let items = structured::extract(&html, None);
+//! assert_eq!(items.len(), 2);
+//! assert_eq!(items[0].syntax, Syntax::JsonLd);
+//! assert_eq!(items[0].types, ["https://schema.org/Product"]);
+//! assert_eq!(items[1].property("name"), Some(&Value::Text("Teapot".to_owned())));
+//! ```
+
+use std::collections::BTreeMap;
+
+use ego_tree::iter::Edge;
+use url::Url;
+
+use crate::element_ref::is_template_contents;
+use crate::{ElementRef, Html};
+
+mod json_ld;
+mod microdata;
+mod rdfa;
+
+pub use self::json_ld::json_ld;
+pub use self::microdata::microdata;
+pub use self::rdfa::rdfa;
+
+/// The syntax an item was written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Syntax {
+    /// JSON-LD in a `<script type="application/ld+json">`.
+    JsonLd,
+
+    /// Microdata `itemscope`, `itemtype` and `itemprop` attributes.
+    Microdata,
+
+    /// RDFa Lite `vocab`, `typeof` and `property` attributes.
+    Rdfa,
+}
+
+/// A structured data item, such as a schema.org `Product`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    /// The syntax the item was written in.
+    pub syntax: Syntax,
+
+    /// The types of the item, as absolute URLs where the vocabulary is known.
+    pub types: Vec<String>,
+
+    /// The global identifier of the item, from `@id`, `itemid` or `resource`.
+    pub id: Option<String>,
+
+    /// The values of each property, in document order.
+    pub properties: BTreeMap<String, Vec<Value>>,
+}
+
+impl Item {
+    fn new(syntax: Syntax) -> Self {
+        Item {
+            syntax,
+            types: Vec::new(),
+            id: None,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Returns true if one of the types of the item is `name`, either as an absolute URL or as
+    /// the last segment of one, such as `Product` for `https://schema.org/Product`.
+    pub fn is_type(&self, name: &str) -> bool {
+        self.types.iter().any(|ty| {
+            ty == name
+                || ty
+                    .strip_suffix(name)
+                    .is_some_and(|vocab| vocab.ends_with(['/', '#', ':']))
+        })
+    }
+
+    /// Returns the first value of a property.
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.properties.get(name).and_then(|values| values.first())
+    }
+
+    fn push(&mut self, name: &str, value: Value) {
+        self.properties
+            .entry(name.to_owned())
+            .or_default()
+            .push(value);
+    }
+}
+
+/// The value of a property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A string.
+    Text(String),
+
+    /// A URL, resolved against the document's base URL when it is relative.
+    Url(String),
+
+    /// A JSON-LD number.
+    Number(f64),
+
+    /// A JSON-LD boolean.
+    Bool(bool),
+
+    /// A nested item.
+    Item(Item),
+}
+
+impl Value {
+    /// Returns the string of a text or URL value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) | Value::Url(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the item of a nested item value.
+    pub fn as_item(&self) -> Option<&Item> {
+        match self {
+            Value::Item(item) => Some(item),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts the items of all three syntaxes, in the order JSON-LD, Microdata, RDFa Lite.
+///
+/// Relative URLs are resolved against the document's `<base>` and `document_url`.
+pub fn extract(html: &Html, document_url: Option<&Url>) -> Vec<Item> {
+    let mut items = json_ld(html);
+    items.extend(microdata(html, document_url));
+    items.extend(rdfa(html, document_url));
+    items
+}
+
+/// Returns the elements of a document in document order, skipping template contents.
+fn elements(html: &Html) -> Vec<ElementRef<'_>> {
+    let mut elements = Vec::new();
+    let mut skipped = None;
+    for edge in html.tree.root().traverse() {
+        match edge {
+            Edge::Open(node) if skipped.is_none() => {
+                if is_template_contents(node) {
+                    skipped = Some(node.id());
+                } else if let Some(element) = ElementRef::wrap(node) {
+                    elements.push(element);
+                }
+            }
+            Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+            _ => {}
+        }
+    }
+    elements
+}
+
+/// Resolves a URL against the base URL, if there is one and the URL is valid.
+fn resolve(base: Option<&Url>, url: &str) -> String {
+    let url = url.trim();
+    base.and_then(|base| base.join(url).ok())
+        .map_or_else(|| url.to_owned(), String::from)
+}
+
+/// Returns the tokens of a space-separated attribute.
+fn tokens(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value.unwrap_or_default().split_ascii_whitespace()
+}
+
+/// Serializes items in the JSON format of the Microdata specification, as
+/// `{"type": [..], "id": "..", "properties": {"name": [..]}}`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Item {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", &self.types)?;
+        if let Some(id) = &self.id {
+            map.serialize_entry("id", id)?;
+        }
+        map.serialize_entry("properties", &self.properties)?;
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Text(text) | Value::Url(text) => serializer.serialize_str(text),
+            Value::Number(number) => serializer.serialize_f64(*number),
+            Value::Bool(bool) => serializer.serialize_bool(*bool),
+            Value::Item(item) => item.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{Item, Syntax, extract};
+    use crate::Html;
+
+    #[test]
+    fn is_type() {
+        let mut item = Item::new(Syntax::Microdata);
+        item.types.push("https://schema.org/Product".to_owned());
+        assert!(item.is_type("Product"));
+        assert!(item.is_type("https://schema.org/Product"));
+        assert!(!item.is_type("duct"));
+    }
+
+    #[test]
+    fn all_syntaxes() {
+        let html = Html::parse_document(
+            r#"<base href="/shop/">
+            <script type="application/ld+json">{"@type": "Offer", "price": 3}</script>
+            <div itemscope itemtype="https://schema.org/Offer"><a itemprop="url" href="o">x</a></div>
+            <div vocab="https://schema.org/" typeof="Offer"><a property="url" href="o">x</a></div>"#,
+        );
+        let url = Url::parse("https://example.com/").unwrap();
+        let items = extract(&html, Some(&url));
+        let syntaxes = items.iter().map(|item| item.syntax).collect::<Vec<_>>();
+        assert_eq!(syntaxes, [Syntax::JsonLd, Syntax::Microdata, Syntax::Rdfa]);
+        assert!(items.iter().all(|item| item.is_type("Offer")));
+        assert_eq!(
+            items[2].property("url").and_then(|url| url.as_str()),
+            Some("https://example.com/shop/o")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let html = Html::parse_fragment(
+            r#"<div itemscope itemtype="https://schema.org/Person" itemid="urn:a">
+            <span itemprop="name">Ann</span>
+            <div itemprop="address" itemscope><span itemprop="city">Oslo</span></div></div>"#,
+        );
+        let items = extract(&html, None);
+        assert_eq!(
+            serde_json::to_string(&items).unwrap(),
+            r#"[{"type":["https://schema.org/Person"],"id":"urn:a","properties":{"address":[{"type":[],"properties":{"city":["Oslo"]}}],"name":["Ann"]}}]"#
+        );
+    }
+}
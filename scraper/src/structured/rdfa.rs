@@ -0,0 +1,241 @@
+//! RDFa Lite extraction.
+
+use std::rc::Rc;
+
+use ego_tree::iter::Edge;
+use url::Url;
+
+use super::json_ld::expand;
+use super::{Item, Syntax, Value, resolve, tokens};
+use crate::element_ref::is_template_contents;
+use crate::node::Element;
+use crate::{ElementRef, Html};
+
+/// Extracts the top-level RDFa Lite items of a document, which are the elements with `typeof`
+/// and without `property` or an enclosing item.
+///
+/// Types are expanded with the `vocab` in scope or with the prefixes declared by `prefix`. The
+/// value of a property is its `content`, else a URL from `resource`, `href`, `src` or `data`,
+/// else the `datetime` of a `<time>`, else the trimmed text. URLs are resolved against the
+/// document's `<base>` and `document_url`.
+pub fn rdfa(html: &Html, document_url: Option<&Url>) -> Vec<Item> {
+    let base = html.base_url(document_url);
+    let mut items = Items::default();
+    let mut scopes = vec![Scope::default()];
+    let mut skipped = None;
+
+    for edge in html.tree.root().traverse() {
+        match edge {
+            Edge::Open(node) if skipped.is_none() => {
+                if is_template_contents(node) {
+                    skipped = Some(node.id());
+                    continue;
+                }
+                let Some(element) = ElementRef::wrap(node) else {
+                    continue;
+                };
+
+                let scope = scopes.last().unwrap().enter(element.value());
+                let properties = tokens(element.value().attr("property")).collect::<Vec<_>>();
+                let item = match element.value().attr("typeof") {
+                    Some(types) => {
+                        let mut item = Item::new(Syntax::Rdfa);
+                        item.types = tokens(Some(types)).map(|ty| scope.expand(ty)).collect();
+                        item.id = element
+                            .value()
+                            .attr("resource")
+                            .map(|id| resolve(base.as_ref(), id));
+                        let parent = scope.item.filter(|_| !properties.is_empty());
+                        Some(items.add(item, parent, &properties))
+                    }
+                    None => {
+                        if let Some(parent) = scope.item {
+                            let value = value(element, base.as_ref());
+                            for name in &properties {
+                                items.items[parent].push(name, value.clone());
+                            }
+                        }
+                        scope.item
+                    }
+                };
+
+                scopes.push(Scope { item, ..scope });
+            }
+            Edge::Close(node) if skipped == Some(node.id()) => skipped = None,
+            Edge::Close(node) if skipped.is_none() && node.value().is_element() => {
+                scopes.pop();
+            }
+            _ => {}
+        }
+    }
+
+    items.finish()
+}
+
+/// The vocabulary, prefixes and item in scope for an element.
+#[derive(Debug, Clone, Default)]
+struct Scope {
+    vocab: Option<String>,
+    prefixes: Rc<Vec<(String, String)>>,
+    item: Option<usize>,
+}
+
+impl Scope {
+    /// Returns the scope of a child element.
+    fn enter(&self, element: &Element) -> Scope {
+        let mut scope = self.clone();
+        if let Some(vocab) = element.attr("vocab") {
+            let vocab = vocab.trim();
+            scope.vocab = (!vocab.is_empty()).then(|| vocab.to_owned());
+        }
+        if let Some(prefix) = element.attr("prefix") {
+            let mut prefixes = (*self.prefixes).clone();
+            let mut tokens = prefix.split_ascii_whitespace();
+            while let (Some(name), Some(url)) = (tokens.next(), tokens.next()) {
+                if let Some(name) = name.strip_suffix(':') {
+                    prefixes.push((name.to_ascii_lowercase(), url.to_owned()));
+                }
+            }
+            scope.prefixes = Rc::new(prefixes);
+        }
+        scope
+    }
+
+    fn expand(&self, term: &str) -> String {
+        if let Some((prefix, reference)) = term.split_once(':')
+            && let Some((_, url)) = self
+                .prefixes
+                .iter()
+                .rev()
+                .find(|(name, _)| name.eq_ignore_ascii_case(prefix))
+        {
+            return format!("{url}{reference}");
+        }
+        expand(term, self.vocab.as_deref())
+    }
+}
+
+fn value(element: ElementRef<'_>, base: Option<&Url>) -> Value {
+    let value = element.value();
+    if let Some(content) = value.attr("content") {
+        return Value::Text(content.to_owned());
+    }
+    if let Some(url) = ["resource", "href", "src", "data"]
+        .iter()
+        .find_map(|attr| value.attr(attr))
+    {
+        return Value::Url(resolve(base, url));
+    }
+    if value.name() == "time"
+        && let Some(datetime) = value.attr("datetime")
+    {
+        return Value::Text(datetime.to_owned());
+    }
+    Value::Text(element.text().collect::<String>().trim().to_owned())
+}
+
+/// The properties of a parent item, and the indices in their values, where a nested item
+/// belongs.
+type Slots = Vec<(String, usize)>;
+
+/// Items in the order they start, with nested items held in place until they are complete.
+#[derive(Debug, Default)]
+struct Items {
+    items: Vec<Item>,
+    /// The parent of each nested item and where in the parent it belongs.
+    parents: Vec<Option<(usize, Slots)>>,
+}
+
+impl Items {
+    fn add(&mut self, item: Item, parent: Option<usize>, properties: &[&str]) -> usize {
+        let parent = parent.map(|parent| {
+            let slots = properties
+                .iter()
+                .map(|name| {
+                    let values = self.items[parent]
+                        .properties
+                        .entry((*name).to_owned())
+                        .or_default();
+                    values.push(Value::Text(String::new()));
+                    ((*name).to_owned(), values.len() - 1)
+                })
+                .collect();
+            (parent, slots)
+        });
+
+        self.items.push(item);
+        self.parents.push(parent);
+        self.items.len() - 1
+    }
+
+    /// Moves nested items into their parents and returns the top-level items.
+    fn finish(mut self) -> Vec<Item> {
+        // Items start after their parents, so nested items are complete when they are moved.
+        for index in (0..self.items.len()).rev() {
+            let Some((parent, slots)) = &self.parents[index] else {
+                continue;
+            };
+            let item = std::mem::replace(&mut self.items[index], Item::new(Syntax::Rdfa));
+            for (name, slot) in slots {
+                self.items[*parent].properties.get_mut(name).unwrap()[*slot] =
+                    Value::Item(item.clone());
+            }
+        }
+
+        self.items
+            .into_iter()
+            .zip(&self.parents)
+            .filter(|(_, parent)| parent.is_none())
+            .map(|(item, _)| item)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rdfa;
+    use crate::Html;
+    use crate::structured::Value;
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_owned())
+    }
+
+    #[test]
+    fn items_and_values() {
+        let html = Html::parse_fragment(
+            r#"<div vocab="https://schema.org/" typeof="Person" resource="urn:p">
+                <span property="name"> Ann </span>
+                <a property="url sameAs" href="https://ann.example">site</a>
+                <meta property="birthDate" content="1990-01-01">
+                <time property="updated" datetime="2024">last year</time>
+                <div property="address" typeof="PostalAddress">
+                    <span property="addressLocality">Oslo</span>
+                </div>
+                <span property="jobTitle">Engineer</span>
+            </div>
+            <p property="orphan">No item</p>
+            <div prefix="ex: https://example.com/ns#" typeof="ex:Thing Other"></div>"#,
+        );
+        let items = rdfa(&html, None);
+        assert_eq!(items.len(), 2);
+
+        let person = &items[0];
+        assert_eq!(person.types, ["https://schema.org/Person"]);
+        assert_eq!(person.id.as_deref(), Some("urn:p"));
+        assert_eq!(person.property("name"), Some(&text("Ann")));
+        let url = Value::Url("https://ann.example".to_owned());
+        assert_eq!(person.property("url"), Some(&url));
+        assert_eq!(person.property("sameAs"), Some(&url));
+        assert_eq!(person.property("birthDate"), Some(&text("1990-01-01")));
+        assert_eq!(person.property("updated"), Some(&text("2024")));
+        assert_eq!(person.property("jobTitle"), Some(&text("Engineer")));
+        assert!(!person.properties.contains_key("addressLocality"));
+
+        let address = person.property("address").unwrap().as_item().unwrap();
+        assert_eq!(address.types, ["https://schema.org/PostalAddress"]);
+        assert_eq!(address.property("addressLocality"), Some(&text("Oslo")));
+
+        assert_eq!(items[1].types, ["https://example.com/ns#Thing", "Other"]);
+    }
+}
@@ -0,0 +1,756 @@
+//! Tables as grids of cells.
+//!
+//! Requires the `table` feature.
+//!
+//! Implements the HTML [table processing model][model]: rows from `<thead>`, `<tbody>`s and
+//! `<tfoot>` in rendering order, cells spanning rows and columns with `rowspan` and `colspan`,
+//! and cells associated with their header cells by `scope`, by `headers` or by their position.
+//! Tables nested in cells are part of the text of those cells, not of the grid.
+//!
+//! [model]: https://html.spec.whatwg.org/multipage/tables.html#table-processing-model
+//!
+//! # Example
+//!
+//! ```
+//! use scraper::{Html, Selector};
+//! use scraper::table::Table;
+//!
+//! let html = Html::parse_fragment(
+//!     r#"<table>
+//!         <tr><th>Name</th><th>Language</th></tr>
+//!         <tr><td>scraper</td><td rowspan="2">Rust</td></tr>
+//!         <tr><td>ego-tree</td></tr>
+//!     </table>"#,
+//! );
+//! let element = html.select(&Selector::parse("table").unwrap()).next().unwrap();
+//! let table = Table::parse(element).unwrap();
+//!
+//! assert_eq!((table.width, table.height), (2, 3));
+//! assert_eq!(table.cell(1, 2).unwrap().text, "Rust");
+//! assert_eq!(table.records()[1]["Language"], "Rust");
+//! assert_eq!(table.to_csv(), "Name,Language\r\nscraper,Rust\r\nego-tree,Rust\r\n");
+//! ```
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::ElementRef;
+
+/// A table.
+#[derive(Debug, Clone)]
+pub struct Table<'a> {
+    /// The `<table>` element.
+    pub element: ElementRef<'a>,
+
+    /// The first `<caption>` child of the table.
+    pub caption: Option<ElementRef<'a>>,
+
+    /// The number of columns.
+    pub width: usize,
+
+    /// The number of rows.
+    pub height: usize,
+
+    /// The cells in the order they are processed, which is document order except that `<tfoot>`
+    /// rows come last.
+    pub cells: Vec<Cell<'a>>,
+
+    /// The index of the cell covering each slot, by row.
+    slots: Vec<Vec<Option<usize>>>,
+}
+
+/// A cell of a table.
+#[derive(Debug, Clone)]
+pub struct Cell<'a> {
+    /// The `<td>` or `<th>` element.
+    pub element: ElementRef<'a>,
+
+    /// Whether the cell is a `<th>`.
+    pub header: bool,
+
+    /// The column of the top left slot of the cell.
+    pub x: usize,
+
+    /// The row of the top left slot of the cell.
+    pub y: usize,
+
+    /// The number of columns the cell spans.
+    pub width: usize,
+
+    /// The number of rows the cell spans, which ends with its row group.
+    pub height: usize,
+
+    /// The text of the cell, with whitespace collapsed.
+    pub text: String,
+
+    /// The indices in [`Table::cells`] of the header cells of this cell.
+    pub headers: Vec<usize>,
+}
+
+/// The `scope` of a header cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Auto,
+    Row,
+    Col,
+    RowGroup,
+    ColGroup,
+}
+
+impl<'a> Table<'a> {
+    /// Forms the table of a `<table>` element, or returns `None` for any other element.
+    pub fn parse(element: ElementRef<'a>) -> Option<Table<'a>> {
+        if element.value().name() != "table" {
+            return None;
+        }
+
+        let mut builder = Builder {
+            table: Table {
+                element,
+                caption: None,
+                width: 0,
+                height: 0,
+                cells: Vec::new(),
+                slots: Vec::new(),
+            },
+            y: 0,
+            downward: Vec::new(),
+            row_groups: Vec::new(),
+            column_groups: Vec::new(),
+        };
+        builder.form();
+        let Builder {
+            mut table,
+            row_groups,
+            column_groups,
+            ..
+        } = builder;
+
+        for index in 0..table.cells.len() {
+            table.cells[index].headers = table.assign_headers(index, &row_groups, &column_groups);
+        }
+        Some(table)
+    }
+
+    /// Returns the cell covering a slot, if any.
+    pub fn cell(&self, x: usize, y: usize) -> Option<&Cell<'a>> {
+        let index = (*self.slots.get(y)?.get(x)?)?;
+        Some(&self.cells[index])
+    }
+
+    /// Returns the rows of the grid, with the cell covering each slot. A cell spanning several
+    /// slots appears in each of them.
+    pub fn rows(&self) -> impl Iterator<Item = Vec<Option<&Cell<'a>>>> + '_ {
+        (0..self.height).map(move |y| (0..self.width).map(|x| self.cell(x, y)).collect())
+    }
+
+    /// Returns the header cells of a cell.
+    pub fn headers<'s>(&'s self, cell: &'s Cell<'a>) -> impl Iterator<Item = &'s Cell<'a>> + 's {
+        cell.headers.iter().map(|index| &self.cells[*index])
+    }
+
+    /// Returns the key of each column: the text of its column header cells, joined with ` / `,
+    /// or the column number starting from 1 for columns without headers. Repeated keys get a
+    /// suffix such as ` (2)`.
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns = Vec::new();
+        for x in 0..self.width {
+            let mut texts = Vec::<&str>::new();
+            for y in 0..self.height {
+                if let Some(cell) = self.cell(x, y)
+                    && cell.header
+                    && self.is_column_header(cell)
+                    && !cell.text.is_empty()
+                    && texts.last() != Some(&cell.text.as_str())
+                {
+                    texts.push(&cell.text);
+                }
+            }
+            let key = if texts.is_empty() {
+                (x + 1).to_string()
+            } else {
+                texts.join(" / ")
+            };
+
+            let mut unique = key.clone();
+            let mut count = 1;
+            while columns.contains(&unique) {
+                count += 1;
+                unique = format!("{key} ({count})");
+            }
+            columns.push(unique);
+        }
+        columns
+    }
+
+    /// Returns a record for each row with a non-empty data cell, mapping the key of each column
+    /// from [`Table::columns`] to the text of the cell in that column, or an empty string for a
+    /// slot without a cell.
+    pub fn records(&self) -> Vec<BTreeMap<String, String>> {
+        let columns = self.columns();
+        self.rows()
+            .enumerate()
+            .filter(|(y, _)| !self.only_headers(*y..*y + 1, 0..self.width))
+            .map(|(_, row)| {
+                columns
+                    .iter()
+                    .zip(row)
+                    .map(|(key, cell)| {
+                        (
+                            key.clone(),
+                            cell.map(|cell| cell.text.clone()).unwrap_or_default(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the grid as CSV, with a cell spanning several slots repeated in each of them and
+    /// rows ending in CRLF.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in self.rows() {
+            for (x, cell) in row.iter().enumerate() {
+                if x > 0 {
+                    csv.push(',');
+                }
+                let text = cell.map_or("", |cell| cell.text.as_str());
+                if text.contains([',', '"', '\r', '\n']) {
+                    csv.push('"');
+                    csv.push_str(&text.replace('"', "\"\""));
+                    csv.push('"');
+                } else {
+                    csv.push_str(text);
+                }
+            }
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    fn scope(cell: &Cell<'_>) -> Scope {
+        if !cell.header {
+            return Scope::Auto;
+        }
+        let scope = cell.element.value().attr("scope").unwrap_or_default();
+        match scope.trim().to_ascii_lowercase().as_str() {
+            "row" => Scope::Row,
+            "col" => Scope::Col,
+            "rowgroup" => Scope::RowGroup,
+            "colgroup" => Scope::ColGroup,
+            _ => Scope::Auto,
+        }
+    }
+
+    /// Returns true if the cells covering the slots in a range of rows or columns are all header
+    /// cells.
+    ///
+    /// Empty data cells, such as the top left corner of a table with both row and column headers,
+    /// are not counted, unlike in the HTML standard.
+    fn only_headers(&self, rows: Range<usize>, columns: Range<usize>) -> bool {
+        rows.flat_map(|y| columns.clone().map(move |x| (x, y)))
+            .all(|(x, y)| {
+                self.cell(x, y)
+                    .is_none_or(|cell| cell.header || cell.text.is_empty())
+            })
+    }
+
+    fn is_column_header(&self, cell: &Cell<'_>) -> bool {
+        match Table::scope(cell) {
+            Scope::Col => true,
+            Scope::Auto => self.only_headers(cell.y..cell.y + cell.height, 0..self.width),
+            _ => false,
+        }
+    }
+
+    fn is_row_header(&self, cell: &Cell<'_>) -> bool {
+        match Table::scope(cell) {
+            Scope::Row => true,
+            Scope::Auto => {
+                !self.is_column_header(cell)
+                    && self.only_headers(0..self.height, cell.x..cell.x + cell.width)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the header cells of a cell, following the algorithm for assigning header cells.
+    fn assign_headers(
+        &self,
+        principal: usize,
+        row_groups: &[Range<usize>],
+        column_groups: &[Range<usize>],
+    ) -> Vec<usize> {
+        let cell = &self.cells[principal];
+        let mut headers = Vec::new();
+
+        if let Some(ids) = cell.element.value().attr("headers") {
+            for id in ids.split_ascii_whitespace() {
+                let header = self
+                    .cells
+                    .iter()
+                    .position(|cell| cell.element.value().id() == Some(id));
+                if let Some(header) = header
+                    && header != principal
+                    && !headers.contains(&header)
+                {
+                    headers.push(header);
+                }
+            }
+        } else {
+            for y in cell.y..cell.y + cell.height {
+                self.scan(principal, cell.x, y, Direction::Left, &mut headers);
+            }
+            for x in cell.x..cell.x + cell.width {
+                self.scan(principal, x, cell.y, Direction::Up, &mut headers);
+            }
+
+            let group_of = |groups: &[Range<usize>], position: usize| {
+                groups.iter().position(|group| group.contains(&position))
+            };
+            let row_group = group_of(row_groups, cell.y);
+            let column_group = group_of(column_groups, cell.x);
+            for (index, header) in self.cells.iter().enumerate() {
+                let in_group = match Table::scope(header) {
+                    Scope::RowGroup => {
+                        row_group.is_some() && group_of(row_groups, header.y) == row_group
+                    }
+                    Scope::ColGroup => {
+                        column_group.is_some() && group_of(column_groups, header.x) == column_group
+                    }
+                    _ => false,
+                };
+                if in_group
+                    && header.x < cell.x + cell.width
+                    && header.y < cell.y + cell.height
+                    && !headers.contains(&index)
+                {
+                    headers.push(index);
+                }
+            }
+        }
+
+        headers.retain(|&header| header != principal && !self.cells[header].text.is_empty());
+        headers
+    }
+
+    /// Scans from a slot of a cell towards the start of its row or column for header cells, which
+    /// are blocked by the headers of earlier header blocks with the same extent.
+    fn scan(
+        &self,
+        principal: usize,
+        x: usize,
+        y: usize,
+        direction: Direction,
+        headers: &mut Vec<usize>,
+    ) {
+        let mut in_header_block = self.cells[principal].header;
+        let mut block = if in_header_block {
+            vec![principal]
+        } else {
+            Vec::new()
+        };
+        let mut opaque = Vec::new();
+
+        let (mut x, mut y) = (x, y);
+        loop {
+            match direction {
+                Direction::Left if x > 0 => x -= 1,
+                Direction::Up if y > 0 => y -= 1,
+                _ => return,
+            }
+            let Some(index) = self.slots[y][x] else {
+                continue;
+            };
+            let current = &self.cells[index];
+
+            if !current.header {
+                if in_header_block {
+                    in_header_block = false;
+                    opaque.append(&mut block);
+                }
+                continue;
+            }
+
+            in_header_block = true;
+            block.push(index);
+            let blocked = match direction {
+                Direction::Up => {
+                    opaque.iter().any(|&other: &usize| {
+                        let other = &self.cells[other];
+                        other.x == current.x && other.width == current.width
+                    }) || !self.is_column_header(current)
+                }
+                Direction::Left => {
+                    opaque.iter().any(|&other: &usize| {
+                        let other = &self.cells[other];
+                        other.y == current.y && other.height == current.height
+                    }) || !self.is_row_header(current)
+                }
+            };
+            if !blocked && !headers.contains(&index) {
+                headers.push(index);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Left,
+    Up,
+}
+
+/// The state of the algorithm for forming a table.
+#[derive(Debug)]
+struct Builder<'a> {
+    table: Table<'a>,
+    /// The current row.
+    y: usize,
+    /// The cells with `rowspan="0"`, which grow to the end of their row group.
+    downward: Vec<usize>,
+    row_groups: Vec<Range<usize>>,
+    column_groups: Vec<Range<usize>>,
+}
+
+impl<'a> Builder<'a> {
+    fn form(&mut self) {
+        let children = self.table.element.child_elements().collect::<Vec<_>>();
+        let mut pending_footers = Vec::new();
+        let mut rows_started = false;
+
+        // The number of rows from each child to the end of the row group of the `<tr>` children,
+        // which is ended by the next `<thead>` or `<tbody>`.
+        let mut remaining = vec![0; children.len()];
+        let mut count = 0;
+        for (index, child) in children.iter().enumerate().rev() {
+            match child.value().name() {
+                "tr" => count += 1,
+                "thead" | "tbody" => count = 0,
+                _ => {}
+            }
+            remaining[index] = count;
+        }
+
+        for (child, remaining) in children.into_iter().zip(remaining) {
+            match child.value().name() {
+                "caption" if self.table.caption.is_none() => self.table.caption = Some(child),
+                "colgroup" if !rows_started => self.column_group(child),
+                "tr" => {
+                    rows_started = true;
+                    self.row(child, remaining);
+                }
+                "thead" | "tbody" => {
+                    rows_started = true;
+                    self.end_row_group();
+                    self.row_group(child);
+                }
+                "tfoot" => {
+                    rows_started = true;
+                    pending_footers.push(child);
+                }
+                _ => {}
+            }
+        }
+
+        self.end_row_group();
+        for footer in pending_footers {
+            self.row_group(footer);
+        }
+    }
+
+    fn column_group(&mut self, colgroup: ElementRef<'a>) {
+        let start = self.table.width;
+        let cols = colgroup
+            .child_elements()
+            .filter(|child| child.value().name() == "col")
+            .collect::<Vec<_>>();
+        if cols.is_empty() {
+            self.table.width += span(colgroup, "span", 1, 1000);
+        } else {
+            for col in cols {
+                self.table.width += span(col, "span", 1, 1000);
+            }
+        }
+        self.column_groups.push(start..self.table.width);
+        self.ensure_slots();
+    }
+
+    fn row_group(&mut self, group: ElementRef<'a>) {
+        let start = self.table.height;
+        let rows = group
+            .child_elements()
+            .filter(|child| child.value().name() == "tr")
+            .collect::<Vec<_>>();
+        let count = rows.len();
+        for (index, row) in rows.into_iter().enumerate() {
+            self.row(row, count - index);
+        }
+        self.end_row_group();
+        if self.table.height > start {
+            self.row_groups.push(start..self.table.height);
+        }
+    }
+
+    fn end_row_group(&mut self) {
+        while self.y < self.table.height {
+            self.grow_downward();
+            self.y += 1;
+        }
+        self.downward.clear();
+    }
+
+    /// Adds a row, with `remaining` rows from it to the end of its row group.
+    fn row(&mut self, row: ElementRef<'a>, remaining: usize) {
+        if self.table.height == self.y {
+            self.table.height += 1;
+            self.ensure_slots();
+        }
+        let mut x = 0;
+        self.grow_downward();
+
+        for element in row.child_elements() {
+            let header = match element.value().name() {
+                "td" => false,
+                "th" => true,
+                _ => continue,
+            };
+            while x < self.table.width && self.table.slots[self.y][x].is_some() {
+                x += 1;
+            }
+            if x == self.table.width {
+                self.table.width += 1;
+            }
+
+            let width = span(element, "colspan", 1, 1000);
+            // Like browsers, and unlike the HTML standard, cells do not span rows past the end of
+            // their row group, so that the grid stays as large as the markup.
+            let mut height = span(element, "rowspan", 0, 65534).min(remaining);
+            let grows_downward = height == 0;
+            if grows_downward {
+                height = 1;
+            }
+            self.table.width = self.table.width.max(x + width);
+            self.table.height = self.table.height.max(self.y + height);
+            self.ensure_slots();
+
+            let index = self.table.cells.len();
+            let text = element.text().collect::<String>();
+            self.table.cells.push(Cell {
+                element,
+                header,
+                x,
+                y: self.y,
+                width,
+                height,
+                text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+                headers: Vec::new(),
+            });
+            self.cover(index);
+            if grows_downward {
+                self.downward.push(index);
+            }
+            x += width;
+        }
+
+        self.y += 1;
+    }
+
+    /// Extends the cells growing downward to the current row.
+    fn grow_downward(&mut self) {
+        for index in self.downward.clone() {
+            let cell = &mut self.table.cells[index];
+            cell.height = self.y - cell.y + 1;
+            self.cover(index);
+        }
+    }
+
+    /// Assigns the slots of a cell that are not already covered by another cell.
+    fn cover(&mut self, index: usize) {
+        let cell = &self.table.cells[index];
+        for row in &mut self.table.slots[cell.y..cell.y + cell.height] {
+            for slot in &mut row[cell.x..cell.x + cell.width] {
+                slot.get_or_insert(index);
+            }
+        }
+    }
+
+    fn ensure_slots(&mut self) {
+        let (width, height) = (self.table.width, self.table.height);
+        self.table.slots.resize_with(height, Vec::new);
+        for row in &mut self.table.slots {
+            row.resize(width, None);
+        }
+    }
+}
+
+/// Parses a span attribute as a non-negative integer, clamped to `min..=max`, with 1 for a
+/// missing or invalid value.
+fn span(element: ElementRef<'_>, attr: &str, min: usize, max: usize) -> usize {
+    let value = element.value().attr(attr).unwrap_or_default().trim_start();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(value, |end| &value[..end]);
+    match digits.parse::<usize>() {
+        Ok(span) => span.clamp(min, max),
+        Err(_) if !digits.is_empty() => max,
+        Err(_) => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+    use crate::{Html, Selector};
+
+    fn table(html: &Html) -> Table<'_> {
+        let selector = Selector::parse("table").unwrap();
+        Table::parse(html.select(&selector).next().unwrap()).unwrap()
+    }
+
+    fn grid(table: &Table<'_>) -> Vec<Vec<String>> {
+        table
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.map_or("-".to_owned(), |cell| cell.text.clone()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spans_and_row_groups() {
+        let html = Html::parse_fragment(
+            r#"<table>
+                <caption>Caption</caption>
+                <tfoot><tr><td colspan="3">Foot</td></tr></tfoot>
+                <thead><tr><th>A</th><th colspan="2">B</th></tr></thead>
+                <tbody>
+                    <tr><td rowspan="0">1</td><td>2</td><td rowspan="3">3</td></tr>
+                    <tr><td>4<table><tr><td>nested</td></tr></table></td></tr>
+                </tbody>
+                <tbody><tr><td>5</td><td colspan="0">6</td></tr></tbody>
+            </table>"#,
+        );
+        let table = table(&html);
+
+        assert_eq!(table.caption.unwrap().inner_html(), "Caption");
+        assert_eq!((table.width, table.height), (3, 5));
+        assert_eq!(
+            grid(&table),
+            [
+                ["A", "B", "B"],
+                ["1", "2", "3"],
+                ["1", "4nested", "3"],
+                ["5", "6", "-"],
+                ["Foot", "Foot", "Foot"],
+            ]
+        );
+        assert_eq!(table.cell(0, 2).unwrap().height, 2);
+        assert_eq!(table.cell(2, 2).unwrap().height, 2);
+        assert_eq!(table.cells.last().unwrap().text, "Foot");
+        assert!(table.cell(3, 0).is_none());
+    }
+
+    #[test]
+    fn row_spans_end_with_their_row_group() {
+        let html = Html::parse_fragment(
+            r#"<table>
+                <tr><td rowspan="65534" colspan="1000">a</td></tr>
+                <tr><td rowspan="5">b</td></tr>
+                <tbody><tr><td>c</td></tr></tbody>
+            </table>"#,
+        );
+        let table = table(&html);
+
+        assert_eq!((table.width, table.height), (1001, 3));
+        assert_eq!(table.cells[0].height, 2);
+        assert_eq!(table.cell(1000, 1).unwrap().height, 1);
+        assert_eq!(table.cell(0, 2).unwrap().text, "c");
+    }
+
+    #[test]
+    fn header_scanning() {
+        let html = Html::parse_fragment(
+            r#"<table>
+                <thead><tr><td></td><th>Q1</th><th>Q2</th></tr></thead>
+                <tbody>
+                    <tr><th scope="rowgroup" colspan="3">North</th></tr>
+                    <tr><th>Oslo</th><td>1</td><td>2</td></tr>
+                </tbody>
+                <tbody>
+                    <tr><th>Bergen</th><td id="x">3</td><td headers="x  b">4</td></tr>
+                </tbody>
+                <tfoot><tr><th id="b">Total</th><td>5</td><td></td></tr></tfoot>
+            </table>"#,
+        );
+        let table = table(&html);
+        let headers = |x, y| {
+            let cell = table.cell(x, y).unwrap();
+            table
+                .headers(cell)
+                .map(|header| header.text.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(headers(1, 2), ["Oslo", "Q1", "North"]);
+        assert_eq!(headers(2, 2), ["Oslo", "Q2", "North"]);
+        assert_eq!(headers(1, 3), ["Bergen", "Q1"]);
+        assert_eq!(headers(2, 3), ["3", "Total"]);
+        assert_eq!(headers(0, 2), ["North"]);
+        assert!(headers(1, 0).is_empty());
+    }
+
+    #[test]
+    fn scope_and_blocking() {
+        let html = Html::parse_fragment(
+            r#"<table>
+                <tr><th scope="col">Name</th><th scope="col">Score</th></tr>
+                <tr><th scope="row">Ann</th><td>1</td></tr>
+                <tr><th scope="col">Name</th><th scope="col">Time</th></tr>
+                <tr><th scope="row">Bob</th><td>2</td></tr>
+            </table>"#,
+        );
+        let table = table(&html);
+        let cell = table.cell(1, 3).unwrap();
+        let headers = table
+            .headers(cell)
+            .map(|header| (header.x, header.y))
+            .collect::<Vec<_>>();
+        assert_eq!(headers, [(0, 3), (1, 2)]);
+    }
+
+    #[test]
+    fn records_and_csv() {
+        let html = Html::parse_fragment(
+            r#"<table>
+                <tr><th>Name</th><th>Note</th><th>Note</th><td></td></tr>
+                <tr><td>Ann</td><td>says "hi", twice</td><td>x</td><td>y</td></tr>
+                <tr><td colspan="2">Bob</td></tr>
+            </table>"#,
+        );
+        let table = table(&html);
+        assert_eq!(table.columns(), ["Name", "Note", "Note (2)", "4"]);
+
+        let records = table.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["Note"], "says \"hi\", twice");
+        assert_eq!(records[0]["4"], "y");
+        assert_eq!(records[1]["Note"], "Bob");
+        assert_eq!(records[1]["Note (2)"], "");
+
+        assert_eq!(
+            table.to_csv(),
+            "Name,Note,Note,\r\nAnn,\"says \"\"hi\"\", twice\",x,y\r\nBob,Bob,,\r\n"
+        );
+    }
+
+    #[test]
+    fn not_a_table() {
+        let html = Html::parse_fragment("<p>text</p>");
+        let p = html.select(&Selector::parse("p").unwrap()).next().unwrap();
+        assert!(Table::parse(p).is_none());
+    }
+}